@@ -0,0 +1,22 @@
+use thiserror::Error;
+
+/// CML error
+#[derive(Clone, Error, Debug)]
+pub enum Error {
+    #[error("malformed markup {0:?}")]
+    Markup(String),
+    #[error("molecule not found")]
+    MoleculeNotFound,
+    #[error("missing attribute {0:?}")]
+    Attribute(&'static str),
+    #[error("invalid number {0:?}")]
+    Number(String),
+    #[error("unknown element type {0:?}")]
+    Element(String),
+    #[error("unknown atom reference {0:?}")]
+    Reference(String),
+    #[error("unsupported bond order {0:?}")]
+    Order(String),
+    #[error("invalid stereo {0:?}")]
+    Stereo(String),
+}
@@ -0,0 +1,690 @@
+//! Chemical Markup Language
+//!
+//! [CML schema](http://www.xml-cml.org/schema/schema3/)
+
+pub use self::error::Error;
+
+use self::xml::{Event, Reader};
+use crate::{
+    errors,
+    semantic::{Atom, Bond, Element, Geometry, MoleculeGraph, Parity},
+};
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+};
+
+impl MoleculeGraph {
+    /// Reads the first molecule of a CML document
+    ///
+    /// Atoms and bonds are read from both the child element and the array
+    /// attribute forms of `atomArray` and `bondArray`. Stereo is read from
+    /// `atomParity` and the cis/trans `bondStereo`.
+    pub fn from_cml(s: &str) -> errors::Result<Self> {
+        let mut reader = Reader::new(s);
+        let mut builder = Builder::default();
+        let (mut depth, mut found) = (0, false);
+        while let Some(event) = reader.next() {
+            let error = |source| errors::Error::Cml {
+                line: reader.line,
+                source,
+            };
+            match event.map_err(error)? {
+                Event::Start {
+                    name: "molecule",
+                    empty,
+                    ..
+                } => {
+                    depth += 1;
+                    found = true;
+                    if empty && depth == 1 {
+                        break;
+                    }
+                }
+                Event::Start {
+                    name,
+                    attributes,
+                    empty,
+                } if depth > 0 => {
+                    builder
+                        .start(name, &attributes, reader.line)
+                        .map_err(error)?;
+                    if empty {
+                        builder.end(name);
+                    }
+                }
+                Event::End("molecule") => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                Event::End(name) => builder.end(name),
+                Event::Text(text) => builder.text(&text),
+                _ => {}
+            }
+        }
+        if !found {
+            return Err(errors::Error::Cml {
+                line: reader.line,
+                source: Error::MoleculeNotFound,
+            });
+        }
+        builder.finish()
+    }
+
+    /// CML document of the molecule
+    pub fn cml(&self) -> impl Display + '_ {
+        Cml(self)
+    }
+}
+
+/// Graph under construction, stereo resolved once all atoms and bonds are
+/// read
+#[derive(Default)]
+struct Builder {
+    graph: MoleculeGraph,
+    ids: BTreeMap<String, NodeIndex>,
+    hydrogens: Vec<Option<u8>>,
+    stereo: Vec<Stereo>,
+    /// Atom or bond of the element being read
+    atom: Option<NodeIndex>,
+    bond: Option<EdgeIndex>,
+    /// Reading the text of a stereo element
+    text: bool,
+}
+
+/// Stereo element with its line
+struct Stereo {
+    line: usize,
+    kind: Kind,
+    references: Vec<String>,
+    value: String,
+}
+
+enum Kind {
+    Atom(NodeIndex),
+    Bond(EdgeIndex),
+}
+
+impl Builder {
+    fn start(
+        &mut self,
+        name: &str,
+        attributes: &[(&str, String)],
+        line: usize,
+    ) -> Result<(), Error> {
+        let attribute = |key: &str| {
+            attributes
+                .iter()
+                .find(|&&(name, _)| name == key)
+                .map(|(_, value)| value.as_str())
+        };
+        match name {
+            "atomArray" => {
+                // Array form, the values by atom
+                let Some(ids) = attribute("atomID") else {
+                    return Ok(());
+                };
+                let column = |key: &str| -> Vec<&str> {
+                    attribute(key)
+                        .map_or_else(Vec::new, |values| values.split_whitespace().collect())
+                };
+                let keys = [
+                    "elementType",
+                    "formalCharge",
+                    "isotopeNumber",
+                    "hydrogenCount",
+                    "x2",
+                    "y2",
+                    "x3",
+                    "y3",
+                    "z3",
+                ];
+                let columns = keys.map(column);
+                for (index, id) in ids.split_whitespace().enumerate() {
+                    let attributes: Vec<_> = keys
+                        .iter()
+                        .zip(&columns)
+                        .filter_map(|(&key, values)| Some((key, values.get(index)?.to_string())))
+                        .chain([("id", id.to_owned())])
+                        .collect();
+                    self.atom(&attributes)?;
+                }
+            }
+            "atom" => self.atom(attributes)?,
+            "bondArray" => {
+                let (Some(first), Some(second)) = (attribute("atomRef1"), attribute("atomRef2"))
+                else {
+                    return Ok(());
+                };
+                let orders: Vec<_> = attribute("order")
+                    .unwrap_or_default()
+                    .split_whitespace()
+                    .collect();
+                let pairs = first.split_whitespace().zip(second.split_whitespace());
+                for (index, (first, second)) in pairs.enumerate() {
+                    let order = orders.get(index).copied().unwrap_or("1");
+                    self.bond(first, second, order)?;
+                }
+            }
+            "bond" => {
+                let references = attribute("atomRefs2").ok_or(Error::Attribute("atomRefs2"))?;
+                let [first, second] = references.split_whitespace().collect::<Vec<_>>()[..] else {
+                    return Err(Error::Reference(references.to_owned()));
+                };
+                self.bond(first, second, attribute("order").unwrap_or("1"))?;
+            }
+            "atomParity" | "bondStereo" => {
+                let kind = match (name, self.atom, self.bond) {
+                    ("atomParity", Some(atom), _) => Kind::Atom(atom),
+                    ("bondStereo", _, Some(bond)) => Kind::Bond(bond),
+                    _ => return Ok(()),
+                };
+                self.stereo.push(Stereo {
+                    line,
+                    kind,
+                    references: attribute("atomRefs4")
+                        .unwrap_or_default()
+                        .split_whitespace()
+                        .map(ToOwned::to_owned)
+                        .collect(),
+                    value: String::new(),
+                });
+                self.text = true;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn atom(&mut self, attributes: &[(&str, String)]) -> Result<(), Error> {
+        let attribute = |key: &str| {
+            attributes
+                .iter()
+                .find(|&&(name, _)| name == key)
+                .map(|(_, value)| value.as_str())
+        };
+        let id = attribute("id").ok_or(Error::Attribute("id"))?;
+        let element = attribute("elementType").ok_or(Error::Attribute("elementType"))?;
+        let element = match element {
+            "R" | "Du" | "*" => None,
+            symbol => Some(
+                symbol
+                    .parse::<Element>()
+                    .map_err(|_| Error::Element(symbol.to_owned()))?,
+            ),
+        };
+        let coordinates = match (attribute("x3"), attribute("y3"), attribute("z3")) {
+            (Some(x), Some(y), Some(z)) => Some([number(x)?, number(y)?, number(z)?]),
+            _ => match (attribute("x2"), attribute("y2")) {
+                (Some(x), Some(y)) => Some([number(x)?, number(y)?, 0.0]),
+                _ => None,
+            },
+        };
+        let index = self.graph.add_node(Atom {
+            element,
+            isotope: attribute("isotopeNumber").map(number).transpose()?,
+            charge: attribute("formalCharge")
+                .map(number)
+                .transpose()?
+                .unwrap_or(0),
+            coordinates,
+            ..Default::default()
+        });
+        self.hydrogens
+            .push(attribute("hydrogenCount").map(number).transpose()?);
+        self.ids.insert(id.to_owned(), index);
+        self.atom = Some(index);
+        Ok(())
+    }
+
+    fn bond(&mut self, first: &str, second: &str, order: &str) -> Result<(), Error> {
+        let bond = match order {
+            "1" | "S" => Bond::Single,
+            "2" | "D" => Bond::Double,
+            "3" | "T" => Bond::Triple,
+            "4" => Bond::Quadruple,
+            "A" => Bond::Aromatic,
+            order => return Err(Error::Order(order.to_owned())),
+        };
+        let (first, second) = (self.reference(first)?, self.reference(second)?);
+        self.bond = Some(self.graph.add_edge(first, second, bond));
+        Ok(())
+    }
+
+    fn reference(&self, id: &str) -> Result<NodeIndex, Error> {
+        self.ids
+            .get(id)
+            .copied()
+            .ok_or_else(|| Error::Reference(id.to_owned()))
+    }
+
+    fn end(&mut self, name: &str) {
+        match name {
+            "atom" => self.atom = None,
+            "bond" => self.bond = None,
+            "atomParity" | "bondStereo" => self.text = false,
+            _ => {}
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if let Some(stereo) = self.stereo.last_mut().filter(|_| self.text) {
+            stereo.value += text;
+        }
+    }
+
+    fn finish(mut self) -> errors::Result<MoleculeGraph> {
+        let graph = &mut self.graph;
+        for (index, hydrogens) in self.hydrogens.into_iter().enumerate() {
+            let index = NodeIndex::new(index);
+            // The hydrogen count includes the explicit hydrogen atoms
+            let hydrogens =
+                hydrogens.map(|hydrogens| hydrogens.saturating_sub(explicit(graph, index)));
+            let atom = graph[index];
+            let implicit = atom.element.is_some_and(|element| element.is_organic())
+                && atom.charge == 0
+                && hydrogens.is_none_or(|hydrogens| hydrogens == graph.implicit_hydrogens(index));
+            graph[index].hydrogens = if implicit {
+                None
+            } else {
+                Some(hydrogens.unwrap_or_default())
+            };
+        }
+        for stereo in self.stereo {
+            let error = |source| errors::Error::Cml {
+                line: stereo.line,
+                source,
+            };
+            let references = stereo
+                .references
+                .iter()
+                .map(|id| {
+                    self.ids
+                        .get(id)
+                        .copied()
+                        .ok_or_else(|| error(Error::Reference(id.clone())))
+                })
+                .collect::<errors::Result<Vec<_>>>()?;
+            let value = stereo.value.trim();
+            let invalid = || error(Error::Stereo(stereo.references.join(" ")));
+            match (stereo.kind, &references[..]) {
+                (Kind::Atom(index), [_, _, _, _]) => {
+                    // Neighbours, the center standing for its implicit hydrogen
+                    let mut distinct = references.clone();
+                    distinct.sort();
+                    distinct.dedup();
+                    if distinct.len() != 4
+                        || references.iter().any(|&reference| {
+                            reference != index && graph.find_edge(index, reference).is_none()
+                        })
+                    {
+                        return Err(invalid());
+                    }
+                    let sign: f64 = value
+                        .parse()
+                        .map_err(|_| error(Error::Number(value.to_owned())))?;
+                    let parity = if sign > 0.0 {
+                        Parity::Clockwise
+                    } else if sign < 0.0 {
+                        Parity::Counterclockwise
+                    } else {
+                        continue;
+                    };
+                    graph.set_parity(index, &references, Some(parity));
+                }
+                (Kind::Bond(bond), &[x, a, b, y]) => {
+                    // On its double bond
+                    let bonded = [(x, a), (b, y)]
+                        .iter()
+                        .all(|&(first, second)| graph.find_edge(first, second).is_some());
+                    if !bonded
+                        || graph.find_edge(a, b) != Some(bond)
+                        || graph[bond] != Bond::Double
+                        || x == b
+                        || y == a
+                    {
+                        return Err(invalid());
+                    }
+                    let geometry = match value {
+                        "C" => Geometry::Z,
+                        "T" => Geometry::E,
+                        _ => return Err(error(Error::Stereo(value.to_owned()))),
+                    };
+                    graph.set_geometry(x, a, b, y, geometry);
+                }
+                // Wedges and stereo without references
+                (Kind::Bond(_), []) => {}
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(self.graph)
+    }
+}
+
+/// Hydrogen atom neighbours
+fn explicit(graph: &MoleculeGraph, index: NodeIndex) -> u8 {
+    graph
+        .neighbors(index)
+        .filter(|&neighbour| graph[neighbour].element == Some(Element::H))
+        .count() as _
+}
+
+fn number<T: std::str::FromStr>(text: &str) -> Result<T, Error> {
+    text.parse().map_err(|_| Error::Number(text.to_owned()))
+}
+
+/// CML writer
+struct Cml<'a>(&'a MoleculeGraph);
+
+impl Display for Cml<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let graph = self.0;
+        let id = |index: NodeIndex| format!("a{}", index.index() + 1);
+        let three = graph.node_weights().any(|atom| {
+            atom.coordinates
+                .is_some_and(|coordinates| coordinates[2] != 0.0)
+        });
+        writeln!(f, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(f, r#"<molecule xmlns="http://www.xml-cml.org/schema">"#)?;
+        writeln!(f, "  <atomArray>")?;
+        for index in graph.node_indices() {
+            let atom = &graph[index];
+            write!(f, r#"    <atom id="{}""#, id(index))?;
+            match atom.element {
+                Some(element) => write!(f, r#" elementType="{element}""#)?,
+                None => write!(f, r#" elementType="R""#)?,
+            }
+            if atom.charge != 0 {
+                write!(f, r#" formalCharge="{}""#, atom.charge)?;
+            }
+            if let Some(isotope) = atom.isotope {
+                write!(f, r#" isotopeNumber="{isotope}""#)?;
+            }
+            write!(
+                f,
+                r#" hydrogenCount="{}""#,
+                graph.hydrogens(index) + explicit(graph, index)
+            )?;
+            if let Some([x, y, z]) = atom.coordinates {
+                if three {
+                    write!(f, r#" x3="{x:.4}" y3="{y:.4}" z3="{z:.4}""#)?;
+                } else {
+                    write!(f, r#" x2="{x:.4}" y2="{y:.4}""#)?;
+                }
+            }
+            // Neighbours in the order the stored parity refers to
+            let mut order: Vec<_> = graph.neighbors(index).collect();
+            if graph.hydrogens(index) > 0 {
+                order.push(index);
+            }
+            order.sort();
+            match graph.parity(index, &order).filter(|_| order.len() == 4) {
+                Some(parity) => {
+                    let references: Vec<_> = order.into_iter().map(id).collect();
+                    let sign = match parity {
+                        Parity::Clockwise => 1,
+                        Parity::Counterclockwise => -1,
+                    };
+                    writeln!(f, ">")?;
+                    writeln!(
+                        f,
+                        r#"      <atomParity atomRefs4="{}">{sign}</atomParity>"#,
+                        references.join(" ")
+                    )?;
+                    writeln!(f, "    </atom>")?;
+                }
+                None => writeln!(f, "/>")?,
+            }
+        }
+        writeln!(f, "  </atomArray>")?;
+        if graph.edge_count() > 0 {
+            writeln!(f, "  <bondArray>")?;
+            for edge in graph.edge_indices() {
+                let (a, b) = graph.edge_endpoints(edge).unwrap();
+                let order = match graph[edge] {
+                    Bond::Single | Bond::Up | Bond::Down => "1",
+                    Bond::Double => "2",
+                    Bond::Triple => "3",
+                    Bond::Quadruple => "4",
+                    Bond::Aromatic => "A",
+                };
+                write!(
+                    f,
+                    r#"    <bond id="b{}" atomRefs2="{} {}" order="{order}""#,
+                    edge.index() + 1,
+                    id(a),
+                    id(b)
+                )?;
+                let reference = |center: NodeIndex, partner: NodeIndex| {
+                    graph
+                        .neighbors(center)
+                        .filter(|&other| other != partner)
+                        .min()
+                };
+                let geometry = match (graph[edge], reference(a, b), reference(b, a)) {
+                    (Bond::Double, Some(x), Some(y)) => {
+                        graph.geometry(x, a, b, y).map(|geometry| (x, y, geometry))
+                    }
+                    _ => None,
+                };
+                match geometry {
+                    Some((x, y, geometry)) => {
+                        let value = match geometry {
+                            Geometry::Z => "C",
+                            Geometry::E => "T",
+                        };
+                        writeln!(f, ">")?;
+                        writeln!(
+                            f,
+                            r#"      <bondStereo atomRefs4="{} {} {} {}">{value}</bondStereo>"#,
+                            id(x),
+                            id(a),
+                            id(b),
+                            id(y)
+                        )?;
+                        writeln!(f, "    </bond>")?;
+                    }
+                    None => writeln!(f, "/>")?,
+                }
+            }
+            writeln!(f, "  </bondArray>")?;
+        }
+        writeln!(f, "</molecule>")
+    }
+}
+
+mod error;
+mod xml;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ALANINE: &str = r#"<?xml version="1.0"?>
+<!-- L-alanine -->
+<cml:cml xmlns:cml="http://www.xml-cml.org/schema">
+  <cml:molecule id="alanine">
+    <cml:atomArray>
+      <cml:atom id="a1" elementType="N" hydrogenCount="2"/>
+      <cml:atom id="a2" elementType="C" hydrogenCount="1">
+        <cml:atomParity atomRefs4="a1 a3 a4 a2">1</cml:atomParity>
+      </cml:atom>
+      <cml:atom id="a3" elementType="C" hydrogenCount="3"/>
+      <cml:atom id="a4" elementType="C"/>
+      <cml:atom id="a5" elementType="O"/>
+      <cml:atom id="a6" elementType="O" formalCharge="-1"/>
+    </cml:atomArray>
+    <cml:bondArray>
+      <cml:bond atomRefs2="a1 a2" order="S"/>
+      <cml:bond atomRefs2="a2 a3" order="S"/>
+      <cml:bond atomRefs2="a2 a4" order="S"/>
+      <cml:bond atomRefs2="a4 a5" order="D"/>
+      <cml:bond atomRefs2="a4 a6" order="S"/>
+    </cml:bondArray>
+  </cml:molecule>
+</cml:cml>
+"#;
+
+    fn canonical(smiles: &str) -> String {
+        smiles.parse::<MoleculeGraph>().unwrap().canonical_smiles()
+    }
+
+    #[test]
+    fn read() {
+        let alanine = MoleculeGraph::from_cml(ALANINE).unwrap();
+        assert_eq!(alanine.canonical_smiles(), canonical("N[C@@H](C)C(=O)[O-]"));
+        let inverted = ALANINE.replace(">1</cml:atomParity>", ">-1</cml:atomParity>");
+        let inverted = MoleculeGraph::from_cml(&inverted).unwrap();
+        assert_eq!(inverted.canonical_smiles(), canonical("N[C@H](C)C(=O)[O-]"));
+        // Array form
+        let ethanol = r#"<molecule>
+  <atomArray atomID="a1 a2 a3" elementType="C C O" isotopeNumber="13 12 18"/>
+  <bondArray atomRef1="a1 a2" atomRef2="a2 a3" order="1 1"/>
+</molecule>"#;
+        let ethanol = MoleculeGraph::from_cml(ethanol).unwrap();
+        assert_eq!(ethanol.to_string(), "[13CH3][12CH2][18OH]");
+        // Cis/trans
+        let butene = r#"<molecule>
+  <atomArray>
+    <atom id="a1" elementType="C"/><atom id="a2" elementType="C"/>
+    <atom id="a3" elementType="C"/><atom id="a4" elementType="C"/>
+  </atomArray>
+  <bondArray>
+    <bond atomRefs2="a1 a2" order="1"/>
+    <bond atomRefs2="a2 a3" order="2">
+      <bondStereo atomRefs4="a1 a2 a3 a4">T</bondStereo>
+    </bond>
+    <bond atomRefs2="a3 a4" order="1"/>
+  </bondArray>
+</molecule>"#;
+        let butene = MoleculeGraph::from_cml(butene).unwrap();
+        assert_eq!(butene.canonical_smiles(), canonical("C/C=C/C"));
+        // Hydrogen counts including the explicit hydrogens
+        let methanol = r#"<molecule>
+  <atomArray>
+    <atom id="a1" elementType="C" hydrogenCount="3"/>
+    <atom id="a2" elementType="O" hydrogenCount="1"/>
+    <atom id="a3" elementType="H"/><atom id="a4" elementType="H"/>
+    <atom id="a5" elementType="H"/><atom id="a6" elementType="H"/>
+  </atomArray>
+  <bondArray atomRef1="a1 a1 a1 a1 a2" atomRef2="a2 a3 a4 a5 a6" order="1 1 1 1 1"/>
+</molecule>"#;
+        let methanol = MoleculeGraph::from_cml(methanol).unwrap();
+        assert_eq!(methanol.hydrogens(NodeIndex::new(0)), 0);
+        let mass = methanol.monoisotopic_mass().unwrap();
+        assert!((mass - 32.026).abs() < 1e-3, "{mass}");
+        let read = MoleculeGraph::from_cml(&methanol.cml().to_string()).unwrap();
+        assert_eq!(read.canonical_smiles(), methanol.canonical_smiles());
+    }
+
+    #[test]
+    fn write() {
+        let graph: MoleculeGraph = "[13CH3][C@@H](N)C(=O)[O-]".parse().unwrap();
+        let cml = graph.cml().to_string();
+        assert!(
+            cml.contains(r#"<atom id="a1" elementType="C" isotopeNumber="13" hydrogenCount="3"/>"#)
+        );
+        assert!(cml.contains(r#"formalCharge="-1""#));
+        assert!(cml.contains("<atomParity atomRefs4="));
+        assert!(cml.contains(r#"atomRefs2="a1 a2" order="1"/>"#));
+    }
+
+    #[test]
+    fn round_trip() {
+        for smiles in [
+            "N[C@@H](C)C(=O)O",
+            "N[C@H](C)C(=O)O",
+            "C/C=C/C",
+            "F/C=C\\F",
+            "CCCCCCCC/C=C\\CCCCCCCC(=O)O",
+            "[NH4+]",
+            "[13CH4]",
+            "[O-]C(=O)CC[NH3+]",
+            "C1CC[C@H](O)CC1",
+            "CC(C)[C@@]1(O)CC[C@H](C)C[C@@H]1O",
+            "C#N",
+            "*CC",
+            "CCCCCCCCCCCCCCCC(=O)OC[C@@H](OC(=O)CCCCCCC/C=C\\CCCCCCCC)COP(=O)([O-])OCC[N+](C)(C)C",
+        ] {
+            let graph: MoleculeGraph = smiles.parse().unwrap();
+            let read = MoleculeGraph::from_cml(&graph.cml().to_string()).unwrap();
+            assert_eq!(
+                read.canonical_smiles(),
+                graph.canonical_smiles(),
+                "{smiles}"
+            );
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let error = |cml: &str| match MoleculeGraph::from_cml(cml) {
+            Err(errors::Error::Cml { line, source }) => (line, source.to_string()),
+            other => panic!("{other:?}"),
+        };
+        assert_eq!(
+            error(&ALANINE.replace(r#"atomRefs2="a2 a4""#, r#"atomRefs2="a2 a9""#)),
+            (18, r#"unknown atom reference "a9""#.to_owned())
+        );
+        assert_eq!(
+            error(&ALANINE.replace(r#"elementType="N""#, r#"elementType="Xx""#)),
+            (6, r#"unknown element type "Xx""#.to_owned())
+        );
+        assert_eq!(
+            error(&ALANINE.replace("a1 a3 a4 a2", "a1 a3 a4")),
+            (8, r#"invalid stereo "a1 a3 a4""#.to_owned())
+        );
+        assert_eq!(
+            error(&ALANINE.replace(r#"id="a3""#, "id=a3")),
+            (
+                10,
+                r#"malformed markup "<cml:atom id=a3 elementType=\"C\" hydrogenCount=\"3\"/>""#
+                    .to_owned()
+            )
+        );
+        assert_eq!(
+            error(&ALANINE.replace("a1 a3 a4 a2", "a1 a3 a5 a2")),
+            (8, r#"invalid stereo "a1 a3 a5 a2""#.to_owned())
+        );
+        assert_eq!(
+            error(&ALANINE.replace("a1 a3 a4 a2", "a1 a3 a3 a2")),
+            (8, r#"invalid stereo "a1 a3 a3 a2""#.to_owned())
+        );
+        let butene = r#"<molecule>
+  <atomArray>
+    <atom id="a1" elementType="C"/><atom id="a2" elementType="C"/>
+    <atom id="a3" elementType="C"/><atom id="a4" elementType="C"/>
+  </atomArray>
+  <bondArray>
+    <bond atomRefs2="a1 a2" order="1"/>
+    <bond atomRefs2="a2 a3" order="2">
+      <bondStereo atomRefs4="a1 a2 a4 a3">T</bondStereo>
+    </bond>
+    <bond atomRefs2="a3 a4" order="1"/>
+  </bondArray>
+</molecule>"#;
+        assert_eq!(
+            error(butene),
+            (9, r#"invalid stereo "a1 a2 a4 a3""#.to_owned())
+        );
+        // Not on the enclosing double bond
+        let single = butene.replace("a1 a2 a4 a3", "a1 a2 a3 a4");
+        let single = single.replace(r#"order="2""#, r#"order="1""#);
+        assert_eq!(
+            error(&single),
+            (9, r#"invalid stereo "a1 a2 a3 a4""#.to_owned())
+        );
+        let misplaced = butene.replace(
+            r#"<bond atomRefs2="a1 a2" order="1"/>"#,
+            r#"<bond atomRefs2="a1 a2" order="1"><bondStereo atomRefs4="a1 a2 a3 a4">T</bondStereo></bond>"#,
+        );
+        assert_eq!(
+            error(&misplaced),
+            (7, r#"invalid stereo "a1 a2 a3 a4""#.to_owned())
+        );
+        assert_eq!(error("<cml/>"), (1, "molecule not found".to_owned()));
+    }
+}
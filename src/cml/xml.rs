@@ -0,0 +1,141 @@
+use super::Error;
+
+/// Markup event
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Event<'a> {
+    /// Start tag with its local name and attributes, `empty` when
+    /// self-closing
+    Start {
+        name: &'a str,
+        attributes: Vec<(&'a str, String)>,
+        empty: bool,
+    },
+    End(&'a str),
+    Text(String),
+}
+
+/// Minimal XML reader
+///
+/// Declarations, processing instructions and comments are skipped,
+/// namespace prefixes dropped and the predefined entities decoded.
+pub(super) struct Reader<'a> {
+    text: &'a str,
+    position: usize,
+    /// Line of the last event
+    pub(super) line: usize,
+    /// Position up to which the lines are counted
+    counted: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(super) fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            position: 0,
+            line: 1,
+            counted: 0,
+        }
+    }
+
+    /// Text up to the terminator, advancing past it
+    fn until(&mut self, terminator: &str) -> Result<&'a str, Error> {
+        let rest = &self.text[self.position..];
+        let Some(end) = rest.find(terminator) else {
+            let line = rest.lines().next().unwrap_or_default();
+            return Err(Error::Markup(line.to_owned()));
+        };
+        self.position += end + terminator.len();
+        Ok(&rest[..end])
+    }
+
+    fn event(&mut self) -> Result<Option<Event<'a>>, Error> {
+        let rest = &self.text[self.position..];
+        Ok(if rest.starts_with("<!--") {
+            self.until("-->")?;
+            None
+        } else if rest.starts_with("<![CDATA[") {
+            self.position += "<![CDATA[".len();
+            Some(Event::Text(self.until("]]>")?.to_owned()))
+        } else if rest.starts_with("<?") || rest.starts_with("<!") {
+            self.until(">")?;
+            None
+        } else if rest.starts_with("</") {
+            self.position += "</".len();
+            Some(Event::End(local(self.until(">")?.trim())))
+        } else if rest.starts_with('<') {
+            self.position += "<".len();
+            Some(start(self.until(">")?)?)
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            self.position += end;
+            Some(Event::Text(decode(&rest[..end])))
+        })
+    }
+}
+
+impl<'a> Iterator for Reader<'a> {
+    type Item = Result<Event<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.position < self.text.len() {
+            self.line += self.text[self.counted..self.position].matches('\n').count();
+            self.counted = self.position;
+            match self.event() {
+                Ok(Some(event)) => return Some(Ok(event)),
+                Ok(None) => {}
+                Err(error) => {
+                    self.position = self.text.len();
+                    return Some(Err(error));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Start tag content, `name key="value" ... /?`
+fn start(tag: &str) -> Result<Event<'_>, Error> {
+    let error = || Error::Markup(format!("<{tag}>"));
+    let (tag, empty) = match tag.strip_suffix('/') {
+        Some(tag) => (tag, true),
+        None => (tag, false),
+    };
+    let tag = tag.trim();
+    let end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+    let (name, mut rest) = tag.split_at(end);
+    let mut attributes = Vec::new();
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+        let (key, value) = rest.split_once('=').ok_or_else(error)?;
+        let value = value.trim_start();
+        let quote = value
+            .chars()
+            .next()
+            .filter(|&quote| quote == '"' || quote == '\'')
+            .ok_or_else(error)?;
+        let (value, next) = value[1..].split_once(quote).ok_or_else(error)?;
+        attributes.push((local(key.trim()), decode(value)));
+        rest = next;
+    }
+    Ok(Event::Start {
+        name: local(name),
+        attributes,
+        empty,
+    })
+}
+
+/// Name without its namespace prefix
+fn local(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+fn decode(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
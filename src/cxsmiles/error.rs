@@ -0,0 +1,14 @@
+use thiserror::Error;
+
+/// CXSMILES error
+#[derive(Clone, Error, Debug)]
+pub enum Error {
+    #[error("unclosed extension")]
+    Unclosed,
+    #[error("invalid number {0:?}")]
+    Number(String),
+    #[error("atom {0} out of range")]
+    Atom(usize),
+    #[error("invalid field {0:?}")]
+    Field(String),
+}
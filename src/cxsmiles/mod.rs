@@ -0,0 +1,425 @@
+//! CXSMILES extension layer
+//!
+//! The `|...|` block following the SMILES, its fields separated by commas:
+//! coordinates `(x,y,z;...)`, atom labels `$...$`, stereo groups `a:`,
+//! `o1:` and `&1:`, radicals `^1:`, link nodes `LN:` and polymer Sgroups
+//! `Sg:`. Atoms are referred to by their position in the SMILES. Other
+//! fields are skipped.
+//!
+//! [ChemAxon Extended SMILES](https://docs.chemaxon.com/display/docs/formats_chemaxon-extended-smiles-and-smarts-cxsmiles-and-cxsmarts.md)
+
+pub use self::error::Error;
+
+use crate::{
+    errors,
+    semantic::{Extension, LinkNode, MoleculeGraph, Radical, Sgroup, StereoGroup},
+};
+use petgraph::graph::NodeIndex;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write},
+};
+
+/// Parsed layer
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Layer {
+    coordinates: Vec<[f64; 3]>,
+    stereo_groups: Vec<(NodeIndex, StereoGroup)>,
+    radicals: Vec<(NodeIndex, Radical)>,
+    extension: Extension,
+}
+
+impl Layer {
+    /// Attaches the layer to the graph parsed from the SMILES
+    pub(crate) fn apply(self, graph: &mut MoleculeGraph) {
+        for (index, coordinates) in self.coordinates.into_iter().enumerate() {
+            graph[NodeIndex::new(index)].coordinates = Some(coordinates);
+        }
+        for (index, group) in self.stereo_groups {
+            graph[index].stereo_group = Some(group);
+        }
+        for (index, radical) in self.radicals {
+            graph[index].radical = Some(radical);
+        }
+        graph.extension = self.extension;
+    }
+}
+
+/// Parses the layer of a SMILES of `atoms` atoms
+///
+/// The text starts with the opening `|` at the `offset` of the input.
+pub(crate) fn parse(text: &str, offset: usize, atoms: usize) -> errors::Result<Layer> {
+    let error = |position: usize, source: Error| errors::Error::CxSmiles {
+        column: offset + position + 1,
+        source,
+    };
+    let content = text
+        .strip_prefix('|')
+        .and_then(|text| text.strip_suffix('|'))
+        .filter(|content| !content.contains('|'))
+        .ok_or_else(|| error(text.len(), Error::Unclosed))?;
+    let mut layer = Layer::default();
+    for (start, field) in fields(content) {
+        layer
+            .field(field, atoms)
+            .map_err(|source| error(start + 1, source))?;
+    }
+    Ok(layer)
+}
+
+impl Layer {
+    fn field(&mut self, field: &str, atoms: usize) -> Result<(), Error> {
+        if let Some(coordinates) = field.strip_prefix('(') {
+            let coordinates = coordinates
+                .strip_suffix(')')
+                .ok_or_else(|| Error::Field(field.to_owned()))?;
+            for (index, entry) in coordinates.split(';').enumerate() {
+                check(index, atoms)?;
+                let mut values = [0.0; 3];
+                for (value, text) in values.iter_mut().zip(entry.split(',')) {
+                    if !text.is_empty() {
+                        *value = number(text)?;
+                    }
+                }
+                self.coordinates.push(values);
+            }
+        } else if let Some(labels) = field.strip_prefix('$') {
+            let labels = labels
+                .strip_suffix('$')
+                .ok_or_else(|| Error::Field(field.to_owned()))?;
+            for (index, label) in labels.split(';').enumerate() {
+                if !label.is_empty() {
+                    check(index, atoms)?;
+                    let index = NodeIndex::new(index);
+                    self.extension.labels.insert(index, label.to_owned());
+                }
+            }
+        } else if let Some(link_nodes) = field.strip_prefix("LN:") {
+            for link_node in link_nodes.split(',') {
+                let (atom, repeats) = link_node
+                    .split_once(':')
+                    .ok_or_else(|| Error::Field(field.to_owned()))?;
+                let repeats: Vec<_> = repeats.split('.').collect();
+                let outer = match repeats[..] {
+                    [_, _] => None,
+                    [_, _, first, second] => Some((index(first, atoms)?, index(second, atoms)?)),
+                    _ => return Err(Error::Field(field.to_owned())),
+                };
+                self.extension.link_nodes.push(LinkNode {
+                    atom: index(atom, atoms)?,
+                    minimum: number(repeats[0])?,
+                    maximum: number(repeats[1])?,
+                    outer,
+                });
+            }
+        } else if let Some(sgroup) = field.strip_prefix("Sg:") {
+            let mut parts = sgroup.split(':');
+            let (Some(kind), Some(indices)) = (parts.next(), parts.next()) else {
+                return Err(Error::Field(field.to_owned()));
+            };
+            self.extension.sgroups.push(Sgroup {
+                kind: kind.to_owned(),
+                atoms: indices
+                    .split(',')
+                    .map(|text| index(text, atoms))
+                    .collect::<Result<_, _>>()?,
+                subscript: parts.next().unwrap_or_default().to_owned(),
+                superscript: parts.next().unwrap_or_default().to_owned(),
+            });
+        } else if let Some((name, indices)) = field.split_once(':') {
+            let digits =
+                |text: &&str| !text.is_empty() && text.bytes().all(|byte| byte.is_ascii_digit());
+            if let Some(radical) = name.strip_prefix('^') {
+                let radical = match radical {
+                    "1" => Radical::Monovalent,
+                    "2" => Radical::Divalent,
+                    "3" => Radical::DivalentSinglet,
+                    "4" => Radical::DivalentTriplet,
+                    "5" => Radical::Trivalent,
+                    "6" => Radical::TrivalentDoublet,
+                    "7" => Radical::TrivalentQuartet,
+                    _ => return Err(Error::Field(field.to_owned())),
+                };
+                for text in indices.split(',') {
+                    self.radicals.push((index(text, atoms)?, radical));
+                }
+                return Ok(());
+            }
+            let group = if name == "a" {
+                StereoGroup::Absolute
+            } else if let Some(group) = name.strip_prefix('o').filter(digits) {
+                StereoGroup::Or(number(group)?)
+            } else if let Some(group) = name.strip_prefix('&').filter(digits) {
+                StereoGroup::And(number(group)?)
+            } else {
+                // Unsupported field
+                return Ok(());
+            };
+            for text in indices.split(',') {
+                self.stereo_groups.push((index(text, atoms)?, group));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Fields with their byte offsets
+///
+/// A comma separates fields unless it is followed by a digit (a list
+/// continues) or is enclosed in coordinates or labels.
+fn fields(content: &str) -> Vec<(usize, &str)> {
+    let mut fields = Vec::new();
+    let (mut start, mut depth, mut label) = (0, 0, false);
+    for (position, char) in content.char_indices() {
+        match char {
+            '(' if !label => depth += 1,
+            ')' if !label => depth -= 1,
+            '$' => label = !label,
+            ',' if depth == 0 && !label => {
+                let next = content[position + 1..].chars().next();
+                if next.is_some_and(|next| next.is_ascii_digit()) {
+                    continue;
+                }
+                fields.push((start, &content[start..position]));
+                start = position + 1;
+            }
+            _ => {}
+        }
+    }
+    if start < content.len() {
+        fields.push((start, &content[start..]));
+    }
+    fields
+}
+
+fn check(index: usize, atoms: usize) -> Result<(), Error> {
+    if index < atoms {
+        Ok(())
+    } else {
+        Err(Error::Atom(index))
+    }
+}
+
+fn index(text: &str, atoms: usize) -> Result<NodeIndex, Error> {
+    let index = number(text)?;
+    check(index, atoms)?;
+    Ok(NodeIndex::new(index))
+}
+
+fn number<T: std::str::FromStr>(text: &str) -> Result<T, Error> {
+    text.parse().map_err(|_| Error::Number(text.to_owned()))
+}
+
+/// Writes the layer of the graph, preceded by a space, nothing when the
+/// graph has no layer information
+///
+/// `positions` are the positions of the atoms in the written SMILES.
+pub(crate) fn write(f: &mut impl Write, graph: &MoleculeGraph, positions: &[usize]) -> fmt::Result {
+    let mut order = vec![NodeIndex::end(); positions.len()];
+    for index in graph.node_indices() {
+        order[positions[index.index()]] = index;
+    }
+    let position = |index: &NodeIndex| positions[index.index()];
+    let list = |indices: &mut Vec<usize>| {
+        indices.sort();
+        let indices: Vec<_> = indices.iter().map(ToString::to_string).collect();
+        indices.join(",")
+    };
+    let mut fields = Vec::new();
+    if graph.node_weights().any(|atom| atom.coordinates.is_some()) {
+        let coordinates: Vec<_> = order
+            .iter()
+            .map(|&index| {
+                let [x, y, z] = graph[index].coordinates.unwrap_or_default();
+                let z = if z == 0.0 { String::new() } else { decimal(z) };
+                format!("{},{},{z}", decimal(x), decimal(y))
+            })
+            .collect();
+        fields.push(format!("({})", coordinates.join(";")));
+    }
+    if !graph.extension.labels.is_empty() {
+        let labels: Vec<_> = order
+            .iter()
+            .map(|index| graph.extension.labels.get(index).map_or("", String::as_str))
+            .collect();
+        fields.push(format!("${}$", labels.join(";")));
+    }
+    let mut groups = BTreeMap::<_, Vec<_>>::new();
+    let mut radicals = BTreeMap::<_, Vec<_>>::new();
+    for index in graph.node_indices() {
+        if let Some(group) = graph[index].stereo_group {
+            groups.entry(group).or_default().push(position(&index));
+        }
+        if let Some(radical) = graph[index].radical {
+            radicals.entry(radical).or_default().push(position(&index));
+        }
+    }
+    for (group, mut indices) in groups {
+        let name = match group {
+            StereoGroup::Absolute => "a".to_owned(),
+            StereoGroup::Or(number) => format!("o{number}"),
+            StereoGroup::And(number) => format!("&{number}"),
+        };
+        fields.push(format!("{name}:{}", list(&mut indices)));
+    }
+    for (radical, mut indices) in radicals {
+        fields.push(format!("^{}:{}", radical as u8 + 1, list(&mut indices)));
+    }
+    for link_node in &graph.extension.link_nodes {
+        let mut field = format!(
+            "LN:{}:{}.{}",
+            position(&link_node.atom),
+            link_node.minimum,
+            link_node.maximum
+        );
+        if let Some((first, second)) = link_node.outer {
+            field += &format!(".{}.{}", position(&first), position(&second));
+        }
+        fields.push(field);
+    }
+    for sgroup in &graph.extension.sgroups {
+        let mut indices: Vec<_> = sgroup.atoms.iter().map(position).collect();
+        fields.push(format!(
+            "Sg:{}:{}:{}:{}",
+            sgroup.kind,
+            list(&mut indices),
+            sgroup.subscript,
+            sgroup.superscript
+        ));
+    }
+    if fields.is_empty() {
+        return Ok(());
+    }
+    write!(f, " |{}|", fields.join(","))
+}
+
+/// Shortest decimal of up to four places
+fn decimal(value: f64) -> String {
+    let text = format!("{value:.4}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "-0" => "0".to_owned(),
+        text => text.to_owned(),
+    }
+}
+
+mod error;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::semantic::{Atom, Element};
+
+    fn parse(smiles: &str) -> MoleculeGraph {
+        smiles.parse().unwrap()
+    }
+
+    #[test]
+    fn read() {
+        let graph = parse("CC(=O)O |(0,0,;1.299,0.75,;2.598,0,0.5;1.299,2.25,)|");
+        assert_eq!(
+            graph[NodeIndex::new(1)].coordinates,
+            Some([1.299, 0.75, 0.0])
+        );
+        assert_eq!(
+            graph[NodeIndex::new(2)].coordinates,
+            Some([2.598, 0.0, 0.5])
+        );
+        let graph = parse("C[C@H](O)[C@@H](C)N |a:1,&1:3|");
+        assert_eq!(
+            graph[NodeIndex::new(1)].stereo_group,
+            Some(StereoGroup::Absolute)
+        );
+        assert_eq!(
+            graph[NodeIndex::new(3)].stereo_group,
+            Some(StereoGroup::And(1))
+        );
+        assert_eq!(graph[NodeIndex::new(0)].stereo_group, None);
+        let graph = parse("[CH2]C[CH] |^1:0,^4:2|");
+        assert_eq!(graph[NodeIndex::new(0)].radical, Some(Radical::Monovalent));
+        assert_eq!(
+            graph[NodeIndex::new(2)].radical,
+            Some(Radical::DivalentTriplet)
+        );
+        let graph = parse("*CCO* |$_AP1;;;;_R1$|");
+        assert_eq!(graph.extension.labels.len(), 2);
+        assert_eq!(graph.extension.labels[&NodeIndex::new(4)], "_R1");
+        let graph = parse("*CC(*)C |Sg:n:1,2,4:n:ht|");
+        assert_eq!(
+            graph.extension.sgroups,
+            [Sgroup {
+                kind: "n".to_owned(),
+                atoms: [1, 2, 4].map(NodeIndex::new).to_vec(),
+                subscript: "n".to_owned(),
+                superscript: "ht".to_owned(),
+            }]
+        );
+        let graph = parse("C1CCC1 |LN:1:1.3.0.2|");
+        assert_eq!(
+            graph.extension.link_nodes,
+            [LinkNode {
+                atom: NodeIndex::new(1),
+                minimum: 1,
+                maximum: 3,
+                outer: Some((NodeIndex::new(0), NodeIndex::new(2))),
+            }]
+        );
+        // Unsupported fields are skipped
+        let graph = parse("C/C=C/CO |c:1,wU:1.0,o1:4|");
+        assert_eq!(
+            graph[NodeIndex::new(4)].stereo_group,
+            Some(StereoGroup::Or(1))
+        );
+        assert_eq!(graph.to_string(), "C/C=C/CO");
+    }
+
+    #[test]
+    fn write() {
+        for cxsmiles in [
+            "CC(=O)O |(0,0,;1.299,0.75,;2.598,0,0.5;1.299,2.25,)|",
+            "C[C@H](O)[C@@H](C)N |a:1,&1:3|",
+            "[CH2]C[CH] |^1:0,^4:2|",
+            "*CCO* |$_AP1;;;;_R1$|",
+            "*CC(*)C |Sg:n:1,2,4:n:ht|",
+            "C1CCC1 |LN:1:1.3.0.2|",
+            "CCO",
+        ] {
+            assert_eq!(parse(cxsmiles).cxsmiles().to_string(), cxsmiles);
+        }
+        // Atoms referred to by their written positions
+        let mut graph = MoleculeGraph::default();
+        let oxygen = graph.add_node(Atom {
+            element: Some(Element::O),
+            ..Default::default()
+        });
+        let carbon = graph.add_node(Atom {
+            element: Some(Element::C),
+            ..Default::default()
+        });
+        graph.add_edge(carbon, oxygen, Default::default());
+        graph.extension.labels.insert(carbon, "_AP1".to_owned());
+        assert_eq!(graph.cxsmiles().to_string(), "OC |$;_AP1$|");
+        let canonical = parse("*CCO* |$_AP1;;;;_R1$|").canonical();
+        for (index, label) in &canonical.extension.labels {
+            assert_eq!(canonical[*index].element, None, "{label}");
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let error = |smiles: &str| match smiles.parse::<MoleculeGraph>() {
+            Err(errors::Error::CxSmiles { column, source }) => (column, source.to_string()),
+            other => panic!("{other:?}"),
+        };
+        assert_eq!(error("CC |a:5|"), (5, "atom 5 out of range".to_owned()));
+        assert_eq!(error("CC |a:1"), (8, "unclosed extension".to_owned()));
+        assert_eq!(
+            error("CC |a:0,^9:1|"),
+            (9, r#"invalid field "^9:1""#.to_owned())
+        );
+        assert_eq!(
+            error("CC |(0,x,)|"),
+            (5, r#"invalid number "x""#.to_owned())
+        );
+    }
+}
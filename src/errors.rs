@@ -1,5 +1,9 @@
-pub use crate::{semantic::Error as SemanticError, syntax::Error as SyntaxError};
+pub use crate::{
+    cml::Error as CmlError, cxsmiles::Error as CxSmilesError, lipid::Error as LipidError,
+    molfile::Error as MolfileError, semantic::Error as SemanticError, syntax::Error as SyntaxError,
+};
 
+use std::{io, sync::Arc};
 use thiserror::Error;
 
 /// Result
@@ -12,4 +16,29 @@ pub enum Error {
     Syntax(#[from] SyntaxError),
     #[error(transparent)]
     Semantic(#[from] SemanticError),
+    #[error(transparent)]
+    Lipid(#[from] LipidError),
+    #[error("molfile line {line}: {source}")]
+    Molfile { line: usize, source: MolfileError },
+    #[error("CML line {line}: {source}")]
+    Cml { line: usize, source: CmlError },
+    #[error("CXSMILES column {column}: {source}")]
+    CxSmiles {
+        column: usize,
+        source: CxSmilesError,
+    },
+    #[error("line {line}, column {column}: {source}")]
+    Smi {
+        line: usize,
+        column: usize,
+        source: Box<Error>,
+    },
+    #[error(transparent)]
+    Io(Arc<io::Error>),
+}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        Self::Io(Arc::new(value))
+    }
 }
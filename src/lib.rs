@@ -5,21 +5,34 @@
 // https://matklad.github.io/2020/04/13/simple-but-powerful-pratt-parsing.html#Recursive-descent-and-left-recursion
 // https://matklad.github.io/2020/04/15/from-pratt-to-dijkstra.html
 
-#![feature(let_chains)]
-
 // // smiles ::= terminator | chain terminator
 // // terminator ::= SPACE | TAB | LINEFEED | CARRIAGE_RETURN | END_OF_STRING
 
 // [Rh-](Cl)(Cl)(Cl)(Cl)$[Rh-](Cl)(Cl)(Cl)Cl
 
-pub use self::{parser::Parser, semantic::MoleculeGraph};
+pub use self::{
+    molfile::{Properties, SdfReader, SdfWriter, Version},
+    parser::Parser,
+    semantic::{
+        AtomList, Chain, Extension, Geometry, LinkNode, MoleculeGraph, Notation, Position, Radical,
+        Rule, Sgroup, StereoGroup,
+    },
+    smi::{SmiReader, SmiWriter},
+};
 
+mod cml;
+mod cxsmiles;
 mod errors;
 mod language;
 mod lexer;
+mod lines;
+pub mod lipid;
+mod molfile;
 mod parser;
 mod semantic;
+mod smi;
 mod syntax;
+mod writer;
 
 #[cfg(test)]
 mod test {
@@ -27,20 +40,13 @@ mod test {
 
     use crate::{
         parser::Parser,
-        semantic::{Atom, Bond, MoleculeGraph},
+        semantic::{Bond, Element, Geometry, MoleculeGraph, Notation, Parity, Rule},
         syntax::{
-            ast::{Branch, Root, SyntaxNodeExt, Tree},
+            ast::{Root, SyntaxNodeExt},
             SyntaxNode, SyntaxToken,
         },
     };
-    use petgraph::{
-        graph::{node_index, NodeIndex, UnGraph},
-        visit::{
-            depth_first_search, Bfs, Control, Dfs, DfsEvent, EdgeRef, IntoEdgeReferences,
-            IntoEdges, IntoNodeIdentifiers,
-        },
-        Graph, Undirected,
-    };
+    use petgraph::{graph::NodeIndex, visit::IntoEdgeReferences};
     use rowan::NodeOrToken;
 
     // #[test]
@@ -76,6 +82,155 @@ mod test {
         }
     }
 
+    #[test]
+    fn ring_bond_branches() {
+        // Ring bonds ending a branch or the SMILES before its layer
+        for (smiles, atoms, bonds) in [
+            ("C(C1CC1)C", 5, 5),
+            ("C(C1)C1", 3, 3),
+            ("[C@@H]1(O)CC1O", 5, 5),
+            ("C1CC1 |&1:1|", 3, 3),
+        ] {
+            let graph: MoleculeGraph = smiles.parse().unwrap();
+            assert_eq!(
+                (graph.node_count(), graph.edge_count()),
+                (atoms, bonds),
+                "{smiles}"
+            );
+        }
+    }
+
+    #[test]
+    fn canonical() {
+        for smiles in [
+            vec!["CCCC(=O)O", "OC(=O)CCC", "C(CC)C(O)=O"],
+            vec![
+                "CCCCCCCC/C=C\\CCCCCCCC(=O)O",
+                "OC(=O)CCCCCCC/C=C\\CCCCCCCC",
+                "C(/CCCCCCCC)=C/CCCCCCCC(=O)O",
+            ],
+            vec![
+                "N[C@@H](C)C(=O)O",
+                "C[C@H](N)C(=O)O",
+                "OC(=O)[C@@H](N)C",
+            ],
+            vec![
+                "CCCCCCCCCCCCCCCC(=O)OC[C@@H](OC(=O)CCCCCCC/C=C\\CCCCCCCC)COC(=O)CCCCCCC/C=C\\C/C=C\\CCCCC",
+                "CCCCC/C=C\\C/C=C\\CCCCCCCC(=O)OC[C@H](OC(=O)CCCCCCC/C=C\\CCCCCCCC)COC(=O)CCCCCCCCCCCCCCC",
+            ],
+            vec!["C1CCC2C1(CC2)C", "CC12CCC1CCC2"],
+            // (2E,4Z)-hexadiene
+            vec!["C/C=C/C=C\\C", "C/C=C\\C=C\\C"],
+            // 9Z and 9E oleoyl swapped between sn-1 and sn-3
+            vec![
+                "CCCCCCCC/C=C\\CCCCCCCC(=O)OCC(OC(=O)CCCCCCCCCCCCCCC)COC(=O)CCCCCCC/C=C/CCCCCCCC",
+                "CCCCCCCC/C=C/CCCCCCCC(=O)OCC(OC(=O)CCCCCCCCCCCCCCC)COC(=O)CCCCCCC/C=C\\CCCCCCCC",
+            ],
+        ] {
+            let canonical: BTreeSet<_> = smiles
+                .iter()
+                .map(|smiles| smiles.parse::<MoleculeGraph>().unwrap().canonical_smiles())
+                .collect();
+            assert_eq!(canonical.len(), 1, "{canonical:?}");
+            let canonical = canonical.first().unwrap();
+            let graph: MoleculeGraph = canonical.parse().unwrap();
+            assert_eq!(&graph.canonical_smiles(), canonical);
+        }
+        let cis: MoleculeGraph = "C/C=C\\C".parse().unwrap();
+        let trans: MoleculeGraph = "C/C=C/C".parse().unwrap();
+        assert_ne!(cis.canonical_smiles(), trans.canonical_smiles());
+        let l: MoleculeGraph = "N[C@@H](C)C(=O)O".parse().unwrap();
+        let d: MoleculeGraph = "N[C@H](C)C(=O)O".parse().unwrap();
+        assert_ne!(l.canonical_smiles(), d.canonical_smiles());
+    }
+
+    #[test]
+    fn canonical_permutation() {
+        // Copy with the atoms in the permuted order, the stereo kept
+        fn permute(graph: &MoleculeGraph, permutation: &[usize]) -> MoleculeGraph {
+            let map = |index: NodeIndex| NodeIndex::new(permutation[index.index()]);
+            let mut order = vec![NodeIndex::end(); graph.node_count()];
+            for index in graph.node_indices() {
+                order[map(index).index()] = index;
+            }
+            let mut permuted = MoleculeGraph::default();
+            for &index in &order {
+                permuted.add_node(graph[index]);
+            }
+            let mut edges: Vec<_> = graph.edge_indices().collect();
+            edges.reverse();
+            for edge in edges {
+                let (source, target) = graph.edge_endpoints(edge).unwrap();
+                permuted.add_edge(map(source), map(target), graph[edge]);
+            }
+            for index in graph.node_indices() {
+                let mut reference: Vec<_> = graph.neighbors(index).collect();
+                if graph.hydrogens(index) > 0 {
+                    reference.push(index);
+                }
+                reference.sort();
+                let reference: Vec<_> = reference.into_iter().map(map).collect();
+                permuted.set_parity(map(index), &reference, graph[index].parity);
+            }
+            permuted
+        }
+
+        for smiles in [
+            "C/C=C/C=C\\C",
+            "N[C@@H](C)C(=O)O",
+            "C[C@H]1CC[C@@H](C)CC1",
+            "CC(C)[C@@]1(O)CC[C@H](C)C[C@@H]1O",
+            "CCCCCCCC/C=C\\CCCCCCCC(=O)OC[C@@H](OC(=O)CCCCCCCCCCCCCCC)COC(=O)CCCCCCC/C=C/CCCCCCCC",
+            // Cubane and tert-butyls, ties left by the refinement
+            "C12C3C4C1C5C2C3C45",
+            "C(C(C)(C)C)(C(C)(C)C)(C(C(C)(C)C)(C(C)(C)C)C(C)(C)C)C(C(C)(C)C)(C(C)(C)C)C(C)(C)C",
+        ] {
+            let graph: MoleculeGraph = smiles.parse().unwrap();
+            let count = graph.node_count();
+            let reversed: Vec<_> = (0..count).rev().collect();
+            let rotated: Vec<_> = (0..count).map(|index| (index + 3) % count).collect();
+            let shuffled: Vec<_> = (0..count).map(|index| index * 7 % count).collect();
+            for permutation in [reversed, rotated, shuffled] {
+                if permutation.iter().collect::<BTreeSet<_>>().len() != count {
+                    continue;
+                }
+                let permuted = permute(&graph, &permutation);
+                assert_eq!(
+                    permuted.canonical_smiles(),
+                    graph.canonical_smiles(),
+                    "{smiles} {permutation:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn mass() {
+        let water: MoleculeGraph = "O".parse().unwrap();
+        assert!((water.molar_mass().unwrap() - 18.015).abs() < 1e-9);
+        let oleic: MoleculeGraph = "CCCCCCCC/C=C\\CCCCCCCC(=O)O".parse().unwrap();
+        assert!((oleic.molar_mass().unwrap() - 282.468).abs() < 1e-3);
+        assert!((oleic.monoisotopic_mass().unwrap() - 282.255880).abs() < 1e-6);
+        let phosphate: MoleculeGraph = "[O-]P(=O)([O-])[O-]".parse().unwrap();
+        assert!((phosphate.monoisotopic_mass().unwrap() - 94.953420).abs() < 1e-6);
+        // Labelled atoms
+        let methane: MoleculeGraph = "[13CH4]".parse().unwrap();
+        assert!((methane.monoisotopic_mass().unwrap() - 17.034655).abs() < 1e-6);
+        assert!((methane.molar_mass().unwrap() - 17.035).abs() < 1e-3);
+        let water: MoleculeGraph = "[2H]O[2H]".parse().unwrap();
+        assert!((water.monoisotopic_mass().unwrap() - 20.023118).abs() < 1e-6);
+        let unknown: MoleculeGraph = "[99C]".parse().unwrap();
+        assert_eq!(unknown.monoisotopic_mass(), None);
+    }
+
+    #[test]
+    fn rings() {
+        for smiles in ["CC1(CC1)C", "C1CCC2C1(CC2)C", "C1CC(C1)O"] {
+            let graph: MoleculeGraph = smiles.parse().unwrap();
+            assert_eq!(graph.to_string(), smiles);
+        }
+    }
+
     #[test]
     fn test() {
         // let parser = Parser::new("CCCCCCCCCCCCCC(=O)O");
@@ -88,31 +243,20 @@ mod test {
 
         println!("{graph:?}");
 
-        enum Notation {
-            Delta,
-            Omega,
-        }
-
-        let notation = Notation::Omega;
-
         let unsaturated = graph.unsaturated().edge_references().count();
         println!("unsaturated: {unsaturated:?}");
 
-        let indices: BTreeSet<_> = graph
-            .unsaturated()
-            .edge_references()
-            .map(|edge| {
-                let index = edge.source().index() + 1;
-                match notation {
-                    Notation::Delta => {
-                        let carbons = graph.carbons().node_identifiers().count();
-                        carbons - index
-                    }
-                    Notation::Omega => index,
-                }
-            })
-            .collect();
-        println!("indices: {indices:?}");
+        let chain = graph.principal_chain().unwrap();
+        let indices = |notation| -> BTreeSet<_> {
+            chain
+                .unsaturations(&graph, notation)
+                .into_iter()
+                .map(|(locant, _)| locant)
+                .collect()
+        };
+        println!("indices: {:?}", indices(Notation::Omega));
+        assert_eq!(indices(Notation::Delta), BTreeSet::from([9, 12]));
+        assert_eq!(indices(Notation::Omega), BTreeSet::from([6, 9]));
         // for edge in graph.unsaturated().edge_references(){
         //     println!("{{index:?}}: {:?}", edge.weight());
         // }
@@ -130,4 +274,145 @@ mod test {
         //     println!("index: {index:?}, value: {:?}", graph[index]);
         // }
     }
+
+    #[test]
+    fn locants() {
+        for smiles in [
+            "CCCCCC=CCC=CCCCCCCCC(=O)O",
+            "OC(=O)CCCCCCCC=CCC=CCCCCC",
+            "C(CCCCCCC(=O)O)C=CCC=CCCCCC",
+        ] {
+            let graph: MoleculeGraph = smiles.parse().unwrap();
+            let chain = graph.principal_chain().unwrap();
+            assert_eq!(chain.len(), 18);
+            assert!(graph.is_carboxyl(chain.atom(1, Notation::Delta).unwrap()));
+            let delta: Vec<_> = chain.unsaturations(&graph, Notation::Delta);
+            let omega: Vec<_> = chain.unsaturations(&graph, Notation::Omega);
+            assert_eq!(
+                delta.iter().map(|&(locant, _)| locant).collect::<Vec<_>>(),
+                [9, 12]
+            );
+            assert_eq!(
+                omega.iter().map(|&(locant, _)| locant).collect::<Vec<_>>(),
+                [6, 9]
+            );
+            for (locant, index) in chain.locants(Notation::Omega) {
+                assert_eq!(chain.locant(index, Notation::Delta), Some(19 - locant));
+            }
+        }
+    }
+
+    #[test]
+    fn positions() {
+        // 12-oxo-9Z-octadecenoic acid, 9-octadecynoic acid
+        let graph: MoleculeGraph = "OC(=O)CCCCCCC/C=C\\CC(=O)CCCCCC".parse().unwrap();
+        let positions = graph.positions(Notation::Delta);
+        let positions: Vec<_> = positions
+            .iter()
+            .map(|position| (position.locant, position.element, position.geometry))
+            .collect();
+        assert_eq!(
+            positions,
+            [
+                (1, Element::O, None),
+                (9, Element::C, Some(Geometry::Z)),
+                (12, Element::O, None),
+            ]
+        );
+        let graph: MoleculeGraph = "CCCCCCCCC#CCCCCCCCC(=O)O".parse().unwrap();
+        let positions = graph.positions(Notation::Omega);
+        assert_eq!(positions[0].locant, 9);
+        assert_eq!(positions[0].bond, Bond::Triple);
+        assert_eq!(positions[1].locant, 18);
+        assert_eq!(positions[1].element, Element::O);
+    }
+
+    #[test]
+    fn longest_chain() {
+        // 4-propyl-2-octene: the chain through the double bond wins the tie
+        let graph: MoleculeGraph = "CCCC(CCC)CC=CC".parse().unwrap();
+        let chain = graph.longest_chain(Rule::DEFAULT).unwrap();
+        assert_eq!(chain.len(), 8);
+        assert_eq!(chain.unsaturations(&graph, Notation::Delta)[0].0, 2);
+        let chain = graph.longest_chain(&[]).unwrap();
+        assert_eq!(chain.len(), 8);
+        // Triolein: acyl chains are separated by the ester oxygens
+        let oleoyl = "C(=O)CCCCCCC/C=C\\CCCCCCCC";
+        let graph: MoleculeGraph = format!("C(O{oleoyl})C(O{oleoyl})CO{oleoyl}")
+            .parse()
+            .unwrap();
+        assert_eq!(graph.the_longest_carbon_chain().len(), 18);
+        // Rings
+        let graph: MoleculeGraph = "C1CCCCC1".parse().unwrap();
+        assert_eq!(graph.the_longest_carbon_chain().len(), 6);
+        assert_eq!(graph.to_string(), "C1CCCCC1");
+        let graph: MoleculeGraph = "CCC1CC(CCCC)C1".parse().unwrap();
+        assert_eq!(graph.the_longest_carbon_chain().len(), 9);
+        assert_eq!(graph.to_string(), "CCC1CC(CCCC)C1");
+        assert!("C1CC".parse::<MoleculeGraph>().is_err());
+    }
+
+    #[test]
+    fn parity() {
+        // L-alanine written from different atoms
+        for smiles in [
+            "N[C@@H](C)C(=O)O",
+            "C[C@H](N)C(=O)O",
+            "OC(=O)[C@@H](N)C",
+            "[C@@H](C)(N)C(=O)O",
+            "O=C(O)[C@@H](N)C",
+        ] {
+            let graph: MoleculeGraph = smiles.parse().unwrap();
+            let alpha = graph
+                .node_indices()
+                .find(|&index| graph[index].parity.is_some())
+                .unwrap();
+            let find = |element, degree| {
+                graph
+                    .neighbors(alpha)
+                    .find(|&index| {
+                        graph[index].element == Some(element)
+                            && graph.neighbors(index).count() == degree
+                    })
+                    .unwrap()
+            };
+            let nitrogen = find(Element::N, 1);
+            let methyl = find(Element::C, 1);
+            let carboxyl = find(Element::C, 3);
+            let order = [nitrogen, alpha, methyl, carboxyl];
+            assert_eq!(
+                graph.parity(alpha, &order),
+                Some(Parity::Clockwise),
+                "{smiles}"
+            );
+            let written: MoleculeGraph = graph.to_string().parse().unwrap();
+            assert_eq!(written.to_string(), graph.to_string());
+        }
+        assert_eq!(
+            "N[C@@H](C)C(=O)O"
+                .parse::<MoleculeGraph>()
+                .unwrap()
+                .to_string(),
+            "N[C@@H](C)C(=O)O"
+        );
+        // Ring closures take the position of their digit
+        for smiles in ["C[C@H]1CCCCO1", "C[C@@H]1OCCCC1", "O1CCCC[C@@H]1C"] {
+            let graph: MoleculeGraph = smiles.parse().unwrap();
+            let alpha = graph
+                .node_indices()
+                .find(|&index| graph[index].parity.is_some())
+                .unwrap();
+            let mut neighbors: Vec<_> = graph.neighbors(alpha).collect();
+            neighbors.sort_by_key(|&index| (graph[index].element, graph.neighbors(index).count()));
+            let [methyl, ring, oxygen] = neighbors[..] else {
+                panic!();
+            };
+            let order = [methyl, alpha, oxygen, ring];
+            assert_eq!(
+                graph.parity(alpha, &order),
+                Some(Parity::Counterclockwise),
+                "{smiles}"
+            );
+        }
+    }
 }
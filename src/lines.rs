@@ -0,0 +1,70 @@
+//! Lines of a reader
+
+use std::io::{self, BufRead};
+
+/// Lines of any [`BufRead`] without their terminators
+///
+/// Invalid UTF-8 is replaced, an I/O error is yielded and ends the
+/// iteration.
+pub(crate) struct LossyLines<R> {
+    reader: R,
+    failed: bool,
+}
+
+impl<R: BufRead> LossyLines<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        Self {
+            reader,
+            failed: false,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for LossyLines<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let mut bytes = Vec::new();
+        loop {
+            match self.reader.read_until(b'\n', &mut bytes) {
+                Ok(0) => return None,
+                Ok(_) => break,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => {
+                    self.failed = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+        let line = String::from_utf8_lossy(&bytes);
+        Some(Ok(line.trim_end_matches(['\n', '\r']).to_owned()))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+
+    /// Reader failing every read
+    pub(crate) struct Failing;
+
+    impl io::Read for Failing {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("failing"))
+        }
+    }
+
+    #[test]
+    fn lines() {
+        let lines: Vec<_> = LossyLines::new(&b"CCO\r\n\neth\xffanol\nCC"[..])
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(lines, ["CCO", "", "eth\u{fffd}anol", "CC"]);
+        let mut lines = LossyLines::new(io::BufReader::new(Failing));
+        assert!(matches!(lines.next(), Some(Err(_))));
+        assert!(lines.next().is_none());
+    }
+}
@@ -0,0 +1,295 @@
+use super::{error::Result, fatty_acid::FattyAcid};
+use crate::semantic::MoleculeGraph;
+use std::sync::OnceLock;
+
+/// Catalogued fatty acid
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Entry {
+    /// Trivial names, the preferred one first
+    pub names: &'static [&'static str],
+    pub abbreviations: &'static [&'static str],
+    /// IUPAC name
+    pub systematic: &'static str,
+    /// LIPID MAPS shorthand
+    pub shorthand: &'static str,
+}
+
+impl Entry {
+    /// Common fatty acids
+    pub const ALL: &'static [Self] = &[
+        entry(&["butyric"], &[], "butanoic acid", "4:0"),
+        entry(&["caproic"], &[], "hexanoic acid", "6:0"),
+        entry(&["caprylic"], &[], "octanoic acid", "8:0"),
+        entry(&["capric"], &[], "decanoic acid", "10:0"),
+        entry(&["lauric"], &[], "dodecanoic acid", "12:0"),
+        entry(&["myristic"], &[], "tetradecanoic acid", "14:0"),
+        entry(&["pentadecylic"], &[], "pentadecanoic acid", "15:0"),
+        entry(&["palmitic"], &[], "hexadecanoic acid", "16:0"),
+        entry(&["margaric"], &[], "heptadecanoic acid", "17:0"),
+        entry(&["stearic"], &[], "octadecanoic acid", "18:0"),
+        entry(&["arachidic"], &[], "icosanoic acid", "20:0"),
+        entry(&["behenic"], &[], "docosanoic acid", "22:0"),
+        entry(&["lignoceric"], &[], "tetracosanoic acid", "24:0"),
+        entry(&["cerotic"], &[], "hexacosanoic acid", "26:0"),
+        entry(
+            &["myristoleic"],
+            &[],
+            "(9Z)-tetradec-9-enoic acid",
+            "14:1(9Z)",
+        ),
+        entry(
+            &["palmitoleic"],
+            &[],
+            "(9Z)-hexadec-9-enoic acid",
+            "16:1(9Z)",
+        ),
+        entry(&["sapienic"], &[], "(6Z)-hexadec-6-enoic acid", "16:1(6Z)"),
+        entry(
+            &["petroselinic"],
+            &[],
+            "(6Z)-octadec-6-enoic acid",
+            "18:1(6Z)",
+        ),
+        entry(&["oleic"], &["OA"], "(9Z)-octadec-9-enoic acid", "18:1(9Z)"),
+        entry(&["elaidic"], &[], "(9E)-octadec-9-enoic acid", "18:1(9E)"),
+        entry(
+            &["cis-vaccenic", "asclepic"],
+            &[],
+            "(11Z)-octadec-11-enoic acid",
+            "18:1(11Z)",
+        ),
+        entry(
+            &["vaccenic", "trans-vaccenic"],
+            &[],
+            "(11E)-octadec-11-enoic acid",
+            "18:1(11E)",
+        ),
+        entry(&["gondoic"], &[], "(11Z)-icos-11-enoic acid", "20:1(11Z)"),
+        entry(&["erucic"], &[], "(13Z)-docos-13-enoic acid", "22:1(13Z)"),
+        entry(
+            &["nervonic", "selacholeic"],
+            &[],
+            "(15Z)-tetracos-15-enoic acid",
+            "24:1(15Z)",
+        ),
+        entry(
+            &["linoleic"],
+            &["LA"],
+            "(9Z,12Z)-octadeca-9,12-dienoic acid",
+            "18:2(9Z,12Z)",
+        ),
+        entry(
+            &["rumenic"],
+            &[],
+            "(9Z,11E)-octadeca-9,11-dienoic acid",
+            "18:2(9Z,11E)",
+        ),
+        entry(
+            &["alpha-linolenic", "α-linolenic"],
+            &["ALA"],
+            "(9Z,12Z,15Z)-octadeca-9,12,15-trienoic acid",
+            "18:3(9Z,12Z,15Z)",
+        ),
+        entry(
+            &["gamma-linolenic", "γ-linolenic"],
+            &["GLA"],
+            "(6Z,9Z,12Z)-octadeca-6,9,12-trienoic acid",
+            "18:3(6Z,9Z,12Z)",
+        ),
+        entry(
+            &["stearidonic"],
+            &["SDA"],
+            "(6Z,9Z,12Z,15Z)-octadeca-6,9,12,15-tetraenoic acid",
+            "18:4(6Z,9Z,12Z,15Z)",
+        ),
+        entry(
+            &["dihomo-gamma-linolenic", "dihomo-γ-linolenic"],
+            &["DGLA"],
+            "(8Z,11Z,14Z)-icosa-8,11,14-trienoic acid",
+            "20:3(8Z,11Z,14Z)",
+        ),
+        entry(
+            &["mead"],
+            &[],
+            "(5Z,8Z,11Z)-icosa-5,8,11-trienoic acid",
+            "20:3(5Z,8Z,11Z)",
+        ),
+        entry(
+            &["arachidonic"],
+            &["AA", "ARA"],
+            "(5Z,8Z,11Z,14Z)-icosa-5,8,11,14-tetraenoic acid",
+            "20:4(5Z,8Z,11Z,14Z)",
+        ),
+        entry(
+            &["eicosapentaenoic", "timnodonic"],
+            &["EPA"],
+            "(5Z,8Z,11Z,14Z,17Z)-icosa-5,8,11,14,17-pentaenoic acid",
+            "20:5(5Z,8Z,11Z,14Z,17Z)",
+        ),
+        entry(
+            &["adrenic"],
+            &[],
+            "(7Z,10Z,13Z,16Z)-docosa-7,10,13,16-tetraenoic acid",
+            "22:4(7Z,10Z,13Z,16Z)",
+        ),
+        entry(
+            &["docosapentaenoic", "clupanodonic"],
+            &["DPA"],
+            "(7Z,10Z,13Z,16Z,19Z)-docosa-7,10,13,16,19-pentaenoic acid",
+            "22:5(7Z,10Z,13Z,16Z,19Z)",
+        ),
+        entry(
+            &["docosahexaenoic", "cervonic"],
+            &["DHA"],
+            "(4Z,7Z,10Z,13Z,16Z,19Z)-docosa-4,7,10,13,16,19-hexaenoic acid",
+            "22:6(4Z,7Z,10Z,13Z,16Z,19Z)",
+        ),
+    ];
+
+    /// Entry by trivial name, abbreviation, systematic name, shorthand or
+    /// SMILES
+    ///
+    /// Names are matched case insensitively with or without the `acid`
+    /// suffix, abbreviations exactly. A shorthand matches the same fatty
+    /// acid, a SMILES the same canonical SMILES.
+    pub fn lookup(key: &str) -> Option<&'static Self> {
+        let key = key.trim();
+        let name = normalize(key);
+        Self::ALL
+            .iter()
+            .find(|entry| {
+                entry
+                    .names
+                    .iter()
+                    .chain([&entry.systematic])
+                    .any(|candidate| normalize(candidate) == name)
+                    || entry.abbreviations.contains(&key)
+            })
+            .or_else(|| {
+                let fatty_acid = key.parse::<FattyAcid>().ok()?;
+                Self::ALL
+                    .iter()
+                    .find(|entry| entry.fatty_acid() == fatty_acid)
+            })
+            .or_else(|| Self::identify(&key.parse().ok()?))
+    }
+
+    /// Entry of the structure by canonical comparison
+    pub fn identify(graph: &MoleculeGraph) -> Option<&'static Self> {
+        let smiles = graph.canonical_smiles();
+        let canonical = canonical();
+        Self::ALL
+            .iter()
+            .zip(canonical)
+            .find_map(|(entry, canonical)| (*canonical == smiles).then_some(entry))
+    }
+
+    /// Preferred trivial name
+    pub fn name(&self) -> &'static str {
+        self.names[0]
+    }
+
+    pub fn fatty_acid(&self) -> FattyAcid {
+        self.shorthand.parse().unwrap_or_default()
+    }
+
+    pub fn graph(&self) -> Result<MoleculeGraph> {
+        MoleculeGraph::try_from(&self.fatty_acid())
+    }
+
+    /// Canonical SMILES of the free acid
+    pub fn smiles(&self) -> Result<String> {
+        Ok(self.graph()?.canonical_smiles())
+    }
+}
+
+const fn entry(
+    names: &'static [&'static str],
+    abbreviations: &'static [&'static str],
+    systematic: &'static str,
+    shorthand: &'static str,
+) -> Entry {
+    Entry {
+        names,
+        abbreviations,
+        systematic,
+        shorthand,
+    }
+}
+
+/// Canonical SMILES of the entries, computed once
+fn canonical() -> &'static [String] {
+    static CANONICAL: OnceLock<Vec<String>> = OnceLock::new();
+    CANONICAL.get_or_init(|| {
+        Entry::ALL
+            .iter()
+            .map(|entry| entry.smiles().unwrap_or_default())
+            .collect()
+    })
+}
+
+/// Lowercase name without the `acid` suffix
+fn normalize(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+    match name.strip_suffix("acid") {
+        Some(stripped) => stripped.trim_end().to_owned(),
+        None => name,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn entries() {
+        for entry in Entry::ALL {
+            let fatty_acid: FattyAcid = entry.shorthand.parse().unwrap();
+            assert_eq!(fatty_acid.to_string(), entry.shorthand);
+            assert!(entry.smiles().is_ok());
+        }
+        let smiles: std::collections::BTreeSet<_> = canonical().iter().collect();
+        assert_eq!(smiles.len(), Entry::ALL.len());
+    }
+
+    #[test]
+    fn lookup() {
+        for key in [
+            "oleic",
+            "Oleic acid",
+            "(9Z)-octadec-9-enoic acid",
+            "18:1(9Z)",
+            "CCCCCCCC/C=C\\CCCCCCCC(=O)O",
+            r"OC(=O)CCCCCCC/C=C\CCCCCCCC",
+        ] {
+            assert_eq!(Entry::lookup(key).map(Entry::name), Some("oleic"), "{key}");
+        }
+        assert_eq!(
+            Entry::lookup("DHA").unwrap().shorthand,
+            "22:6(4Z,7Z,10Z,13Z,16Z,19Z)"
+        );
+        assert_eq!(Entry::lookup("nervonic").unwrap().shorthand, "24:1(15Z)");
+        assert_eq!(
+            Entry::lookup("α-linolenic").map(Entry::name),
+            Some("alpha-linolenic")
+        );
+        assert_eq!(
+            Entry::lookup("CCCCCCCC/C=C/CCCCCCCC(=O)O").map(Entry::name),
+            Some("elaidic")
+        );
+        assert!(Entry::lookup("dha").is_none());
+        assert!(Entry::lookup("18:1").is_none());
+        assert!(Entry::lookup("unknown").is_none());
+    }
+
+    #[test]
+    fn identify() {
+        let graph: MoleculeGraph = "CCCCC/C=C\\C/C=C\\C/C=C\\C/C=C\\CCCC(=O)O".parse().unwrap();
+        assert_eq!(
+            Entry::identify(&graph).map(Entry::name),
+            Some("arachidonic")
+        );
+        let graph: MoleculeGraph = "CCCCCCCC/C=C\\CCCCCCCC(=O)OC".parse().unwrap();
+        assert!(Entry::identify(&graph).is_none());
+    }
+}
@@ -0,0 +1,160 @@
+use super::{
+    class::Lipid,
+    error::{Error, Result},
+    fatty_acid::FattyAcid,
+};
+use crate::semantic::{Bond, Geometry, MoleculeGraph};
+use std::collections::BTreeMap;
+
+/// Chromatographic increments of the unsaturated bonds
+///
+/// Added to the carbon number for each unsaturated bond of a chain.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Increments {
+    /// Cis double bond or unknown geometry
+    pub cis: f64,
+    /// Trans double bond
+    pub trans: f64,
+    /// Triple bond
+    pub triple: f64,
+    /// Correction of a double bond by its Δ locant
+    pub positions: BTreeMap<u8, f64>,
+}
+
+impl Increments {
+    /// RP-HPLC equivalent carbon number, `ECN = CN - 2·DB`
+    pub fn ecn() -> Self {
+        Self {
+            cis: -2.0,
+            trans: -2.0,
+            triple: -2.0,
+            positions: BTreeMap::new(),
+        }
+    }
+
+    fn increment(&self, fatty_acid: &FattyAcid) -> f64 {
+        let mut sum = fatty_acid.carbons as f64;
+        for unsaturation in &fatty_acid.unsaturations {
+            sum += match (unsaturation.bond, unsaturation.geometry) {
+                (Bond::Triple, _) => self.triple,
+                (_, Some(Geometry::E)) => self.trans,
+                _ => self.cis,
+            };
+            if unsaturation.bond == Bond::Double {
+                if let Some(correction) = unsaturation
+                    .index
+                    .and_then(|index| self.positions.get(&index))
+                {
+                    sum += correction;
+                }
+            }
+        }
+        sum
+    }
+}
+
+impl Lipid {
+    /// Equivalent carbon number, the sum over the chains
+    pub fn equivalent_carbon_number(&self, increments: &Increments) -> f64 {
+        self.chains()
+            .into_iter()
+            .map(|chain| increments.increment(chain))
+            .sum()
+    }
+
+    /// Equivalent chain length of a single chain lipid (FAME)
+    ///
+    /// The increments are the fractional chain lengths of the column.
+    pub fn equivalent_chain_length(&self, increments: &Increments) -> Result<f64> {
+        match self.chains()[..] {
+            [chain] => Ok(increments.increment(chain)),
+            _ => Err(Error::UnknownClass),
+        }
+    }
+}
+
+impl MoleculeGraph {
+    /// Equivalent carbon number of the recognised lipid
+    pub fn equivalent_carbon_number(&self, increments: &Increments) -> Result<f64> {
+        Ok(Lipid::try_from(self)?.equivalent_carbon_number(increments))
+    }
+
+    /// Equivalent chain length of the recognised FAME
+    pub fn equivalent_chain_length(&self, increments: &Increments) -> Result<f64> {
+        Lipid::try_from(self)?.equivalent_chain_length(increments)
+    }
+}
+
+/// Equivalent chain length from the retention time
+///
+/// Logarithmic interpolation between the saturated standards (carbons,
+/// retention time) bracketing the retention time, extrapolated from the
+/// closest pair outside them. `None` with less than two standards.
+pub fn equivalent_chain_length(retention: f64, standards: &[(u8, f64)]) -> Option<f64> {
+    let mut standards = standards.to_vec();
+    standards.sort_by(|a, b| a.1.total_cmp(&b.1));
+    let index = standards
+        .windows(2)
+        .position(|window| retention <= window[1].1)
+        .unwrap_or(standards.len().checked_sub(2)?);
+    let [(n, tn), (m, tm)] = standards.get(index..index + 2)? else {
+        return None;
+    };
+    let (n, m) = (*n as f64, *m as f64);
+    Some(n + (m - n) * (retention.ln() - tn.ln()) / (tm.ln() - tn.ln()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ecn() {
+        let increments = Increments::ecn();
+        for (smiles, ecn) in [
+            (
+                "CCCCCCCCCCCCCCCC(=O)OCC(OC(=O)CCCCCCC/C=C\\CCCCCCCC)COC(=O)CCCCCCC/C=C\\CCCCCCCC",
+                48.0,
+            ),
+            (
+                "CCCCC/C=C\\C/C=C\\CCCCCCCC(=O)OCC(OC(=O)CCCCCCC/C=C\\C/C=C\\CCCCC)COC(=O)CCCCCCC/C=C\\C/C=C\\CCCCC",
+                42.0,
+            ),
+            ("CCCCCCCC/C=C\\CCCCCCCC(=O)OC", 16.0),
+        ] {
+            let graph: MoleculeGraph = smiles.parse().unwrap();
+            assert_eq!(graph.equivalent_carbon_number(&increments).unwrap(), ecn);
+        }
+        // Partial corrections
+        let increments = Increments {
+            trans: -1.6,
+            positions: BTreeMap::from([(9, 0.1)]),
+            ..Increments::ecn()
+        };
+        let elaidic: MoleculeGraph = "CCCCCCCC/C=C/CCCCCCCC(=O)O".parse().unwrap();
+        let ecn = elaidic.equivalent_carbon_number(&increments).unwrap();
+        assert!((ecn - 16.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn ecl() {
+        let increments = Increments {
+            cis: 0.3,
+            trans: 0.2,
+            ..Default::default()
+        };
+        let methyl_oleate: MoleculeGraph = "CCCCCCCC/C=C\\CCCCCCCC(=O)OC".parse().unwrap();
+        let ecl = methyl_oleate.equivalent_chain_length(&increments).unwrap();
+        assert!((ecl - 18.3).abs() < 1e-12);
+        let triacylglycerol: MoleculeGraph = "CCCC(=O)OCC(OC(=O)CCC)COC(=O)CCC".parse().unwrap();
+        assert!(triacylglycerol
+            .equivalent_chain_length(&increments)
+            .is_err());
+        let standards = [(16, 10.0), (18, 14.0), (20, 19.0)];
+        assert_eq!(equivalent_chain_length(14.0, &standards), Some(18.0));
+        let ecl = equivalent_chain_length(11.0, &standards).unwrap();
+        assert!((ecl - (16.0 + 2.0 * (1.1f64).ln() / (1.4f64).ln())).abs() < 1e-12);
+        assert!(equivalent_chain_length(22.0, &standards).unwrap() > 20.0);
+        assert_eq!(equivalent_chain_length(11.0, &standards[..1]), None);
+    }
+}
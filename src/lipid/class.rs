@@ -0,0 +1,384 @@
+use super::{
+    error::{Error, Result},
+    fatty_acid::{carboxyl_oxygens, FattyAcid},
+    glycerolipid::Glycerolipid,
+    glycerophospholipid::{fragment, Glycerophospholipid, Headgroup},
+};
+use crate::semantic::{Element, MoleculeGraph};
+use petgraph::{graph::NodeIndex, visit::EdgeRef};
+use std::fmt::{self, Display, Formatter};
+
+/// Steroid nucleus ring atoms (cyclopenta[a]phenanthrene)
+const STEROID_RING_ATOMS: usize = 17;
+
+/// Lipid
+///
+/// Classified lipid with its chains.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Lipid {
+    /// Free fatty acid
+    FattyAcid(FattyAcid),
+    /// Fatty alcohol, the chain numbered from the hydroxyl carbon
+    FattyAlcohol(FattyAcid),
+    /// Wax ester of a fatty acid and a fatty alcohol
+    WaxEster {
+        alcohol: FattyAcid,
+        fatty_acid: FattyAcid,
+    },
+    /// Fatty acid methyl ester
+    MethylEster(FattyAcid),
+    /// Fatty acid ethyl ester
+    EthylEster(FattyAcid),
+    /// Fatty acid ester of a sterol
+    SterolEster(FattyAcid),
+    Glycerolipid(Glycerolipid),
+    Glycerophospholipid(Glycerophospholipid),
+}
+
+impl Lipid {
+    pub fn category(&self) -> Category {
+        self.class().category()
+    }
+
+    pub fn class(&self) -> Class {
+        match self {
+            Self::FattyAcid(_) => Class::FreeFattyAcid,
+            Self::FattyAlcohol(_) => Class::FattyAlcohol,
+            Self::WaxEster { .. } => Class::WaxEster,
+            Self::MethylEster(_) => Class::MethylEster,
+            Self::EthylEster(_) => Class::EthylEster,
+            Self::SterolEster(_) => Class::SterolEster,
+            Self::Glycerolipid(glycerolipid) => match glycerolipid.fatty_acids().count() {
+                1 => Class::Monoacylglycerol,
+                2 => Class::Diacylglycerol,
+                _ => Class::Triacylglycerol,
+            },
+            Self::Glycerophospholipid(glycerophospholipid) => {
+                if glycerophospholipid.is_lyso() {
+                    Class::Lysoglycerophospholipid(glycerophospholipid.headgroup)
+                } else {
+                    Class::Glycerophospholipid(glycerophospholipid.headgroup)
+                }
+            }
+        }
+    }
+
+    /// Chains, the alcohol of a wax ester first
+    pub fn chains(&self) -> Vec<&FattyAcid> {
+        match self {
+            Self::FattyAcid(fatty_acid)
+            | Self::FattyAlcohol(fatty_acid)
+            | Self::MethylEster(fatty_acid)
+            | Self::EthylEster(fatty_acid)
+            | Self::SterolEster(fatty_acid) => vec![fatty_acid],
+            Self::WaxEster {
+                alcohol,
+                fatty_acid,
+            } => vec![alcohol, fatty_acid],
+            Self::Glycerolipid(glycerolipid) => glycerolipid.fatty_acids().collect(),
+            Self::Glycerophospholipid(glycerophospholipid) => {
+                glycerophospholipid.fatty_acids().collect()
+            }
+        }
+    }
+}
+
+impl Display for Lipid {
+    /// Class abbreviation followed by the chains, `FAME 18:1(9Z)` or
+    /// `WE 16:0/18:1(9Z)`
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Glycerolipid(glycerolipid) => glycerolipid.fmt(f),
+            Self::Glycerophospholipid(glycerophospholipid) => glycerophospholipid.fmt(f),
+            _ => {
+                write!(f, "{} ", self.class())?;
+                for (index, chain) in self.chains().into_iter().enumerate() {
+                    if index > 0 {
+                        f.write_str("/")?;
+                    }
+                    write!(f, "{chain}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl TryFrom<&MoleculeGraph> for Lipid {
+    type Error = Error;
+
+    fn try_from(value: &MoleculeGraph) -> Result<Self> {
+        if let Ok(glycerophospholipid) = Glycerophospholipid::try_from(value) {
+            return Ok(Self::Glycerophospholipid(glycerophospholipid));
+        }
+        if let Ok(glycerolipid) = Glycerolipid::try_from(value) {
+            return Ok(Self::Glycerolipid(glycerolipid));
+        }
+        if let Ok(fatty_acid) = FattyAcid::try_from(value) {
+            return Ok(Self::FattyAcid(fatty_acid));
+        }
+        let heavy: Vec<_> = value
+            .node_indices()
+            .filter(|&index| value[index].element != Some(Element::H))
+            .collect();
+        ester(value, &heavy)
+            .or_else(|| alcohol(value, &heavy))
+            .ok_or(Error::UnknownClass)
+    }
+}
+
+/// Lipid category
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Category {
+    FattyAcyls,
+    Glycerolipids,
+    Glycerophospholipids,
+    Sterols,
+}
+
+impl Display for Category {
+    /// LIPID MAPS category code
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::FattyAcyls => f.write_str("FA"),
+            Self::Glycerolipids => f.write_str("GL"),
+            Self::Glycerophospholipids => f.write_str("GP"),
+            Self::Sterols => f.write_str("ST"),
+        }
+    }
+}
+
+/// Lipid class
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Class {
+    FreeFattyAcid,
+    FattyAlcohol,
+    WaxEster,
+    MethylEster,
+    EthylEster,
+    SterolEster,
+    Monoacylglycerol,
+    Diacylglycerol,
+    Triacylglycerol,
+    Glycerophospholipid(Headgroup),
+    Lysoglycerophospholipid(Headgroup),
+}
+
+impl Class {
+    pub fn category(&self) -> Category {
+        match self {
+            Self::FreeFattyAcid
+            | Self::FattyAlcohol
+            | Self::WaxEster
+            | Self::MethylEster
+            | Self::EthylEster => Category::FattyAcyls,
+            Self::SterolEster => Category::Sterols,
+            Self::Monoacylglycerol | Self::Diacylglycerol | Self::Triacylglycerol => {
+                Category::Glycerolipids
+            }
+            Self::Glycerophospholipid(_) | Self::Lysoglycerophospholipid(_) => {
+                Category::Glycerophospholipids
+            }
+        }
+    }
+}
+
+impl Display for Class {
+    /// Class abbreviation
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::FreeFattyAcid => f.write_str("FA"),
+            Self::FattyAlcohol => f.write_str("FOH"),
+            Self::WaxEster => f.write_str("WE"),
+            Self::MethylEster => f.write_str("FAME"),
+            Self::EthylEster => f.write_str("FAEE"),
+            Self::SterolEster => f.write_str("SE"),
+            Self::Monoacylglycerol => f.write_str("MG"),
+            Self::Diacylglycerol => f.write_str("DG"),
+            Self::Triacylglycerol => f.write_str("TG"),
+            Self::Glycerophospholipid(headgroup) => f.write_str(headgroup.abbreviation()),
+            Self::Lysoglycerophospholipid(headgroup) => f.write_str(headgroup.lyso()),
+        }
+    }
+}
+
+/// Recognises a fatty acid ester of an alkyl alcohol or a sterol
+fn ester(graph: &MoleculeGraph, heavy: &[NodeIndex]) -> Option<Lipid> {
+    for carboxyl in graph.node_indices() {
+        let Some((_, oxygen)) = carboxyl_oxygens(graph, carboxyl) else {
+            continue;
+        };
+        let Ok((fatty_acid, atoms)) = FattyAcid::acyl(graph, carboxyl) else {
+            continue;
+        };
+        let alcohol = fragment(graph, oxygen, carboxyl);
+        if atoms.len() + alcohol.len() != heavy.len()
+            || alcohol[1..]
+                .iter()
+                .any(|&index| graph[index].element != Some(Element::C))
+        {
+            continue;
+        }
+        let Some(&start) = alcohol.get(1) else {
+            continue;
+        };
+        let bonds = alcohol[1..]
+            .iter()
+            .flat_map(|&index| {
+                graph
+                    .neighbors(index)
+                    .filter(|to| alcohol[1..].contains(to))
+            })
+            .count()
+            / 2;
+        if bonds + 1 == alcohol.len() - 1 {
+            let Ok((chain, chain_atoms)) = FattyAcid::alkyl(graph, start) else {
+                continue;
+            };
+            // Vinyl and similar esters are not classified
+            if chain_atoms.len() + 1 != alcohol.len()
+                || chain.carbons <= 2 && !chain.unsaturations.is_empty()
+            {
+                continue;
+            }
+            return Some(match chain.carbons {
+                1 => Lipid::MethylEster(fatty_acid),
+                2 => Lipid::EthylEster(fatty_acid),
+                _ => Lipid::WaxEster {
+                    alcohol: chain,
+                    fatty_acid,
+                },
+            });
+        }
+        if is_sterol(graph, &alcohol[1..], start) {
+            return Some(Lipid::SterolEster(fatty_acid));
+        }
+    }
+    None
+}
+
+/// Recognises a fatty alcohol
+fn alcohol(graph: &MoleculeGraph, heavy: &[NodeIndex]) -> Option<Lipid> {
+    let mut oxygens = heavy
+        .iter()
+        .filter(|&&index| graph[index].element != Some(Element::C));
+    let (Some(&oxygen), None) = (oxygens.next(), oxygens.next()) else {
+        return None;
+    };
+    if graph[oxygen].element != Some(Element::O) || graph[oxygen].charge != 0 {
+        return None;
+    }
+    let mut carbons = graph
+        .edges(oxygen)
+        .filter(|edge| graph[edge.target()].element != Some(Element::H));
+    let (Some(edge), None) = (carbons.next(), carbons.next()) else {
+        return None;
+    };
+    if edge.weight().valence() != 1 {
+        return None;
+    }
+    let carbon = edge.target();
+    let (chain, atoms) = FattyAcid::alkyl(graph, carbon).ok()?;
+    (atoms.len() + 1 == heavy.len()).then_some(Lipid::FattyAlcohol(chain))
+}
+
+/// Is the carbon skeleton a sterol with the hydroxyl carbon in its steroid
+/// nucleus
+///
+/// Side chains are pruned, the remaining ring atoms have to form four fused
+/// rings.
+fn is_sterol(graph: &MoleculeGraph, atoms: &[NodeIndex], hydroxyl: NodeIndex) -> bool {
+    let mut rings = atoms.to_vec();
+    loop {
+        let degree = |index: NodeIndex, rings: &[NodeIndex]| {
+            graph
+                .neighbors(index)
+                .filter(|to| rings.contains(to))
+                .count()
+        };
+        let pruned: Vec<_> = rings
+            .iter()
+            .copied()
+            .filter(|&index| degree(index, &rings) > 1)
+            .collect();
+        if pruned.len() == rings.len() {
+            break;
+        }
+        rings = pruned;
+    }
+    let bonds = rings
+        .iter()
+        .flat_map(|&index| graph.neighbors(index).filter(|to| rings.contains(to)))
+        .count()
+        / 2;
+    rings.len() == STEROID_RING_ATOMS && bonds + 1 - rings.len() == 4 && rings.contains(&hydroxyl)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn classification() {
+        for (smiles, class, lipid) in [
+            (
+                "CCCCCCCC/C=C\\CCCCCCCC(=O)O",
+                Class::FreeFattyAcid,
+                "FA 18:1(9Z)",
+            ),
+            ("CCCCCCCCCCCCCCCCCCO", Class::FattyAlcohol, "FOH 18:0"),
+            (
+                "CCCCCCCCCCCCCCCC(=O)OCCCCCCCC/C=C\\CCCCCCCC",
+                Class::WaxEster,
+                "WE 18:1(9Z)/16:0",
+            ),
+            (
+                "CCCCCCCC/C=C\\CCCCCCCC(=O)OC",
+                Class::MethylEster,
+                "FAME 18:1(9Z)",
+            ),
+            ("CCCCCCCCCCCCCCCC(=O)OCC", Class::EthylEster, "FAEE 16:0"),
+            (
+                "CC(C)CCCC(C)C1CCC2C1(CCC3C2CC=C4C3(CCC(C4)OC(=O)CCCCCCC/C=C\\CCCCCCCC)C)C",
+                Class::SterolEster,
+                "SE 18:1(9Z)",
+            ),
+            (
+                "CCCCCCCCCCCCCCCC(=O)OCC(O)CO",
+                Class::Monoacylglycerol,
+                "MG(16:0/0:0/0:0)[rac]",
+            ),
+            (
+                "CCCCCCCCCCCCCCCC(=O)OCC(OC(=O)CCCCCCCCCCCCCCC)COC(=O)CCCCCCCCCCCCCCC",
+                Class::Triacylglycerol,
+                "TG(16:0/16:0/16:0)",
+            ),
+            (
+                "CCCCCCCCCCCCCCCC(=O)OCC(O)COP(=O)([O-])OCC[NH3+]",
+                Class::Lysoglycerophospholipid(Headgroup::Ethanolamine),
+                "LPE(16:0/0:0)[rac]",
+            ),
+        ] {
+            let graph: MoleculeGraph = smiles.parse().unwrap();
+            let classified = Lipid::try_from(&graph).unwrap();
+            assert_eq!(classified.class(), class, "{smiles}");
+            assert_eq!(classified.to_string(), lipid);
+        }
+    }
+
+    #[test]
+    fn category() {
+        let graph: MoleculeGraph =
+            "CCCCCCCCCCCCCCCC(=O)OC[C@@H](OC(=O)CCCCCCCCCCCCCCC)COP(=O)([O-])OCC[N+](C)(C)C"
+                .parse()
+                .unwrap();
+        let classified = Lipid::try_from(&graph).unwrap();
+        assert_eq!(classified.category(), Category::Glycerophospholipids);
+        assert_eq!(classified.class().to_string(), "PC");
+        assert_eq!(classified.chains().len(), 2);
+        for smiles in ["CCN", "CCCCC(=O)OC(C)C", "CCCCCCC(O)C"] {
+            let graph: MoleculeGraph = smiles.parse().unwrap();
+            assert!(Lipid::try_from(&graph).is_err(), "{smiles}");
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use super::{
+    error::{Error, Result},
+    fatty_acid::FattyAcid,
+    glycerolipid::{Glycerolipid, Positions},
+};
+use crate::semantic::MoleculeGraph;
+use std::collections::BTreeMap;
+
+/// Fatty acid composition
+///
+/// Amount (mole % or fraction) of each fatty acid.
+pub type Composition = BTreeMap<FattyAcid, f64>;
+
+/// TAG positional distribution
+///
+/// Predicts the TAG species of a fat from its fatty acid composition and,
+/// optionally, the sn-2 composition from pancreatic lipase hydrolysis.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Distribution {
+    total: Composition,
+    sn2: Option<Composition>,
+}
+
+impl Distribution {
+    pub fn new(total: Composition) -> Result<Self> {
+        Ok(Self {
+            total: normalize(total)?,
+            sn2: None,
+        })
+    }
+
+    /// Sets the sn-2 composition (2-MAG after lipase hydrolysis)
+    pub fn with_sn2(self, sn2: Composition) -> Result<Self> {
+        Ok(Self {
+            sn2: Some(normalize(sn2)?),
+            ..self
+        })
+    }
+
+    /// Mole fractions of the fatty acids
+    pub fn total(&self) -> &Composition {
+        &self.total
+    }
+
+    /// Mole fractions at the sn-2 position under the model
+    pub fn sn2(&self, model: Model) -> Result<Composition> {
+        match model {
+            Model::Random => Ok(self.total.clone()),
+            Model::Regiospecific => self
+                .sn2
+                .clone()
+                .ok_or(Error::Composition("sn-2 composition required")),
+            Model::Preferential => {
+                let unsaturated: f64 = self
+                    .total
+                    .iter()
+                    .filter(|(fatty_acid, _)| fatty_acid.unsaturation() > 0)
+                    .map(|(_, fraction)| fraction)
+                    .sum();
+                let saturated = 1.0 - unsaturated;
+                // Saturated acids only occupy sn-2 left over by the
+                // unsaturated ones
+                let excess = (1.0 - 3.0 * unsaturated).max(0.0);
+                Ok(self
+                    .total
+                    .iter()
+                    .map(|(fatty_acid, &fraction)| {
+                        let sn2 = if fatty_acid.unsaturation() > 0 {
+                            fraction / unsaturated * (1.0 - excess)
+                        } else if saturated > 0.0 {
+                            fraction / saturated * excess
+                        } else {
+                            0.0
+                        };
+                        (fatty_acid.clone(), sn2)
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /// Mole fractions at each of the sn-1 and sn-3 positions under the model
+    ///
+    /// Derived from the mass balance `3 · total = 2 · sn-1,3 + sn-2`,
+    /// negative values clipped.
+    pub fn sn13(&self, model: Model) -> Result<Composition> {
+        let sn2 = self.sn2(model)?;
+        let mut sn13 = Composition::new();
+        for (fatty_acid, &fraction) in &self.total {
+            let sn2 = sn2.get(fatty_acid).copied().unwrap_or_default();
+            sn13.insert(fatty_acid.clone(), ((3.0 * fraction - sn2) / 2.0).max(0.0));
+        }
+        normalize(sn13)
+    }
+
+    /// TAG species with their mole fractions, most abundant first
+    ///
+    /// sn-1 and sn-3 are equivalent in all models, so asymmetric species are
+    /// racemic.
+    pub fn species(&self, model: Model) -> Result<Vec<Species>> {
+        let sn2 = self.sn2(model)?;
+        let sn13 = self.sn13(model)?;
+        let mut species = Vec::new();
+        for (first, &x) in &sn13 {
+            for (second, &y) in &sn2 {
+                for (third, &z) in sn13.range(first..) {
+                    let mut fraction = x * y * z;
+                    if first != third {
+                        fraction *= 2.0;
+                    }
+                    if fraction > 0.0 {
+                        species.push(Species::new(
+                            [first.clone(), second.clone(), third.clone()],
+                            fraction,
+                        ));
+                    }
+                }
+            }
+        }
+        species.sort_by(|a, b| {
+            b.fraction
+                .total_cmp(&a.fraction)
+                .then_with(|| a.glycerolipid.cmp(&b.glycerolipid))
+        });
+        Ok(species)
+    }
+}
+
+/// Positional distribution model
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Model {
+    /// 1,2,3-random: every position has the overall composition
+    Random,
+    /// 1,3-random-2-random (Vander Wal, 1960): the measured sn-2
+    /// composition, sn-1 and sn-3 from the mass balance
+    Regiospecific,
+    /// 1,3-random-2-random with the unsaturated acids preferentially at
+    /// sn-2 (Gunstone, 1965)
+    Preferential,
+}
+
+/// TAG species
+#[derive(Clone, Debug, PartialEq)]
+pub struct Species {
+    pub glycerolipid: Glycerolipid,
+    /// Mole fraction
+    pub fraction: f64,
+}
+
+impl Species {
+    /// Species with sn-1 and sn-3 interchangeable
+    pub fn new(acyls: [FattyAcid; 3], fraction: f64) -> Self {
+        let positions = if acyls[0] == acyls[2] {
+            Positions::Stereospecific
+        } else {
+            Positions::Racemic
+        };
+        Self {
+            glycerolipid: Glycerolipid::new(acyls.map(Some), positions),
+            fraction,
+        }
+    }
+
+    pub fn graph(&self) -> Result<MoleculeGraph> {
+        MoleculeGraph::try_from(&self.glycerolipid)
+    }
+}
+
+/// Scales the composition to mole fractions
+pub(super) fn normalize(composition: Composition) -> Result<Composition> {
+    if composition
+        .values()
+        .any(|amount| !amount.is_finite() || *amount < 0.0)
+    {
+        return Err(Error::Composition("negative or non-finite amount"));
+    }
+    let sum: f64 = composition.values().sum();
+    if sum <= 0.0 {
+        return Err(Error::Composition("empty"));
+    }
+    Ok(composition
+        .into_iter()
+        .map(|(fatty_acid, amount)| (fatty_acid, amount / sum))
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn composition(items: &[(&str, f64)]) -> Composition {
+        items
+            .iter()
+            .map(|&(fatty_acid, amount)| (fatty_acid.parse().unwrap(), amount))
+            .collect()
+    }
+
+    fn fractions(species: &[Species]) -> Vec<(String, f64)> {
+        species
+            .iter()
+            .map(|species| {
+                let fraction = (species.fraction * 1e6).round() / 1e6;
+                (species.glycerolipid.to_string(), fraction)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn random() {
+        let distribution =
+            Distribution::new(composition(&[("16:0", 50.0), ("18:1(9Z)", 50.0)])).unwrap();
+        let species = distribution.species(Model::Random).unwrap();
+        assert_eq!(
+            fractions(&species),
+            [
+                ("TG(16:0/16:0/18:1(9Z))[rac]".to_owned(), 0.25),
+                ("TG(16:0/18:1(9Z)/18:1(9Z))[rac]".to_owned(), 0.25),
+                ("TG(16:0/16:0/16:0)".to_owned(), 0.125),
+                ("TG(16:0/18:1(9Z)/16:0)".to_owned(), 0.125),
+                ("TG(18:1(9Z)/16:0/18:1(9Z))".to_owned(), 0.125),
+                ("TG(18:1(9Z)/18:1(9Z)/18:1(9Z))".to_owned(), 0.125),
+            ]
+        );
+        let sum: f64 = species.iter().map(|species| species.fraction).sum();
+        assert!((sum - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn regiospecific() {
+        let distribution = Distribution::new(composition(&[("16:0", 50.0), ("18:1(9Z)", 50.0)]))
+            .unwrap()
+            .with_sn2(composition(&[("18:1(9Z)", 100.0)]))
+            .unwrap();
+        let sn13 = distribution.sn13(Model::Regiospecific).unwrap();
+        assert_eq!(sn13[&"16:0".parse().unwrap()], 0.75);
+        let species = distribution.species(Model::Regiospecific).unwrap();
+        assert_eq!(
+            fractions(&species),
+            [
+                ("TG(16:0/18:1(9Z)/16:0)".to_owned(), 0.5625),
+                ("TG(16:0/18:1(9Z)/18:1(9Z))[rac]".to_owned(), 0.375),
+                ("TG(18:1(9Z)/18:1(9Z)/18:1(9Z))".to_owned(), 0.0625),
+            ]
+        );
+        assert_eq!(
+            species[0].graph().unwrap().to_string(),
+            "CCCCCCCCCCCCCCCC(=O)OCC(OC(=O)CCCCCCC/C=C\\CCCCCCCC)COC(=O)CCCCCCCCCCCCCCC"
+        );
+        assert!(Distribution::new(composition(&[("16:0", 1.0)]))
+            .unwrap()
+            .species(Model::Regiospecific)
+            .is_err());
+    }
+
+    #[test]
+    fn preferential() {
+        let distribution =
+            Distribution::new(composition(&[("16:0", 50.0), ("18:1(9Z)", 50.0)])).unwrap();
+        assert_eq!(
+            fractions(&distribution.species(Model::Preferential).unwrap())[0],
+            ("TG(16:0/18:1(9Z)/16:0)".to_owned(), 0.5625)
+        );
+        // Saturated excess at sn-2
+        let distribution =
+            Distribution::new(composition(&[("16:0", 80.0), ("18:1(9Z)", 20.0)])).unwrap();
+        let sn2 = distribution.sn2(Model::Preferential).unwrap();
+        assert!((sn2[&"18:1(9Z)".parse().unwrap()] - 0.6).abs() < 1e-12);
+        assert!((sn2[&"16:0".parse().unwrap()] - 0.4).abs() < 1e-12);
+        assert!(Distribution::new(composition(&[("16:0", -1.0)])).is_err());
+    }
+}
@@ -0,0 +1,51 @@
+use std::ops::Range;
+use thiserror::Error;
+
+/// Result
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Lipid error
+#[derive(Clone, Error, Debug)]
+pub enum Error {
+    #[error("carboxyl group not found")]
+    CarboxylNotFound,
+    #[error("hydroxyl group not found")]
+    HydroxylNotFound,
+    #[error("glycerol backbone not found")]
+    GlycerolNotFound,
+    #[error("unexpected configuration at atom {index}")]
+    Configuration { index: usize },
+    #[error("unexpected atom {{ index: {index} }}")]
+    UnexpectedAtom { index: usize },
+    #[error("invalid shorthand {0:?}")]
+    Shorthand(String),
+    #[error("invalid carbon count {0}")]
+    Carbons(u8),
+    #[error("invalid locant {{ index: {index}, carbons: {carbons} }}")]
+    Locant { index: u8, carbons: u8 },
+    #[error("unsaturation count mismatch {{ expected: {expected}, found: {found} }}")]
+    Count { expected: u8, found: u8 },
+    #[error("valence exceeded at carbon {index}")]
+    Valence { index: u8 },
+    #[error("invalid composition: {0}")]
+    Composition(&'static str),
+    #[error("extent {0} out of range")]
+    Extent(f64),
+    #[error("unknown lipid class")]
+    UnknownClass,
+    #[error("unsaturation positions are unknown")]
+    UnknownPositions,
+    #[error("sn-positions are unknown")]
+    UnknownSnPositions,
+    #[error(transparent)]
+    Name(#[from] NameError),
+}
+
+/// Name syntax error
+#[derive(Clone, Error, Debug)]
+#[error("name syntax error {{ expected: {expected:?}, found: {found:?}, span: {span:?} }}")]
+pub struct NameError {
+    pub expected: &'static [&'static str],
+    pub found: String,
+    pub span: Range<usize>,
+}
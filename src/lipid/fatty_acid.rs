@@ -0,0 +1,666 @@
+use super::error::{Error, Result};
+use crate::semantic::{Atom, Bond, Element, Geometry, MoleculeGraph, Notation};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, one_of, space0, space1, u8},
+    combinator::{all_consuming, map, opt},
+    multi::separated_list1,
+    sequence::{delimited, pair, preceded, separated_pair, tuple},
+    IResult,
+};
+use petgraph::{graph::NodeIndex, visit::EdgeRef};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+/// Fatty acid
+///
+/// Straight carbon chain numbered from the carboxyl carbon (`1`) with its
+/// unsaturated bonds and substituents.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct FattyAcid {
+    pub carbons: u8,
+    pub unsaturations: Vec<Unsaturation>,
+    pub substituents: Vec<Substituent>,
+}
+
+impl FattyAcid {
+    pub fn new(carbons: u8, unsaturations: impl IntoIterator<Item = Unsaturation>) -> Self {
+        let mut unsaturations: Vec<_> = unsaturations.into_iter().collect();
+        unsaturations.sort();
+        Self {
+            carbons,
+            unsaturations,
+            substituents: Vec::new(),
+        }
+    }
+
+    /// Unsaturated bonds count
+    pub fn unsaturation(&self) -> u8 {
+        self.unsaturations.len() as _
+    }
+
+    /// Are all unsaturation positions known
+    pub fn is_positional(&self) -> bool {
+        self.unsaturations
+            .iter()
+            .all(|unsaturation| unsaturation.index.is_some())
+    }
+
+    /// Omega index of the unsaturation closest to the methyl end
+    ///
+    /// `None` when a position is unknown or past the chain.
+    pub fn omega(&self) -> Option<u8> {
+        let index = self
+            .unsaturations
+            .iter()
+            .map(|unsaturation| unsaturation.index)
+            .max()??;
+        self.carbons.checked_sub(index)
+    }
+
+    /// Shorthand in the given notation
+    pub fn shorthand(&self, shorthand: Shorthand) -> impl Display + '_ {
+        Formatted {
+            fatty_acid: self,
+            shorthand,
+        }
+    }
+
+    /// Acyl starting at the carboxyl carbon
+    ///
+    /// Returns the fatty acid and the atoms of the acyl, the single bonded
+    /// carboxyl oxygen excluded.
+    pub(crate) fn acyl(
+        graph: &MoleculeGraph,
+        carboxyl: NodeIndex,
+    ) -> Result<(Self, Vec<NodeIndex>)> {
+        let (oxo, _) = carboxyl_oxygens(graph, carboxyl).ok_or(Error::CarboxylNotFound)?;
+        let (fatty_acid, mut atoms) = Self::alkyl(graph, carboxyl)?;
+        atoms.push(oxo);
+        Ok((fatty_acid, atoms))
+    }
+
+    /// Carbon chain starting at the carbon, numbered from it
+    ///
+    /// Returns the chain and its atoms, the heteroatoms of the first carbon
+    /// excluded.
+    pub(crate) fn alkyl(graph: &MoleculeGraph, start: NodeIndex) -> Result<(Self, Vec<NodeIndex>)> {
+        let chain = graph.chain(start);
+        let mut atoms = chain.to_vec();
+        let mut substituents = Vec::new();
+        for (index, &from) in chain.iter().enumerate().skip(1) {
+            for to in graph.neighbors(from) {
+                if chain.contains(&to) || graph[to].element == Some(Element::H) {
+                    continue;
+                }
+                if !is_methyl(graph, to, from) {
+                    return Err(Error::UnexpectedAtom { index: to.index() });
+                }
+                atoms.push(to);
+                substituents.push(Substituent {
+                    index: index as u8 + 1,
+                    kind: Functional::Methyl,
+                });
+            }
+        }
+        let unsaturations = chain
+            .positions(graph, Notation::Delta)
+            .into_iter()
+            .filter(|position| position.element == Element::C)
+            .map(|position| Unsaturation {
+                index: Some(position.locant as _),
+                bond: position.bond,
+                geometry: position.geometry,
+            });
+        let mut fatty_acid = Self::new(chain.len() as _, unsaturations);
+        fatty_acid.substituents = substituents;
+        fatty_acid.substituents.sort();
+        Ok((fatty_acid, atoms))
+    }
+
+    /// Adds the acyl to the graph
+    ///
+    /// Atoms are added starting at the carboxyl carbon for
+    /// [`Notation::Delta`] and at the methyl end for [`Notation::Omega`].
+    /// Returns the carboxyl carbon.
+    pub(crate) fn attach(&self, graph: &mut MoleculeGraph, from: Notation) -> Result<NodeIndex> {
+        self.validate()?;
+        if !self.is_positional() {
+            return Err(Error::UnknownPositions);
+        }
+        let bonds: BTreeMap<_, _> = self
+            .unsaturations
+            .iter()
+            .filter_map(|unsaturation| Some((unsaturation.index?, unsaturation)))
+            .collect();
+        let locants: Vec<_> = match from {
+            Notation::Delta => (1..=self.carbons).collect(),
+            Notation::Omega => (1..=self.carbons).rev().collect(),
+        };
+        // Carbon indices by locant
+        let mut carbons = vec![NodeIndex::end(); self.carbons as usize + 1];
+        let mut previous: Option<u8> = None;
+        for &locant in &locants {
+            carbons[locant as usize] = graph.add_node(carbon());
+            if let Some(previous) = previous {
+                let bond = bonds
+                    .get(&locant.min(previous))
+                    .map_or(Bond::Single, |unsaturation| unsaturation.bond);
+                graph.add_edge(carbons[previous as usize], carbons[locant as usize], bond);
+            }
+            if locant == 1 && from == Notation::Delta {
+                oxo(graph, carbons[1]);
+            }
+            for substituent in &self.substituents {
+                if substituent.index == locant {
+                    let methyl = graph.add_node(carbon());
+                    graph.add_edge(carbons[locant as usize], methyl, Bond::Single);
+                }
+            }
+            previous = Some(locant);
+        }
+        let mut geometries: Vec<_> = bonds.iter().collect();
+        if from == Notation::Omega {
+            geometries.reverse();
+        }
+        for (&locant, unsaturation) in geometries {
+            let locant = locant as usize;
+            let Some(geometry) = unsaturation.geometry else {
+                continue;
+            };
+            if locant > 1 && locant + 2 <= self.carbons as usize {
+                graph.set_geometry(
+                    carbons[locant + 2],
+                    carbons[locant + 1],
+                    carbons[locant],
+                    carbons[locant - 1],
+                    geometry,
+                );
+            }
+        }
+        if from == Notation::Omega {
+            oxo(graph, carbons[1]);
+        }
+        Ok(carbons[1])
+    }
+
+    fn validate(&self) -> Result<()> {
+        let carbons = self.carbons;
+        if carbons < 1 {
+            return Err(Error::Carbons(carbons));
+        }
+        let mut valences = vec![1; carbons as usize + 2];
+        valences[1] = 3;
+        for locant in 2..carbons {
+            valences[locant as usize] = 2;
+        }
+        let mut indices = BTreeSet::new();
+        for unsaturation in &self.unsaturations {
+            let Some(index) = unsaturation.index else {
+                continue;
+            };
+            // Out of the chain or repeated
+            if index < 2 || index >= carbons || !indices.insert(index) {
+                return Err(Error::Locant { index, carbons });
+            }
+            let extra = unsaturation.bond.valence() - 1;
+            valences[index as usize] += extra;
+            valences[index as usize + 1] += extra;
+        }
+        for substituent in &self.substituents {
+            let index = substituent.index;
+            if index < 2 || index >= carbons {
+                return Err(Error::Locant { index, carbons });
+            }
+            valences[index as usize] += 1;
+        }
+        if let Some(index) = (1..=carbons).find(|&index| valences[index as usize] > 4) {
+            return Err(Error::Valence { index });
+        }
+        Ok(())
+    }
+}
+
+impl Display for FattyAcid {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.shorthand(Shorthand::LipidMaps).fmt(f)
+    }
+}
+
+impl FromStr for FattyAcid {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (_, fatty_acid) =
+            all_consuming(shorthand)(s).map_err(|_| Error::Shorthand(s.to_owned()))?;
+        let fatty_acid = fatty_acid?;
+        fatty_acid.validate()?;
+        Ok(fatty_acid)
+    }
+}
+
+impl TryFrom<&MoleculeGraph> for FattyAcid {
+    type Error = Error;
+
+    /// Recognises a free fatty acid (or its carboxylate)
+    fn try_from(value: &MoleculeGraph) -> Result<Self> {
+        let heavy = value
+            .node_indices()
+            .filter(|&index| value[index].element != Some(Element::H))
+            .count();
+        let mut error = Error::CarboxylNotFound;
+        for carboxyl in value.node_indices() {
+            let Some((_, oxygen)) = carboxyl_oxygens(value, carboxyl) else {
+                continue;
+            };
+            if value
+                .neighbors(oxygen)
+                .any(|neighbor| neighbor != carboxyl && value[neighbor].element != Some(Element::H))
+            {
+                continue;
+            }
+            match Self::acyl(value, carboxyl) {
+                Ok((fatty_acid, atoms)) if atoms.len() + 1 == heavy => return Ok(fatty_acid),
+                Ok((_, atoms)) => {
+                    let index = value
+                        .node_indices()
+                        .find(|index| {
+                            *index != oxygen
+                                && !atoms.contains(index)
+                                && value[*index].element != Some(Element::H)
+                        })
+                        .unwrap();
+                    error = Error::UnexpectedAtom {
+                        index: index.index(),
+                    };
+                }
+                Err(other) => error = other,
+            }
+        }
+        Err(error)
+    }
+}
+
+impl TryFrom<&FattyAcid> for MoleculeGraph {
+    type Error = Error;
+
+    fn try_from(value: &FattyAcid) -> Result<Self> {
+        let mut graph = MoleculeGraph::default();
+        let carboxyl = value.attach(&mut graph, Notation::Omega)?;
+        let hydroxyl = graph.add_node(Atom {
+            element: Some(Element::O),
+            ..Default::default()
+        });
+        graph.add_edge(carboxyl, hydroxyl, Bond::Single);
+        Ok(graph)
+    }
+}
+
+/// Unsaturated bond
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Unsaturation {
+    /// Delta index of the lower numbered carbon, `None` if unknown
+    pub index: Option<u8>,
+    /// Double or triple bond
+    pub bond: Bond,
+    pub geometry: Option<Geometry>,
+}
+
+impl Unsaturation {
+    pub fn double(index: u8, geometry: Option<Geometry>) -> Self {
+        Self {
+            index: Some(index),
+            bond: Bond::Double,
+            geometry,
+        }
+    }
+
+    pub fn triple(index: u8) -> Self {
+        Self {
+            index: Some(index),
+            bond: Bond::Triple,
+            geometry: None,
+        }
+    }
+}
+
+impl Display for Unsaturation {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if let Some(index) = self.index {
+            write!(f, "{index}")?;
+        }
+        if let Some(geometry) = self.geometry {
+            write!(f, "{geometry}")?;
+        }
+        if self.bond == Bond::Triple {
+            f.write_str("a")?;
+        }
+        Ok(())
+    }
+}
+
+/// Substituent of the carbon chain
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Substituent {
+    /// Index of the substituted carbon
+    pub index: u8,
+    pub kind: Functional,
+}
+
+impl Display for Substituent {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.index, self.kind)
+    }
+}
+
+/// Functional group
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Functional {
+    Methyl,
+}
+
+impl Display for Functional {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Methyl => f.write_str("Me"),
+        }
+    }
+}
+
+/// Shorthand notation
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Shorthand {
+    /// LIPID MAPS, `18:2(9Z,12Z)`
+    #[default]
+    LipidMaps,
+    /// Omega, `C18:2 n-6`
+    Omega,
+    /// Delta, `C18:2 Δ9Z,12Z`
+    Delta,
+}
+
+struct Formatted<'a> {
+    fatty_acid: &'a FattyAcid,
+    shorthand: Shorthand,
+}
+
+impl Display for Formatted<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let FattyAcid {
+            carbons,
+            unsaturations,
+            substituents,
+        } = self.fatty_acid;
+        let positional = self.fatty_acid.is_positional() && !unsaturations.is_empty();
+        match self.shorthand {
+            Shorthand::LipidMaps => {
+                write!(f, "{carbons}:{}", unsaturations.len())?;
+                if positional {
+                    f.write_str("(")?;
+                    list(f, unsaturations)?;
+                    f.write_str(")")?;
+                }
+            }
+            Shorthand::Omega => {
+                write!(f, "C{carbons}:{}", unsaturations.len())?;
+                if let Some(omega) = self.fatty_acid.omega().filter(|_| positional) {
+                    write!(f, " n-{omega}")?;
+                }
+            }
+            Shorthand::Delta => {
+                write!(f, "C{carbons}:{}", unsaturations.len())?;
+                if positional {
+                    f.write_str(" Δ")?;
+                    list(f, unsaturations)?;
+                }
+            }
+        }
+        if !substituents.is_empty() {
+            f.write_str(";")?;
+            list(f, substituents)?;
+        }
+        Ok(())
+    }
+}
+
+fn list<T: Display>(f: &mut Formatter, items: &[T]) -> fmt::Result {
+    for (index, item) in items.iter().enumerate() {
+        if index != 0 {
+            f.write_str(",")?;
+        }
+        item.fmt(f)?;
+    }
+    Ok(())
+}
+
+/// Positions of the unsaturations in a shorthand
+enum Positions {
+    Delta(Vec<Unsaturation>),
+    Omega(u8),
+}
+
+fn shorthand(input: &str) -> IResult<&str, Result<FattyAcid>> {
+    let (input, _) = opt(pair(tag("FA"), space1))(input)?;
+    let (input, _) = opt(char('C'))(input)?;
+    let (input, (carbons, count)) = separated_pair(u8, char(':'), u8)(input)?;
+    let (input, positions) = opt(alt((
+        map(
+            delimited(
+                char('('),
+                separated_list1(char(','), unsaturation),
+                char(')'),
+            ),
+            Positions::Delta,
+        ),
+        map(
+            preceded(pair(space0, alt((tag("n-"), tag("ω-"), tag("ω")))), u8),
+            Positions::Omega,
+        ),
+        map(
+            preceded(
+                pair(space0, char('Δ')),
+                separated_list1(char(','), unsaturation),
+            ),
+            Positions::Delta,
+        ),
+    )))(input)?;
+    let (input, substituents) =
+        opt(preceded(char(';'), separated_list1(char(','), substituent)))(input)?;
+    let unsaturations = match positions {
+        None => Ok(vec![
+            Unsaturation {
+                index: None,
+                bond: Bond::Double,
+                geometry: None,
+            };
+            count as _
+        ]),
+        Some(Positions::Delta(unsaturations)) if unsaturations.len() == count as usize => {
+            Ok(unsaturations)
+        }
+        Some(Positions::Delta(unsaturations)) => Err(Error::Count {
+            expected: count,
+            found: unsaturations.len() as _,
+        }),
+        // Methylene interrupted double bonds starting at the omega index
+        Some(Positions::Omega(omega)) => (0..count)
+            .map(|number| {
+                let index = (carbons as i16) - (omega as i16) - 3 * number as i16;
+                match u8::try_from(index) {
+                    Ok(index) if omega > 0 => Ok(Unsaturation::double(index, None)),
+                    _ => Err(Error::Locant {
+                        index: omega,
+                        carbons,
+                    }),
+                }
+            })
+            .collect(),
+    };
+    Ok((
+        input,
+        unsaturations.map(|unsaturations| {
+            let mut fatty_acid = FattyAcid::new(carbons, unsaturations);
+            fatty_acid.substituents = substituents.unwrap_or_default();
+            fatty_acid.substituents.sort();
+            fatty_acid
+        }),
+    ))
+}
+
+fn unsaturation(input: &str) -> IResult<&str, Unsaturation> {
+    map(tuple((u8, opt(one_of("ZEcta")))), |(index, suffix)| {
+        let (bond, geometry) = match suffix {
+            Some('Z' | 'c') => (Bond::Double, Some(Geometry::Z)),
+            Some('E' | 't') => (Bond::Double, Some(Geometry::E)),
+            Some('a') => (Bond::Triple, None),
+            _ => (Bond::Double, None),
+        };
+        Unsaturation {
+            index: Some(index),
+            bond,
+            geometry,
+        }
+    })(input)
+}
+
+fn substituent(input: &str) -> IResult<&str, Substituent> {
+    map(pair(u8, tag("Me")), |(index, _)| Substituent {
+        index,
+        kind: Functional::Methyl,
+    })(input)
+}
+
+/// Oxo and single bonded oxygens of a carboxyl carbon
+pub(super) fn carboxyl_oxygens(
+    graph: &MoleculeGraph,
+    index: NodeIndex,
+) -> Option<(NodeIndex, NodeIndex)> {
+    if graph[index].element != Some(Element::C) {
+        return None;
+    }
+    let (mut oxo, mut oxygen) = (None, None);
+    let mut carbons = 0;
+    for edge in graph.edges(index) {
+        let neighbor = edge.target();
+        match (graph[neighbor].element, edge.weight()) {
+            (Some(Element::O), Bond::Double) if oxo.is_none() => oxo = Some(neighbor),
+            (Some(Element::O), Bond::Single | Bond::Up | Bond::Down) if oxygen.is_none() => {
+                oxygen = Some(neighbor)
+            }
+            (Some(Element::C), Bond::Single | Bond::Up | Bond::Down) => carbons += 1,
+            (Some(Element::H), _) => {}
+            _ => return None,
+        }
+    }
+    (carbons <= 1).then_some((oxo?, oxygen?))
+}
+
+/// Is the carbon a methyl substituent of `parent`
+fn is_methyl(graph: &MoleculeGraph, index: NodeIndex, parent: NodeIndex) -> bool {
+    graph[index].element == Some(Element::C)
+        && graph.edges(index).all(|edge| {
+            edge.target() == parent && graph[edge.id()].valence() == 1
+                || graph[edge.target()].element == Some(Element::H)
+        })
+}
+
+/// Adds the double bonded carboxyl oxygen
+fn oxo(graph: &mut MoleculeGraph, carboxyl: NodeIndex) {
+    let oxo = graph.add_node(Atom {
+        element: Some(Element::O),
+        ..Default::default()
+    });
+    graph.add_edge(carboxyl, oxo, Bond::Double);
+}
+
+fn carbon() -> Atom {
+    Atom {
+        element: Some(Element::C),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn shorthand() {
+        let linoleic = FattyAcid::new(
+            18,
+            [
+                Unsaturation::double(9, Some(Geometry::Z)),
+                Unsaturation::double(12, Some(Geometry::Z)),
+            ],
+        );
+        assert_eq!("18:2(9Z,12Z)".parse::<FattyAcid>().unwrap(), linoleic);
+        assert_eq!("FA 18:2(9Z,12Z)".parse::<FattyAcid>().unwrap(), linoleic);
+        assert_eq!("C18:2 Δ9Z,12Z".parse::<FattyAcid>().unwrap(), linoleic);
+        assert_eq!(linoleic.to_string(), "18:2(9Z,12Z)");
+        assert_eq!(
+            linoleic.shorthand(Shorthand::Omega).to_string(),
+            "C18:2 n-6"
+        );
+        assert_eq!(
+            linoleic.shorthand(Shorthand::Delta).to_string(),
+            "C18:2 Δ9Z,12Z"
+        );
+        let omega = "C18:2 n-6".parse::<FattyAcid>().unwrap();
+        assert_eq!(omega.to_string(), "18:2(9,12)");
+        assert_eq!("18:1".parse::<FattyAcid>().unwrap().to_string(), "18:1");
+        assert_eq!(
+            "17:0;16Me".parse::<FattyAcid>().unwrap().to_string(),
+            "17:0;16Me"
+        );
+        assert!("18:2(9Z)".parse::<FattyAcid>().is_err());
+        assert!("18:1(18Z)".parse::<FattyAcid>().is_err());
+        assert!(matches!("0:0".parse::<FattyAcid>(), Err(Error::Carbons(0))));
+        for shorthand in ["18:2(9,9)", "18:2(9Z,9E)", "C18:2 Δ9Z,9Z"] {
+            assert!(
+                matches!(
+                    shorthand.parse::<FattyAcid>(),
+                    Err(Error::Locant {
+                        index: 9,
+                        carbons: 18
+                    })
+                ),
+                "{shorthand}"
+            );
+        }
+        assert!(MoleculeGraph::try_from(&FattyAcid::new(0, [])).is_err());
+        assert_eq!(
+            FattyAcid::new(6, [Unsaturation::double(9, None)]).omega(),
+            None
+        );
+    }
+
+    #[test]
+    fn recognition() {
+        let graph: MoleculeGraph = "CCCCC/C=C\\C/C=C\\CCCCCCCC(=O)O".parse().unwrap();
+        let fatty_acid = FattyAcid::try_from(&graph).unwrap();
+        assert_eq!(fatty_acid.to_string(), "18:2(9Z,12Z)");
+        let graph: MoleculeGraph = "OC(=O)CCCCCCC/C=C/CCCCCCCC".parse().unwrap();
+        let fatty_acid = FattyAcid::try_from(&graph).unwrap();
+        assert_eq!(fatty_acid.to_string(), "18:1(9E)");
+        let graph: MoleculeGraph = "CC(C)CCCC(=O)[O-]".parse().unwrap();
+        let fatty_acid = FattyAcid::try_from(&graph).unwrap();
+        assert_eq!(fatty_acid.to_string(), "6:0;5Me");
+        let graph: MoleculeGraph = "CCCCCC(=O)OC".parse().unwrap();
+        assert!(FattyAcid::try_from(&graph).is_err());
+    }
+
+    #[test]
+    fn graph() {
+        let fatty_acid: FattyAcid = "18:2(9Z,12Z)".parse().unwrap();
+        let graph = MoleculeGraph::try_from(&fatty_acid).unwrap();
+        assert_eq!(graph.to_string(), "CCCCC/C=C\\C/C=C\\CCCCCCCC(=O)O");
+        let fatty_acid: FattyAcid = "17:0;16Me".parse().unwrap();
+        let graph = MoleculeGraph::try_from(&fatty_acid).unwrap();
+        assert_eq!(graph.to_string(), "CC(C)CCCCCCCCCCCCCCC(=O)O");
+        assert_eq!(FattyAcid::try_from(&graph).unwrap(), fatty_acid);
+    }
+}
@@ -0,0 +1,381 @@
+use super::{
+    error::{Error, Result},
+    fatty_acid::FattyAcid,
+    glycerolipid::Glycerolipid,
+    glycerophospholipid::{Glycerophospholipid, Headgroup},
+};
+use crate::semantic::MoleculeGraph;
+
+/// Electron mass (Da)
+const ELECTRON: f64 = 0.000548579909;
+
+/// Hydrogen atom mass (Da)
+const HYDROGEN: f64 = 1.00782503207;
+
+/// Predicted product ion
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fragment {
+    pub kind: Ion,
+    pub charge: i8,
+    pub mz: f64,
+    /// Neutral loss from the precursor, `None` for the precursor and ions
+    /// not formed by a loss
+    pub loss: Option<f64>,
+    /// `[M+NH4-NH3-FA 16:0]+`, `[FA 18:1(9Z)-H]-`...
+    pub annotation: String,
+}
+
+/// Fragment ion by the fragmentation rule forming it
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Ion {
+    Precursor,
+    /// Loss of a free hydroxyl as water
+    Water,
+    /// Loss of the fatty acid, the diacylglycerol-like ion of a
+    /// triacylglycerol
+    Acid(FattyAcid),
+    /// Loss of the fatty acid as ketene
+    Ketene(FattyAcid),
+    /// `[RCO]+`
+    Acylium(FattyAcid),
+    /// `[RCO+74]+`, the monoacylglycerol-like ion
+    Monoacylglycerol(FattyAcid),
+    /// `[RCOO]-`
+    Carboxylate(FattyAcid),
+    /// Headgroup ion or loss of a glycerophospholipid
+    Headgroup,
+}
+
+impl MoleculeGraph {
+    /// Predicted MS/MS fragments of a glycerolipid or glycerophospholipid
+    ///
+    /// Glycerolipids fragment as `[M+NH4]+`: loss of ammonia with each fatty
+    /// acid or with water from a free hydroxyl, acylium and `[RCO+74]+`
+    /// ions. Glycerophospholipids fragment as `[M+H]+` with the loss of the
+    /// phosphorylated headgroup, or the `[184]+` ion of choline, and as
+    /// `[M-H]-`, `[M-CH3]-` for choline, with the loss of each fatty acid
+    /// as acid or ketene, the carboxylate anions and the headgroup ions.
+    /// Fatty acids are considered once per distinct acyl.
+    pub fn fragments(&self) -> Result<Vec<Fragment>> {
+        // Neutral form of a charged graph, anions protonated
+        let charge: i32 = self
+            .node_indices()
+            .map(|index| self[index].charge as i32)
+            .sum();
+        let neutral =
+            self.monoisotopic_mass().ok_or(Error::UnknownClass)? - charge as f64 * HYDROGEN;
+        if let Ok(glycerophospholipid) = Glycerophospholipid::try_from(self) {
+            let fatty_acids = distinct(glycerophospholipid.fatty_acids())?;
+            return Ok(glycerophospholipid_fragments(
+                glycerophospholipid.headgroup,
+                neutral,
+                &fatty_acids,
+            ));
+        }
+        let glycerolipid = Glycerolipid::try_from(self)?;
+        let fatty_acids = distinct(glycerolipid.fatty_acids())?;
+        let hydroxyl = glycerolipid.acyls.iter().any(Option::is_none);
+        Ok(glycerolipid_fragments(neutral, hydroxyl, &fatty_acids))
+    }
+}
+
+/// Distinct fatty acids with the monoisotopic masses of the free acids
+fn distinct<'a>(
+    fatty_acids: impl Iterator<Item = &'a FattyAcid>,
+) -> Result<Vec<(&'a FattyAcid, f64)>> {
+    let mut distinct: Vec<(&FattyAcid, f64)> = Vec::new();
+    for fatty_acid in fatty_acids {
+        if distinct.iter().any(|&(other, _)| other == fatty_acid) {
+            continue;
+        }
+        let graph = MoleculeGraph::try_from(fatty_acid)?;
+        distinct.push((fatty_acid, graph.monoisotopic_mass().unwrap_or_default()));
+    }
+    Ok(distinct)
+}
+
+fn glycerolipid_fragments(
+    neutral: f64,
+    hydroxyl: bool,
+    fatty_acids: &[(&FattyAcid, f64)],
+) -> Vec<Fragment> {
+    let water = mass("O");
+    let ammonia = mass("N");
+    let glycerol = mass("OCC(O)CO");
+    let precursor = ion(neutral + ammonia, 1);
+    let mut fragments = vec![Fragment {
+        kind: Ion::Precursor,
+        charge: 1,
+        mz: precursor,
+        loss: None,
+        annotation: "[M+NH4]+".to_owned(),
+    }];
+    let mut loss = |kind, loss, annotation: String| {
+        fragments.push(Fragment {
+            kind,
+            charge: 1,
+            mz: precursor - loss,
+            loss: Some(loss),
+            annotation,
+        })
+    };
+    if hydroxyl {
+        loss(Ion::Water, ammonia + water, "[M+NH4-NH3-H2O]+".to_owned());
+    }
+    for &(fatty_acid, acid) in fatty_acids {
+        loss(
+            Ion::Acid(fatty_acid.clone()),
+            ammonia + acid,
+            format!("[M+NH4-NH3-FA {fatty_acid}]+"),
+        );
+    }
+    for &(fatty_acid, acid) in fatty_acids {
+        fragments.push(Fragment {
+            kind: Ion::Acylium(fatty_acid.clone()),
+            charge: 1,
+            mz: ion(acid - water, 1),
+            loss: None,
+            annotation: format!("[FA {fatty_acid}+H-H2O]+"),
+        });
+        fragments.push(Fragment {
+            kind: Ion::Monoacylglycerol(fatty_acid.clone()),
+            charge: 1,
+            mz: ion(acid + glycerol - 2.0 * water, 1),
+            loss: None,
+            annotation: format!("[MG {fatty_acid}+H-H2O]+"),
+        });
+    }
+    fragments
+}
+
+fn glycerophospholipid_fragments(
+    headgroup: Headgroup,
+    neutral: f64,
+    fatty_acids: &[(&FattyAcid, f64)],
+) -> Vec<Fragment> {
+    let water = mass("O");
+    let mut fragments = Vec::new();
+    // Positive mode
+    let precursor = ion(neutral, 1);
+    fragments.push(Fragment {
+        kind: Ion::Precursor,
+        charge: 1,
+        mz: precursor,
+        loss: None,
+        annotation: "[M+H]+".to_owned(),
+    });
+    let (name, phosphate) = match headgroup {
+        Headgroup::Choline => ("phosphocholine", "C[N+](C)(C)CCOP(=O)([O-])O"),
+        Headgroup::Ethanolamine => ("phosphoethanolamine", "NCCOP(=O)(O)O"),
+        Headgroup::Serine => ("phosphoserine", "NC(COP(=O)(O)O)C(=O)O"),
+        Headgroup::Inositol => ("inositol phosphate", "OC1C(O)C(O)C(OP(=O)(O)O)C(O)C1O"),
+        Headgroup::Glycerol => ("glycerophosphate", "OCC(O)COP(=O)(O)O"),
+        Headgroup::Hydrogen => ("phosphoric acid", "OP(=O)(O)O"),
+    };
+    let phosphate = mass(phosphate);
+    fragments.push(Fragment {
+        kind: Ion::Headgroup,
+        charge: 1,
+        mz: precursor - phosphate,
+        loss: Some(phosphate),
+        annotation: format!("[M+H-{name}]+"),
+    });
+    if headgroup == Headgroup::Choline {
+        fragments.push(Fragment {
+            kind: Ion::Headgroup,
+            charge: 1,
+            mz: ion(phosphate, 1),
+            loss: None,
+            annotation: format!("[{name}+H]+"),
+        });
+    }
+    // Negative mode, choline demethylated from the formate adduct
+    let (precursor, annotation) = match headgroup {
+        Headgroup::Choline => (neutral + HYDROGEN - mass("C") + ELECTRON, "[M-CH3]-"),
+        _ => (ion(neutral, -1), "[M-H]-"),
+    };
+    fragments.push(Fragment {
+        kind: Ion::Precursor,
+        charge: -1,
+        mz: precursor,
+        loss: None,
+        annotation: annotation.to_owned(),
+    });
+    let prefix = annotation.trim_end_matches("]-");
+    for &(fatty_acid, acid) in fatty_acids {
+        for (kind, loss, annotation) in [
+            (
+                Ion::Acid(fatty_acid.clone()),
+                acid,
+                format!("{prefix}-FA {fatty_acid}]-"),
+            ),
+            (
+                Ion::Ketene(fatty_acid.clone()),
+                acid - water,
+                format!("{prefix}-(FA {fatty_acid}-H2O)]-"),
+            ),
+        ] {
+            fragments.push(Fragment {
+                kind,
+                charge: -1,
+                mz: precursor - loss,
+                loss: Some(loss),
+                annotation,
+            });
+        }
+    }
+    for &(fatty_acid, acid) in fatty_acids {
+        fragments.push(Fragment {
+            kind: Ion::Carboxylate(fatty_acid.clone()),
+            charge: -1,
+            mz: ion(acid, -1),
+            loss: None,
+            annotation: format!("[FA {fatty_acid}-H]-"),
+        });
+    }
+    match headgroup {
+        Headgroup::Choline => {}
+        Headgroup::Serine => {
+            let serine = mass("NC(CO)C(=O)O") - water;
+            fragments.push(Fragment {
+                kind: Ion::Headgroup,
+                charge: -1,
+                mz: precursor - serine,
+                loss: Some(serine),
+                annotation: "[M-H-C3H5NO2]-".to_owned(),
+            });
+        }
+        Headgroup::Ethanolamine | Headgroup::Glycerol => fragments.push(Fragment {
+            kind: Ion::Headgroup,
+            charge: -1,
+            mz: ion(phosphate, -1),
+            loss: None,
+            annotation: format!("[{name}-H]-"),
+        }),
+        Headgroup::Inositol | Headgroup::Hydrogen => {
+            let (name, phosphate) = match headgroup {
+                Headgroup::Inositol => (name, phosphate),
+                _ => ("glycerophosphate", mass("OCC(O)COP(=O)(O)O")),
+            };
+            fragments.push(Fragment {
+                kind: Ion::Headgroup,
+                charge: -1,
+                mz: ion(phosphate - water, -1),
+                loss: None,
+                annotation: format!("[{name}-H2O-H]-"),
+            });
+        }
+    }
+    fragments
+}
+
+/// m/z of the neutral mass protonated or deprotonated to the charge
+fn ion(neutral: f64, charge: i8) -> f64 {
+    let charge = charge as f64;
+    (neutral + charge * (HYDROGEN - ELECTRON)).abs() / charge.abs()
+}
+
+/// Monoisotopic mass of the SMILES
+fn mass(smiles: &str) -> f64 {
+    smiles
+        .parse::<MoleculeGraph>()
+        .ok()
+        .and_then(|graph| graph.monoisotopic_mass())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn predict(smiles: &str) -> Vec<Fragment> {
+        smiles
+            .parse::<MoleculeGraph>()
+            .unwrap()
+            .fragments()
+            .unwrap()
+    }
+
+    fn mz(fragments: &[Fragment], annotation: &str) -> f64 {
+        fragments
+            .iter()
+            .find(|fragment| fragment.annotation == annotation)
+            .unwrap_or_else(|| panic!("{annotation}"))
+            .mz
+    }
+
+    #[test]
+    fn triacylglycerol() {
+        // TG(16:0/18:1(9Z)/16:0)
+        let fragments = predict(
+            "CCCCCCCCCCCCCCCC(=O)OC[C@@H](OC(=O)CCCCCCC/C=C\\CCCCCCCC)COC(=O)CCCCCCCCCCCCCCC",
+        );
+        // 2 acylium, 2 [RCO+74]+, 2 losses and the precursor
+        assert_eq!(fragments.len(), 7);
+        assert!(!fragments.iter().any(|fragment| fragment.kind == Ion::Water));
+        assert!((mz(&fragments, "[M+NH4]+") - 850.7858).abs() < 1e-3);
+        assert!((mz(&fragments, "[M+NH4-NH3-FA 16:0]+") - 577.5190).abs() < 1e-3);
+        assert!((mz(&fragments, "[M+NH4-NH3-FA 18:1(9Z)]+") - 551.5034).abs() < 1e-3);
+        assert!((mz(&fragments, "[FA 16:0+H-H2O]+") - 239.2369).abs() < 1e-3);
+        assert!((mz(&fragments, "[MG 16:0+H-H2O]+") - 313.2737).abs() < 1e-3);
+        let loss = fragments
+            .iter()
+            .find(|fragment| fragment.kind == Ion::Acid("16:0".parse().unwrap()))
+            .and_then(|fragment| fragment.loss)
+            .unwrap();
+        assert!((loss - 273.2667).abs() < 1e-3);
+    }
+
+    #[test]
+    fn diacylglycerol() {
+        let fragments = predict("CCCCCCCCCCCCCCCC(=O)OCC(O)COC(=O)CCCCCCCCCCCCCCC");
+        assert!((mz(&fragments, "[M+NH4]+") - 586.5405).abs() < 1e-3);
+        assert!((mz(&fragments, "[M+NH4-NH3-H2O]+") - 551.5034).abs() < 1e-3);
+    }
+
+    #[test]
+    fn glycerophospholipids() {
+        // PC(16:0/18:1(9Z))
+        let fragments = predict(
+            "CCCCCCCCCCCCCCCC(=O)OC[C@@H](OC(=O)CCCCCCC/C=C\\CCCCCCCC)COP(=O)([O-])OCC[N+](C)(C)C",
+        );
+        assert!((mz(&fragments, "[M+H]+") - 760.5851).abs() < 1e-3);
+        assert!((mz(&fragments, "[phosphocholine+H]+") - 184.0733).abs() < 1e-3);
+        assert!((mz(&fragments, "[M+H-phosphocholine]+") - 577.5190).abs() < 1e-3);
+        assert!((mz(&fragments, "[M-CH3]-") - 744.5549).abs() < 1e-3);
+        assert!((mz(&fragments, "[FA 18:1(9Z)-H]-") - 281.2486).abs() < 1e-3);
+        // PE(16:0/18:1(9Z))
+        let fragments = predict(
+            "CCCCCCCCCCCCCCCC(=O)OC[C@@H](OC(=O)CCCCCCC/C=C\\CCCCCCCC)COP(=O)([O-])OCC[NH3+]",
+        );
+        assert!((mz(&fragments, "[M+H]+") - 718.5381).abs() < 1e-3);
+        assert!((mz(&fragments, "[M+H-phosphoethanolamine]+") - 577.5190).abs() < 1e-3);
+        assert!((mz(&fragments, "[M-H]-") - 716.5236).abs() < 1e-3);
+        assert!((mz(&fragments, "[M-H-FA 16:0]-") - 460.2834).abs() < 1e-3);
+        assert!((mz(&fragments, "[M-H-(FA 16:0-H2O)]-") - 478.2939).abs() < 1e-3);
+        assert!((mz(&fragments, "[phosphoethanolamine-H]-") - 140.0118).abs() < 1e-3);
+        for (shorthand, annotation, expected) in [
+            ("PS(16:0/18:1(9Z))", "[M-H-C3H5NO2]-", 673.4814),
+            ("PI(16:0/18:1(9Z))", "[inositol phosphate-H2O-H]-", 241.0118),
+            ("PG(16:0/18:1(9Z))", "[glycerophosphate-H]-", 171.0064),
+            ("PA(16:0/18:1(9Z))", "[glycerophosphate-H2O-H]-", 152.9958),
+            ("PA(16:0/18:1(9Z))", "[M-H]-", 673.4814),
+        ] {
+            let glycerophospholipid: Glycerophospholipid = shorthand.parse().unwrap();
+            let fragments = MoleculeGraph::try_from(&glycerophospholipid)
+                .unwrap()
+                .fragments()
+                .unwrap();
+            assert!(
+                (mz(&fragments, annotation) - expected).abs() < 1e-3,
+                "{shorthand} {annotation}"
+            );
+        }
+    }
+
+    #[test]
+    fn unknown() {
+        let graph: MoleculeGraph = "CCCCCCCC/C=C\\CCCCCCCC(=O)O".parse().unwrap();
+        assert!(graph.fragments().is_err());
+    }
+}
@@ -0,0 +1,422 @@
+use super::{
+    error::{Error, Result},
+    fatty_acid::FattyAcid,
+};
+use crate::semantic::{Atom, Bond, Element, MoleculeGraph, Notation, Parity};
+use petgraph::{graph::NodeIndex, visit::EdgeRef};
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+/// Glycerolipid
+///
+/// Mono-, di- or triacylglycerol with the fatty acids esterified at the
+/// sn-1, sn-2 and sn-3 positions of the glycerol backbone.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Glycerolipid {
+    /// Acyls by position, `None` for a free hydroxyl
+    pub acyls: [Option<FattyAcid>; 3],
+    pub positions: Positions,
+}
+
+impl Glycerolipid {
+    /// Glycerolipid with the acyls in the normal order of the given
+    /// positions
+    ///
+    /// Racemic acyls are ordered so that sn-1 precedes sn-3, unknown ones
+    /// are sorted. Free hydroxyls come last in both cases.
+    pub fn new(acyls: [Option<FattyAcid>; 3], positions: Positions) -> Self {
+        let mut acyls = acyls;
+        match positions {
+            Positions::Stereospecific => {}
+            Positions::Racemic => {
+                if key(&acyls[2]) < key(&acyls[0]) {
+                    acyls.swap(0, 2);
+                }
+            }
+            Positions::Unknown => acyls.sort_by(|a, b| key(a).cmp(&key(b))),
+        }
+        Self { acyls, positions }
+    }
+
+    /// Esterified fatty acids
+    pub fn fatty_acids(&self) -> impl Iterator<Item = &FattyAcid> {
+        self.acyls.iter().flatten()
+    }
+
+    /// Class abbreviation (`MG`, `DG` or `TG`)
+    pub fn abbreviation(&self) -> Option<&'static str> {
+        match self.fatty_acids().count() {
+            1 => Some("MG"),
+            2 => Some("DG"),
+            3 => Some("TG"),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Glycerolipid {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(self.abbreviation().unwrap_or("GL"))?;
+        write_acyls(f, &self.acyls, self.positions)
+    }
+}
+
+impl FromStr for Glycerolipid {
+    type Err = Error;
+
+    /// Parses `TG(16:0/18:1(9Z)/18:2(9Z,12Z))`, `DG(16:0/0:0/18:1)[rac]` or
+    /// `TG(16:0_18:1_18:2)`
+    fn from_str(s: &str) -> Result<Self> {
+        let (class, acyls, positions) = parse_acyls(s)?;
+        let glycerolipid = Self::new(acyls, positions);
+        if glycerolipid.abbreviation() != Some(class) {
+            return Err(Error::Shorthand(s.to_owned()));
+        }
+        Ok(glycerolipid)
+    }
+}
+
+impl TryFrom<&MoleculeGraph> for Glycerolipid {
+    type Error = Error;
+
+    /// Recognises a mono-, di- or triacylglycerol
+    ///
+    /// The sn-positions are taken from the configuration of the sn-2
+    /// carbon, without it the glycerolipid is racemic.
+    fn try_from(value: &MoleculeGraph) -> Result<Self> {
+        let heavy: Vec<_> = value
+            .node_indices()
+            .filter(|&index| value[index].element != Some(Element::H))
+            .collect();
+        let mut error = Error::GlycerolNotFound;
+        for index in value.node_indices() {
+            let Some(backbone) = backbone(value, index) else {
+                continue;
+            };
+            let mut atoms: Vec<_> = backbone.iter().flat_map(|&(c, o)| [c, o]).collect();
+            let mut acyls = [None, None, None];
+            let mut result = Ok(());
+            for (acyl, &(carbon, oxygen)) in acyls.iter_mut().zip(&backbone) {
+                let Some(carboxyl) = value.neighbors(oxygen).find(|&neighbor| {
+                    neighbor != carbon && value[neighbor].element != Some(Element::H)
+                }) else {
+                    continue;
+                };
+                match FattyAcid::acyl(value, carboxyl) {
+                    Ok((fatty_acid, acyl_atoms)) => {
+                        *acyl = Some(fatty_acid);
+                        atoms.extend(acyl_atoms);
+                    }
+                    Err(other) => {
+                        result = Err(other);
+                        break;
+                    }
+                }
+            }
+            if let Err(other) = result {
+                error = other;
+                continue;
+            }
+            if let Some(&index) = heavy.iter().find(|index| !atoms.contains(index)) {
+                error = Error::UnexpectedAtom {
+                    index: index.index(),
+                };
+                continue;
+            }
+            if acyls.iter().all(Option::is_none) {
+                continue;
+            }
+            let [(first, _), (middle, oxygen), (last, _)] = backbone;
+            let order = [first, middle, oxygen, last];
+            let positions = match value.parity(middle, &order) {
+                Some(Parity::Clockwise) => Positions::Stereospecific,
+                Some(Parity::Counterclockwise) => {
+                    acyls.swap(0, 2);
+                    Positions::Stereospecific
+                }
+                None if acyls[0] == acyls[2] => Positions::Stereospecific,
+                None => Positions::Racemic,
+            };
+            return Ok(Self::new(acyls, positions));
+        }
+        Err(error)
+    }
+}
+
+impl TryFrom<&Glycerolipid> for MoleculeGraph {
+    type Error = Error;
+
+    /// Builds the glycerolipid with the sn-2 configuration when the
+    /// positions are stereospecific
+    fn try_from(value: &Glycerolipid) -> Result<Self> {
+        if value.abbreviation().is_none() {
+            return Err(Error::GlycerolNotFound);
+        }
+        let [sn1, sn2, sn3] = &value.acyls;
+        let mut graph = MoleculeGraph::default();
+        let first = match sn1 {
+            Some(fatty_acid) => {
+                let carboxyl = fatty_acid.attach(&mut graph, Notation::Omega)?;
+                let oxygen = graph.add_node(atom(Element::O));
+                graph.add_edge(carboxyl, oxygen, Bond::Single);
+                oxygen
+            }
+            None => graph.add_node(atom(Element::O)),
+        };
+        let carbons = [
+            graph.add_node(atom(Element::C)),
+            graph.add_node(atom(Element::C)),
+        ];
+        graph.add_edge(first, carbons[0], Bond::Single);
+        graph.add_edge(carbons[0], carbons[1], Bond::Single);
+        let middle = ester(&mut graph, carbons[1], sn2.as_ref())?;
+        let last = graph.add_node(atom(Element::C));
+        graph.add_edge(carbons[1], last, Bond::Single);
+        ester(&mut graph, last, sn3.as_ref())?;
+        if value.positions == Positions::Stereospecific && sn1 != sn3 {
+            let order = [carbons[0], carbons[1], middle, last];
+            graph.set_parity(carbons[1], &order, Some(Parity::Clockwise));
+        }
+        Ok(graph)
+    }
+}
+
+/// Knowledge of the acyl positions
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Positions {
+    /// Stereospecific numbering, `TG(16:0/18:1/18:2)`
+    #[default]
+    Stereospecific,
+    /// sn-2 known, sn-1 and sn-3 interchangeable, `TG(16:0/18:1/18:2)[rac]`
+    Racemic,
+    /// Unknown positions, `TG(16:0_18:1_18:2)`
+    Unknown,
+}
+
+/// Writes the acyls of a shorthand in parentheses
+///
+/// The acyls are separated by `/` by sn-position, a free hydroxyl written
+/// `0:0`, and by `_` when the positions are unknown. Racemic acyls are
+/// followed by `[rac]`.
+pub(super) fn write_acyls(
+    f: &mut Formatter,
+    acyls: &[Option<FattyAcid>],
+    positions: Positions,
+) -> fmt::Result {
+    f.write_str("(")?;
+    match positions {
+        Positions::Stereospecific | Positions::Racemic => {
+            for (index, acyl) in acyls.iter().enumerate() {
+                if index > 0 {
+                    f.write_str("/")?;
+                }
+                match acyl {
+                    Some(fatty_acid) => write!(f, "{fatty_acid}")?,
+                    None => f.write_str("0:0")?,
+                }
+            }
+        }
+        Positions::Unknown => {
+            for (index, fatty_acid) in acyls.iter().flatten().enumerate() {
+                if index > 0 {
+                    f.write_str("_")?;
+                }
+                write!(f, "{fatty_acid}")?;
+            }
+        }
+    }
+    f.write_str(")")?;
+    if positions == Positions::Racemic {
+        f.write_str("[rac]")?;
+    }
+    Ok(())
+}
+
+/// Parses a shorthand written by [`write_acyls`]
+///
+/// Returns the class abbreviation, the acyls by position and the
+/// positions. All `N` positions are given unless they are unknown.
+pub(super) fn parse_acyls<const N: usize>(
+    s: &str,
+) -> Result<(&str, [Option<FattyAcid>; N], Positions)> {
+    let error = || Error::Shorthand(s.to_owned());
+    let (class, rest) = s.trim().split_once('(').ok_or_else(error)?;
+    let (chains, suffix) = rest.rsplit_once(')').ok_or_else(error)?;
+    let (separator, positions) = match suffix {
+        "" if chains.contains('/') => ('/', Positions::Stereospecific),
+        "" => ('_', Positions::Unknown),
+        "[rac]" => ('/', Positions::Racemic),
+        _ => return Err(error()),
+    };
+    let chains = chains
+        .split(separator)
+        .map(|chain| match chain.trim() {
+            "0:0" => Ok(None),
+            chain => chain.parse().map(Some),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if chains.len() > N || positions != Positions::Unknown && chains.len() != N {
+        return Err(error());
+    }
+    let mut acyls = [(); N].map(|_| None);
+    for (acyl, chain) in acyls.iter_mut().zip(chains) {
+        *acyl = chain;
+    }
+    Ok((class.trim(), acyls, positions))
+}
+
+/// Glycerol carbons with their oxygens, the given atom being sn-2
+pub(super) fn backbone(
+    graph: &MoleculeGraph,
+    middle: NodeIndex,
+) -> Option<[(NodeIndex, NodeIndex); 3]> {
+    let (carbons, oxygen) = substituents(graph, middle)?;
+    let [first, last] = carbons[..] else {
+        return None;
+    };
+    let mut outer = [first, last].map(|carbon| {
+        let (carbons, oxygen) = substituents(graph, carbon)?;
+        (carbons == [middle]).then_some((carbon, oxygen))
+    });
+    outer.sort();
+    let [Some(first), Some(last)] = outer else {
+        return None;
+    };
+    Some([first, (middle, oxygen), last])
+}
+
+/// Carbon neighbours and the single oxygen of a glycerol carbon
+fn substituents(graph: &MoleculeGraph, index: NodeIndex) -> Option<(Vec<NodeIndex>, NodeIndex)> {
+    if graph[index].element != Some(Element::C) || graph[index].charge != 0 {
+        return None;
+    }
+    let mut carbons = Vec::new();
+    let mut oxygen = None;
+    for edge in graph.edges(index) {
+        let neighbor = edge.target();
+        match (graph[neighbor].element, graph[edge.id()].valence()) {
+            (Some(Element::H), _) => {}
+            (Some(Element::C), 1) => carbons.push(neighbor),
+            (Some(Element::O), 1) if oxygen.is_none() => oxygen = Some(neighbor),
+            _ => return None,
+        }
+    }
+    let oxygen = oxygen?;
+    let heavy = graph
+        .neighbors(oxygen)
+        .filter(|&neighbor| graph[neighbor].element != Some(Element::H))
+        .count();
+    (graph[oxygen].charge == 0 && heavy <= 2).then_some((carbons, oxygen))
+}
+
+/// Adds the oxygen of a glycerol carbon, esterified with the fatty acid if
+/// any
+pub(super) fn ester(
+    graph: &mut MoleculeGraph,
+    carbon: NodeIndex,
+    fatty_acid: Option<&FattyAcid>,
+) -> Result<NodeIndex> {
+    let oxygen = graph.add_node(atom(Element::O));
+    graph.add_edge(carbon, oxygen, Bond::Single);
+    if let Some(fatty_acid) = fatty_acid {
+        let carboxyl = fatty_acid.attach(graph, Notation::Delta)?;
+        graph.add_edge(oxygen, carboxyl, Bond::Single);
+    }
+    Ok(oxygen)
+}
+
+/// Sort key placing free hydroxyls last
+pub(super) fn key(acyl: &Option<FattyAcid>) -> (bool, Option<&FattyAcid>) {
+    (acyl.is_none(), acyl.as_ref())
+}
+
+pub(super) fn atom(element: Element) -> Atom {
+    Atom {
+        element: Some(element),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const POPL: &str =
+        "CCCCCCCCCCCCCCCC(=O)OC[C@@H](OC(=O)CCCCCCC/C=C\\CCCCCCCC)COC(=O)CCCCCCC/C=C\\C/C=C\\CCCCC";
+
+    #[test]
+    fn shorthand() {
+        let glycerolipid: Glycerolipid = "TG(16:0/18:1(9Z)/18:2(9Z,12Z))".parse().unwrap();
+        assert_eq!(glycerolipid.positions, Positions::Stereospecific);
+        assert_eq!(glycerolipid.to_string(), "TG(16:0/18:1(9Z)/18:2(9Z,12Z))");
+        let glycerolipid: Glycerolipid = "TG(18:2_16:0_18:1)".parse().unwrap();
+        assert_eq!(glycerolipid.to_string(), "TG(16:0_18:1_18:2)");
+        let glycerolipid: Glycerolipid = "DG(18:1/0:0/16:0)[rac]".parse().unwrap();
+        assert_eq!(glycerolipid.to_string(), "DG(16:0/0:0/18:1)[rac]");
+        let glycerolipid: Glycerolipid = "MG(0:0/18:1(9Z)/0:0)".parse().unwrap();
+        assert_eq!(glycerolipid.fatty_acids().count(), 1);
+        assert!("DG(16:0/18:1/18:2)".parse::<Glycerolipid>().is_err());
+        assert!("TG(16:0/18:1)".parse::<Glycerolipid>().is_err());
+        assert!("TG(16:0_18:1_18:2_18:3)".parse::<Glycerolipid>().is_err());
+    }
+
+    #[test]
+    fn graph() {
+        let glycerolipid: Glycerolipid = "TG(16:0/18:1(9Z)/18:2(9Z,12Z))".parse().unwrap();
+        let graph = MoleculeGraph::try_from(&glycerolipid).unwrap();
+        assert_eq!(graph.to_string(), POPL);
+        let glycerolipid: Glycerolipid = "TG(16:0/18:1(9Z)/16:0)".parse().unwrap();
+        let graph = MoleculeGraph::try_from(&glycerolipid).unwrap();
+        assert_eq!(
+            graph.to_string(),
+            "CCCCCCCCCCCCCCCC(=O)OCC(OC(=O)CCCCCCC/C=C\\CCCCCCCC)COC(=O)CCCCCCCCCCCCCCC"
+        );
+        assert!("MG(0:0/0:0/0:0)".parse::<Glycerolipid>().is_err());
+        assert!(MoleculeGraph::try_from(&Glycerolipid::default()).is_err());
+    }
+
+    #[test]
+    fn recognition() {
+        let graph: MoleculeGraph = POPL.parse().unwrap();
+        let glycerolipid = Glycerolipid::try_from(&graph).unwrap();
+        assert_eq!(glycerolipid.to_string(), "TG(16:0/18:1(9Z)/18:2(9Z,12Z))");
+        // Written from the sn-3 end
+        let graph: MoleculeGraph = "CCCCC/C=C\\C/C=C\\CCCCCCCC(=O)OC[C@H](OC(=O)CCCCCCC/C=C\\CCCCCCCC)COC(=O)CCCCCCCCCCCCCCC"
+            .parse()
+            .unwrap();
+        assert_eq!(Glycerolipid::try_from(&graph).unwrap(), glycerolipid);
+        let graph: MoleculeGraph = "CCCCCCCCCCCCCCCC(=O)OCC(O)COC(=O)CCCCCCC/C=C\\CCCCCCCC"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            Glycerolipid::try_from(&graph).unwrap().to_string(),
+            "DG(16:0/0:0/18:1(9Z))[rac]"
+        );
+        let graph: MoleculeGraph = "OC[C@@H](O)COC(=O)CCCCCCCCCCCCCCC".parse().unwrap();
+        assert_eq!(
+            Glycerolipid::try_from(&graph).unwrap().to_string(),
+            "MG(0:0/0:0/16:0)"
+        );
+        let graph: MoleculeGraph = "OCC(O)CO".parse().unwrap();
+        assert!(Glycerolipid::try_from(&graph).is_err());
+        let graph: MoleculeGraph = "CCCCCC(=O)OCC(OC)COC(=O)CCCCC".parse().unwrap();
+        assert!(Glycerolipid::try_from(&graph).is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        for shorthand in [
+            "TG(16:0/18:1(9Z)/18:2(9Z,12Z))",
+            "TG(18:2(9Z,12Z)/18:1(9Z)/16:0)",
+            "DG(16:0/18:1(9Z)/0:0)",
+            "TG(18:1(9E)/18:1(9E)/18:1(9E))",
+        ] {
+            let glycerolipid: Glycerolipid = shorthand.parse().unwrap();
+            let graph = MoleculeGraph::try_from(&glycerolipid).unwrap();
+            let graph: MoleculeGraph = graph.to_string().parse().unwrap();
+            assert_eq!(Glycerolipid::try_from(&graph).unwrap(), glycerolipid);
+        }
+    }
+}
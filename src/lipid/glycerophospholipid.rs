@@ -0,0 +1,543 @@
+use super::{
+    error::{Error, Result},
+    fatty_acid::FattyAcid,
+    glycerolipid::{atom, backbone, ester, key, parse_acyls, write_acyls, Positions},
+};
+use crate::semantic::{Atom, Bond, Element, MoleculeGraph, Notation, Parity};
+use petgraph::{algo::is_isomorphic_matching, graph::NodeIndex};
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+/// Glycerophospholipid
+///
+/// sn-glycero-3-phosphate with the fatty acids esterified at the sn-1 and
+/// sn-2 positions and the headgroup esterified to the phosphate. The
+/// headgroups are charged as at physiological pH.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Glycerophospholipid {
+    pub headgroup: Headgroup,
+    /// Acyls by position (sn-1, sn-2), `None` for a free hydroxyl
+    pub acyls: [Option<FattyAcid>; 2],
+    pub positions: Positions,
+}
+
+impl Glycerophospholipid {
+    /// Glycerophospholipid with the acyls in the normal order of the given
+    /// positions
+    ///
+    /// Unknown acyls are sorted, the free hydroxyl of a lyso form last.
+    pub fn new(headgroup: Headgroup, acyls: [Option<FattyAcid>; 2], positions: Positions) -> Self {
+        let mut acyls = acyls;
+        if positions == Positions::Unknown && key(&acyls[1]) < key(&acyls[0]) {
+            acyls.swap(0, 1);
+        }
+        Self {
+            headgroup,
+            acyls,
+            positions,
+        }
+    }
+
+    /// Esterified fatty acids
+    pub fn fatty_acids(&self) -> impl Iterator<Item = &FattyAcid> {
+        self.acyls.iter().flatten()
+    }
+
+    /// Is the lyso form with a single acyl
+    pub fn is_lyso(&self) -> bool {
+        self.fatty_acids().count() == 1
+    }
+
+    /// Class abbreviation (`PC`, `LPC`, `PE`...)
+    pub fn abbreviation(&self) -> Option<&'static str> {
+        match self.fatty_acids().count() {
+            1 => Some(self.headgroup.lyso()),
+            2 => Some(self.headgroup.abbreviation()),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Glycerophospholipid {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(
+            self.abbreviation()
+                .unwrap_or_else(|| self.headgroup.abbreviation()),
+        )?;
+        write_acyls(f, &self.acyls, self.positions)
+    }
+}
+
+impl FromStr for Glycerophospholipid {
+    type Err = Error;
+
+    /// Parses `PC(16:0/18:1(9Z))`, `LPC(16:0/0:0)`, `PE(16:0_18:1)` or
+    /// `LPE(18:1)`
+    fn from_str(s: &str) -> Result<Self> {
+        let error = || Error::Shorthand(s.to_owned());
+        let (class, acyls, positions) = parse_acyls(s)?;
+        let headgroup = Headgroup::ALL
+            .into_iter()
+            .find(|headgroup| headgroup.abbreviation() == class || headgroup.lyso() == class)
+            .ok_or_else(error)?;
+        let glycerophospholipid = Self::new(headgroup, acyls, positions);
+        if glycerophospholipid.abbreviation() != Some(class) {
+            return Err(error());
+        }
+        Ok(glycerophospholipid)
+    }
+}
+
+impl TryFrom<&MoleculeGraph> for Glycerophospholipid {
+    type Error = Error;
+
+    /// Recognises a glycerophospholipid in any protonation state
+    ///
+    /// Without the configuration of the sn-2 carbon the glycerophospholipid
+    /// is racemic.
+    fn try_from(value: &MoleculeGraph) -> Result<Self> {
+        let heavy: Vec<_> = value
+            .node_indices()
+            .filter(|&index| value[index].element != Some(Element::H))
+            .collect();
+        let mut error = Error::GlycerolNotFound;
+        for index in value.node_indices() {
+            let Some(mut backbone) = backbone(value, index) else {
+                continue;
+            };
+            let phosphorus = |(carbon, oxygen): (NodeIndex, NodeIndex)| {
+                value.neighbors(oxygen).find(|&neighbor| {
+                    neighbor != carbon && value[neighbor].element == Some(Element::P)
+                })
+            };
+            let phosphorus = match (phosphorus(backbone[0]), phosphorus(backbone[2])) {
+                (None, Some(phosphorus)) => phosphorus,
+                (Some(phosphorus), None) => {
+                    backbone.reverse();
+                    phosphorus
+                }
+                _ => continue,
+            };
+            match recognize(value, backbone, phosphorus, &heavy) {
+                Ok(glycerophospholipid) => return Ok(glycerophospholipid),
+                Err(other) => error = other,
+            }
+        }
+        Err(error)
+    }
+}
+
+impl TryFrom<&Glycerophospholipid> for MoleculeGraph {
+    type Error = Error;
+
+    /// Builds the glycerophospholipid with the sn-2 configuration when the
+    /// positions are stereospecific
+    fn try_from(value: &Glycerophospholipid) -> Result<Self> {
+        if value.abbreviation().is_none() {
+            return Err(Error::GlycerolNotFound);
+        }
+        let [sn1, sn2] = &value.acyls;
+        let mut graph = MoleculeGraph::default();
+        let first = match sn1 {
+            Some(fatty_acid) => {
+                let carboxyl = fatty_acid.attach(&mut graph, Notation::Omega)?;
+                let oxygen = graph.add_node(atom(Element::O));
+                graph.add_edge(carboxyl, oxygen, Bond::Single);
+                oxygen
+            }
+            None => graph.add_node(atom(Element::O)),
+        };
+        let carbons = [
+            graph.add_node(atom(Element::C)),
+            graph.add_node(atom(Element::C)),
+        ];
+        graph.add_edge(first, carbons[0], Bond::Single);
+        graph.add_edge(carbons[0], carbons[1], Bond::Single);
+        let middle = ester(&mut graph, carbons[1], sn2.as_ref())?;
+        let last = graph.add_node(atom(Element::C));
+        graph.add_edge(carbons[1], last, Bond::Single);
+        let oxygen = ester(&mut graph, last, None)?;
+        let oxygen = phosphate(&mut graph, oxygen);
+        value.headgroup.attach(&mut graph, oxygen);
+        if value.positions == Positions::Stereospecific {
+            let order = [carbons[0], carbons[1], middle, last];
+            graph.set_parity(carbons[1], &order, Some(Parity::Clockwise));
+        }
+        Ok(graph)
+    }
+}
+
+/// Glycerophospholipid headgroup
+///
+/// Alcohol esterified to the phosphate.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Headgroup {
+    /// Phosphatidylcholine
+    #[default]
+    Choline,
+    /// Phosphatidylethanolamine
+    Ethanolamine,
+    /// Phosphatidylserine (L-serine)
+    Serine,
+    /// Phosphatidylinositol (1D-myo-inositol)
+    Inositol,
+    /// Phosphatidylglycerol (sn-glycerol)
+    Glycerol,
+    /// Phosphatidic acid, without headgroup
+    Hydrogen,
+}
+
+impl Headgroup {
+    pub const ALL: [Self; 6] = [
+        Self::Choline,
+        Self::Ethanolamine,
+        Self::Serine,
+        Self::Inositol,
+        Self::Glycerol,
+        Self::Hydrogen,
+    ];
+
+    /// Class abbreviation
+    pub fn abbreviation(&self) -> &'static str {
+        match self {
+            Self::Choline => "PC",
+            Self::Ethanolamine => "PE",
+            Self::Serine => "PS",
+            Self::Inositol => "PI",
+            Self::Glycerol => "PG",
+            Self::Hydrogen => "PA",
+        }
+    }
+
+    /// Lyso class abbreviation
+    pub fn lyso(&self) -> &'static str {
+        match self {
+            Self::Choline => "LPC",
+            Self::Ethanolamine => "LPE",
+            Self::Serine => "LPS",
+            Self::Inositol => "LPI",
+            Self::Glycerol => "LPG",
+            Self::Hydrogen => "LPA",
+        }
+    }
+
+    /// Adds the headgroup to the phosphate ester oxygen
+    fn attach(&self, graph: &mut MoleculeGraph, oxygen: NodeIndex) {
+        match self {
+            Self::Choline => {
+                let carbon = chain(graph, oxygen, atom(Element::C));
+                let carbon = chain(graph, carbon, atom(Element::C));
+                let nitrogen = chain(graph, carbon, ion(Element::N, 0, 1));
+                for _ in 0..3 {
+                    chain(graph, nitrogen, atom(Element::C));
+                }
+            }
+            Self::Ethanolamine => {
+                let carbon = chain(graph, oxygen, atom(Element::C));
+                let carbon = chain(graph, carbon, atom(Element::C));
+                chain(graph, carbon, ion(Element::N, 3, 1));
+            }
+            Self::Serine => {
+                let first = chain(graph, oxygen, atom(Element::C));
+                let alpha = chain(graph, first, atom(Element::C));
+                let nitrogen = chain(graph, alpha, ion(Element::N, 3, 1));
+                let carboxyl = chain(graph, alpha, atom(Element::C));
+                let oxo = graph.add_node(atom(Element::O));
+                graph.add_edge(carboxyl, oxo, Bond::Double);
+                chain(graph, carboxyl, ion(Element::O, 0, -1));
+                let order = [nitrogen, alpha, first, carboxyl];
+                graph.set_parity(alpha, &order, Some(Parity::Clockwise));
+            }
+            Self::Inositol => {
+                // Ring carbons from C1 with their oxygens, the C1 oxygen
+                // being the phosphate ester one
+                let mut carbons = vec![chain(graph, oxygen, atom(Element::C))];
+                let mut oxygens = vec![oxygen];
+                for index in 1..6 {
+                    let carbon = chain(graph, carbons[index - 1], atom(Element::C));
+                    carbons.push(carbon);
+                    oxygens.push(chain(graph, carbon, atom(Element::O)));
+                }
+                graph.add_edge(carbons[5], carbons[0], Bond::Single);
+                // Face of each hydroxyl, the C2 one being axial in myo
+                let parities = [
+                    Parity::Clockwise,
+                    Parity::Clockwise,
+                    Parity::Clockwise,
+                    Parity::Counterclockwise,
+                    Parity::Clockwise,
+                    Parity::Counterclockwise,
+                ];
+                for (index, parity) in parities.into_iter().enumerate() {
+                    let order = [
+                        carbons[(index + 5) % 6],
+                        carbons[(index + 1) % 6],
+                        oxygens[index],
+                        carbons[index],
+                    ];
+                    graph.set_parity(carbons[index], &order, Some(parity));
+                }
+            }
+            Self::Glycerol => {
+                let first = chain(graph, oxygen, atom(Element::C));
+                let middle = chain(graph, first, atom(Element::C));
+                let hydroxyl = chain(graph, middle, atom(Element::O));
+                let last = chain(graph, middle, atom(Element::C));
+                chain(graph, last, atom(Element::O));
+                let order = [first, middle, hydroxyl, last];
+                graph.set_parity(middle, &order, Some(Parity::Clockwise));
+            }
+            Self::Hydrogen => {}
+        }
+    }
+
+    /// Does the headgroup match the fragment, starting at the phosphate
+    /// ester oxygen
+    ///
+    /// Charges and configurations are not compared.
+    fn matches(&self, graph: &MoleculeGraph, fragment: &[NodeIndex]) -> bool {
+        let mut template = MoleculeGraph::default();
+        let oxygen = template.add_node(atom(Element::O));
+        self.attach(&mut template, oxygen);
+        let fragment = graph.filter_map(
+            |index, atom| fragment.contains(&index).then_some(*atom),
+            |_, bond| Some(*bond),
+        );
+        is_isomorphic_matching(
+            &fragment,
+            &*template,
+            |a, b| a.element == b.element,
+            |a, b| a.valence() == b.valence(),
+        )
+    }
+}
+
+impl Display for Headgroup {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(self.abbreviation())
+    }
+}
+
+/// Recognises the glycerophospholipid on the backbone ordered from sn-1
+fn recognize(
+    graph: &MoleculeGraph,
+    backbone: [(NodeIndex, NodeIndex); 3],
+    phosphorus: NodeIndex,
+    heavy: &[NodeIndex],
+) -> Result<Glycerophospholipid> {
+    let mut atoms: Vec<_> = backbone.iter().flat_map(|&(c, o)| [c, o]).collect();
+    atoms.push(phosphorus);
+    let mut acyls = [None, None];
+    for (acyl, &(carbon, oxygen)) in acyls.iter_mut().zip(&backbone) {
+        let Some(carboxyl) = graph
+            .neighbors(oxygen)
+            .find(|&neighbor| neighbor != carbon && graph[neighbor].element != Some(Element::H))
+        else {
+            continue;
+        };
+        let (fatty_acid, acyl_atoms) = FattyAcid::acyl(graph, carboxyl)?;
+        *acyl = Some(fatty_acid);
+        atoms.extend(acyl_atoms);
+    }
+    let mut head = None;
+    for oxygen in graph.neighbors(phosphorus) {
+        if oxygen == backbone[2].1 || graph[oxygen].element == Some(Element::H) {
+            continue;
+        }
+        if graph[oxygen].element != Some(Element::O) {
+            return Err(Error::UnexpectedAtom {
+                index: oxygen.index(),
+            });
+        }
+        atoms.push(oxygen);
+        if graph
+            .neighbors(oxygen)
+            .any(|neighbor| neighbor != phosphorus && graph[neighbor].element != Some(Element::H))
+        {
+            if head.is_some() {
+                return Err(Error::UnexpectedAtom {
+                    index: oxygen.index(),
+                });
+            }
+            head = Some(oxygen);
+        }
+    }
+    let headgroup = match head {
+        None => Headgroup::Hydrogen,
+        Some(oxygen) => {
+            let fragment = fragment(graph, oxygen, phosphorus);
+            atoms.extend(fragment.iter().skip(1));
+            Headgroup::ALL
+                .into_iter()
+                .filter(|&headgroup| headgroup != Headgroup::Hydrogen)
+                .find(|headgroup| headgroup.matches(graph, &fragment))
+                .ok_or(Error::UnexpectedAtom {
+                    index: oxygen.index(),
+                })?
+        }
+    };
+    if let Some(&index) = heavy.iter().find(|index| !atoms.contains(index)) {
+        return Err(Error::UnexpectedAtom {
+            index: index.index(),
+        });
+    }
+    if acyls.iter().all(Option::is_none) {
+        return Err(Error::GlycerolNotFound);
+    }
+    let [(first, _), (middle, oxygen), (last, _)] = backbone;
+    let positions = match graph.parity(middle, &[first, middle, oxygen, last]) {
+        Some(Parity::Clockwise) => Positions::Stereospecific,
+        Some(Parity::Counterclockwise) => {
+            return Err(Error::Configuration {
+                index: middle.index(),
+            })
+        }
+        None => Positions::Racemic,
+    };
+    Ok(Glycerophospholipid::new(headgroup, acyls, positions))
+}
+
+/// Heavy atoms connected to the start, the side of the excluded neighbour
+/// excluded
+pub(super) fn fragment(
+    graph: &MoleculeGraph,
+    start: NodeIndex,
+    excluded: NodeIndex,
+) -> Vec<NodeIndex> {
+    let mut atoms = vec![start];
+    let mut index = 0;
+    while let Some(&from) = atoms.get(index) {
+        for to in graph.neighbors(from) {
+            if to != excluded && graph[to].element != Some(Element::H) && !atoms.contains(&to) {
+                atoms.push(to);
+            }
+        }
+        index += 1;
+    }
+    atoms
+}
+
+/// Adds the phosphate to the glycerol oxygen
+///
+/// Returns the ester oxygen of the headgroup.
+fn phosphate(graph: &mut MoleculeGraph, oxygen: NodeIndex) -> NodeIndex {
+    let phosphorus = graph.add_node(atom(Element::P));
+    graph.add_edge(oxygen, phosphorus, Bond::Single);
+    let oxo = graph.add_node(atom(Element::O));
+    graph.add_edge(phosphorus, oxo, Bond::Double);
+    let anion = graph.add_node(ion(Element::O, 0, -1));
+    graph.add_edge(phosphorus, anion, Bond::Single);
+    let ester = graph.add_node(atom(Element::O));
+    graph.add_edge(phosphorus, ester, Bond::Single);
+    ester
+}
+
+/// Adds the atom single bonded to `from`
+fn chain(graph: &mut MoleculeGraph, from: NodeIndex, atom: Atom) -> NodeIndex {
+    let index = graph.add_node(atom);
+    graph.add_edge(from, index, Bond::Single);
+    index
+}
+
+fn ion(element: Element, hydrogens: u8, charge: i8) -> Atom {
+    Atom {
+        element: Some(element),
+        hydrogens: Some(hydrogens),
+        charge,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const POPC: &str =
+        "CCCCCCCCCCCCCCCC(=O)OC[C@@H](OC(=O)CCCCCCC/C=C\\CCCCCCCC)COP(=O)([O-])OCC[N+](C)(C)C";
+
+    #[test]
+    fn shorthand() {
+        let glycerophospholipid: Glycerophospholipid = "PC(16:0/18:1(9Z))".parse().unwrap();
+        assert_eq!(glycerophospholipid.headgroup, Headgroup::Choline);
+        assert_eq!(glycerophospholipid.to_string(), "PC(16:0/18:1(9Z))");
+        let glycerophospholipid: Glycerophospholipid = "LPE(0:0/18:1(9Z))".parse().unwrap();
+        assert!(glycerophospholipid.is_lyso());
+        assert_eq!(glycerophospholipid.to_string(), "LPE(0:0/18:1(9Z))");
+        let glycerophospholipid: Glycerophospholipid = "PS(18:1_16:0)".parse().unwrap();
+        assert_eq!(glycerophospholipid.to_string(), "PS(16:0_18:1)");
+        let glycerophospholipid: Glycerophospholipid = "LPI(18:0)".parse().unwrap();
+        assert_eq!(glycerophospholipid.to_string(), "LPI(18:0)");
+        assert!("PC(16:0/0:0)".parse::<Glycerophospholipid>().is_err());
+        assert!("LPC(16:0/18:1)".parse::<Glycerophospholipid>().is_err());
+        assert!("PX(16:0/18:1)".parse::<Glycerophospholipid>().is_err());
+        assert!("PC(16:0/18:1/18:2)".parse::<Glycerophospholipid>().is_err());
+    }
+
+    #[test]
+    fn graph() {
+        let glycerophospholipid: Glycerophospholipid = "PC(16:0/18:1(9Z))".parse().unwrap();
+        let graph = MoleculeGraph::try_from(&glycerophospholipid).unwrap();
+        assert_eq!(graph.to_string(), POPC);
+        let glycerophospholipid: Glycerophospholipid = "LPS(16:0/0:0)".parse().unwrap();
+        let graph = MoleculeGraph::try_from(&glycerophospholipid).unwrap();
+        assert_eq!(
+            graph.to_string(),
+            "CCCCCCCCCCCCCCCC(=O)OC[C@@H](O)COP(=O)([O-])OC[C@H]([NH3+])C(=O)[O-]"
+        );
+        let glycerophospholipid: Glycerophospholipid = "PA(16:0/16:0)".parse().unwrap();
+        let graph = MoleculeGraph::try_from(&glycerophospholipid).unwrap();
+        assert_eq!(
+            graph.to_string(),
+            "CCCCCCCCCCCCCCCC(=O)OC[C@@H](OC(=O)CCCCCCCCCCCCCCC)COP(=O)([O-])O"
+        );
+    }
+
+    #[test]
+    fn recognition() {
+        let graph: MoleculeGraph = POPC.parse().unwrap();
+        let glycerophospholipid = Glycerophospholipid::try_from(&graph).unwrap();
+        assert_eq!(glycerophospholipid.to_string(), "PC(16:0/18:1(9Z))");
+        // Neutral phosphate written from the sn-2 acyl
+        let graph: MoleculeGraph =
+            "CCCCCCCCCCCCCCCC(=O)O[C@H](COC(=O)CCCCCCCCCCCCCCC)COP(=O)(O)OCCN"
+                .parse()
+                .unwrap();
+        let glycerophospholipid = Glycerophospholipid::try_from(&graph).unwrap();
+        assert_eq!(glycerophospholipid.to_string(), "PE(16:0/16:0)");
+        let graph: MoleculeGraph = "CCCCCCCCCCCCCCCC(=O)OCC(O)COP(=O)(O)OCC(O)CO"
+            .parse()
+            .unwrap();
+        let glycerophospholipid = Glycerophospholipid::try_from(&graph).unwrap();
+        assert_eq!(glycerophospholipid.to_string(), "LPG(16:0/0:0)[rac]");
+        let graph: MoleculeGraph = "CCCCCCCCCCCCCCCC(=O)OCC(O)COP(=O)(O)OC".parse().unwrap();
+        assert!(Glycerophospholipid::try_from(&graph).is_err());
+        let graph: MoleculeGraph = "OCC(O)COP(=O)(O)OCC[N+](C)(C)C".parse().unwrap();
+        assert!(Glycerophospholipid::try_from(&graph).is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        for headgroup in Headgroup::ALL {
+            for acyls in [
+                "16:0/18:1(9Z)",
+                "18:2(9Z,12Z)/0:0",
+                "0:0/20:4(5Z,8Z,11Z,14Z)",
+            ] {
+                let glycerophospholipid = Glycerophospholipid {
+                    headgroup,
+                    ..format!("PC({acyls})")
+                        .parse()
+                        .unwrap_or_else(|_| format!("LPC({acyls})").parse().unwrap())
+                };
+                let graph = MoleculeGraph::try_from(&glycerophospholipid).unwrap();
+                let graph: MoleculeGraph = graph.to_string().parse().unwrap();
+                assert_eq!(
+                    Glycerophospholipid::try_from(&graph).unwrap(),
+                    glycerophospholipid
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use super::{
+    error::{Error, Result},
+    fatty_acid::FattyAcid,
+    glycerolipid::{Glycerolipid, Positions},
+};
+use crate::semantic::MoleculeGraph;
+use std::collections::BTreeMap;
+
+const GLYCEROL: &str = "OCC(O)CO";
+const WATER: &str = "O";
+
+/// sn-1,3 specific lipase hydrolysis
+///
+/// Pancreatic lipase cleaves the primary (sn-1 and sn-3) esters, each with
+/// the given extent (`0` to `1`), the sn-2 ester is kept. Acyl migration is
+/// neglected. Amounts are in moles.
+pub fn hydrolysis(
+    species: impl IntoIterator<Item = (Glycerolipid, f64)>,
+    extent: f64,
+) -> Result<Hydrolysate> {
+    if !(0.0..=1.0).contains(&extent) {
+        return Err(Error::Extent(extent));
+    }
+    let mut hydrolysate = Hydrolysate::default();
+    for (glycerolipid, amount) in species {
+        if glycerolipid.positions == Positions::Unknown {
+            return Err(Error::UnknownSnPositions);
+        }
+        *hydrolysate.input.entry(glycerolipid.clone()).or_default() += amount;
+        let [sn1, _, sn3] = &glycerolipid.acyls;
+        for (first, x) in cleavages(sn1, extent) {
+            for (last, z) in cleavages(sn3, extent) {
+                let amount = amount * x * z;
+                if amount == 0.0 {
+                    continue;
+                }
+                let mut acyls = glycerolipid.acyls.clone();
+                for (index, cleaved) in [(0, first), (2, last)] {
+                    if let Some(fatty_acid) = cleaved.then(|| acyls[index].take()).flatten() {
+                        *hydrolysate.fatty_acids.entry(fatty_acid).or_default() += amount;
+                        hydrolysate.water += amount;
+                    }
+                }
+                let product = Glycerolipid::new(acyls, glycerolipid.positions);
+                let pool = match product.fatty_acids().count() {
+                    0 => {
+                        hydrolysate.glycerol += amount;
+                        continue;
+                    }
+                    1 => &mut hydrolysate.monoacylglycerols,
+                    2 => &mut hydrolysate.diacylglycerols,
+                    _ => &mut hydrolysate.triacylglycerols,
+                };
+                *pool.entry(product).or_default() += amount;
+            }
+        }
+    }
+    Ok(hydrolysate)
+}
+
+/// Products of the lipase hydrolysis
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Hydrolysate {
+    /// Hydrolysed glycerolipids
+    pub input: BTreeMap<Glycerolipid, f64>,
+    /// Unhydrolysed triacylglycerols
+    pub triacylglycerols: BTreeMap<Glycerolipid, f64>,
+    pub diacylglycerols: BTreeMap<Glycerolipid, f64>,
+    pub monoacylglycerols: BTreeMap<Glycerolipid, f64>,
+    /// Free fatty acids
+    pub fatty_acids: BTreeMap<FattyAcid, f64>,
+    pub glycerol: f64,
+    /// Consumed water
+    pub water: f64,
+}
+
+impl Hydrolysate {
+    /// Mass balance (g for amounts in moles)
+    pub fn balance(&self) -> Result<Balance> {
+        let water = molar_mass(WATER);
+        let glycerol = molar_mass(GLYCEROL);
+        let mut reactants = self.water * water;
+        for (glycerolipid, amount) in &self.input {
+            reactants += amount * glycerolipid_mass(glycerolipid)?;
+        }
+        let mut products = self.glycerol * glycerol;
+        for (glycerolipid, amount) in self
+            .triacylglycerols
+            .iter()
+            .chain(&self.diacylglycerols)
+            .chain(&self.monoacylglycerols)
+        {
+            products += amount * glycerolipid_mass(glycerolipid)?;
+        }
+        for (fatty_acid, amount) in &self.fatty_acids {
+            let graph = MoleculeGraph::try_from(fatty_acid)?;
+            products += amount * graph.molar_mass().unwrap_or_default();
+        }
+        Ok(Balance {
+            reactants,
+            products,
+        })
+    }
+}
+
+/// Mass balance
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Balance {
+    pub reactants: f64,
+    pub products: f64,
+}
+
+impl Balance {
+    /// Relative difference of the products and the reactants
+    pub fn error(&self) -> f64 {
+        (self.products - self.reactants) / self.reactants
+    }
+}
+
+/// Cleaved or kept ester with its probability, a free hydroxyl always kept
+fn cleavages(acyl: &Option<FattyAcid>, extent: f64) -> Vec<(bool, f64)> {
+    match acyl {
+        Some(_) => vec![(false, 1.0 - extent), (true, extent)],
+        None => vec![(false, 1.0)],
+    }
+}
+
+fn glycerolipid_mass(glycerolipid: &Glycerolipid) -> Result<f64> {
+    let graph = MoleculeGraph::try_from(glycerolipid)?;
+    Ok(graph.molar_mass().unwrap_or_default())
+}
+
+fn molar_mass(smiles: &str) -> f64 {
+    smiles
+        .parse::<MoleculeGraph>()
+        .ok()
+        .and_then(|graph| graph.molar_mass())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn complete() {
+        let popo: Glycerolipid = "TG(16:0/18:1(9Z)/16:0)".parse().unwrap();
+        let hydrolysate = hydrolysis([(popo, 1.0)], 1.0).unwrap();
+        assert!(hydrolysate.triacylglycerols.is_empty());
+        assert!(hydrolysate.diacylglycerols.is_empty());
+        let monoacylglycerol: Glycerolipid = "MG(0:0/18:1(9Z)/0:0)".parse().unwrap();
+        assert_eq!(hydrolysate.monoacylglycerols[&monoacylglycerol], 1.0);
+        assert_eq!(hydrolysate.fatty_acids[&"16:0".parse().unwrap()], 2.0);
+        assert_eq!(hydrolysate.water, 2.0);
+        assert!(hydrolysate.balance().unwrap().error().abs() < 1e-12);
+    }
+
+    #[test]
+    fn partial() {
+        let graph: MoleculeGraph = "CCCCCCCCCCCCCCCC(=O)OC[C@@H](OC(=O)CCCCCCC/C=C\\CCCCCCCC)COC(=O)CCCCCCC/C=C\\C/C=C\\CCCCC"
+            .parse()
+            .unwrap();
+        let glycerolipid = Glycerolipid::try_from(&graph).unwrap();
+        let hydrolysate = hydrolysis([(glycerolipid, 2.0)], 0.5).unwrap();
+        let amounts = |pool: &BTreeMap<Glycerolipid, f64>| {
+            pool.iter()
+                .map(|(glycerolipid, &amount)| (glycerolipid.to_string(), amount))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            amounts(&hydrolysate.triacylglycerols),
+            [("TG(16:0/18:1(9Z)/18:2(9Z,12Z))".to_owned(), 0.5)]
+        );
+        assert_eq!(
+            amounts(&hydrolysate.diacylglycerols),
+            [
+                ("DG(0:0/18:1(9Z)/18:2(9Z,12Z))".to_owned(), 0.5),
+                ("DG(16:0/18:1(9Z)/0:0)".to_owned(), 0.5),
+            ]
+        );
+        assert_eq!(
+            amounts(&hydrolysate.monoacylglycerols),
+            [("MG(0:0/18:1(9Z)/0:0)".to_owned(), 0.5)]
+        );
+        let free: f64 = hydrolysate.fatty_acids.values().sum();
+        assert_eq!(free, hydrolysate.water);
+        assert_eq!(free, 2.0);
+        assert!(hydrolysate.balance().unwrap().error().abs() < 1e-12);
+        let unknown: Glycerolipid = "TG(16:0_18:1_18:2)".parse().unwrap();
+        assert!(hydrolysis([(unknown, 1.0)], 1.0).is_err());
+        assert!(hydrolysis([], 1.5).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+use super::{
+    distribution::{Composition, Distribution, Model, Species},
+    error::{Error, Result},
+    glycerolipid::{Glycerolipid, Positions},
+};
+
+/// Interesterification
+///
+/// Exchange of the acyls of a TAG blend. At equilibrium the acyls exchanged
+/// are randomly distributed over the positions they can reach.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Interesterification {
+    /// Chemical (alkoxide catalysed): all positions randomised
+    Chemical,
+    /// sn-1,3 specific lipase: sn-1 and sn-3 randomised, sn-2 retained
+    Enzymatic,
+}
+
+impl Interesterification {
+    /// Product TAG species of the blend (amounts in moles), most abundant
+    /// first
+    pub fn products(
+        &self,
+        species: impl IntoIterator<Item = (Glycerolipid, f64)>,
+    ) -> Result<Vec<Species>> {
+        let mut total = Composition::new();
+        let mut sn2 = Composition::new();
+        for (glycerolipid, amount) in species {
+            if glycerolipid.fatty_acids().count() != 3 {
+                return Err(Error::Composition("triacylglycerols only"));
+            }
+            if *self == Self::Enzymatic && glycerolipid.positions == Positions::Unknown {
+                return Err(Error::UnknownSnPositions);
+            }
+            for fatty_acid in glycerolipid.fatty_acids() {
+                *total.entry(fatty_acid.clone()).or_default() += amount;
+            }
+            if let Some(fatty_acid) = &glycerolipid.acyls[1] {
+                *sn2.entry(fatty_acid.clone()).or_default() += amount;
+            }
+        }
+        let distribution = Distribution::new(total)?;
+        match self {
+            Self::Chemical => distribution.species(Model::Random),
+            Self::Enzymatic => distribution.with_sn2(sn2)?.species(Model::Regiospecific),
+        }
+    }
+}
+
+impl Species {
+    /// Canonical SMILES of the species
+    pub fn smiles(&self) -> Result<String> {
+        Ok(self.graph()?.canonical_smiles())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::semantic::MoleculeGraph;
+
+    fn blend() -> Vec<(Glycerolipid, f64)> {
+        vec![
+            ("TG(16:0/16:0/16:0)".parse().unwrap(), 1.0),
+            ("TG(18:1(9Z)/18:1(9Z)/18:1(9Z))".parse().unwrap(), 1.0),
+        ]
+    }
+
+    #[test]
+    fn chemical() {
+        let products = Interesterification::Chemical.products(blend()).unwrap();
+        assert_eq!(products.len(), 6);
+        assert_eq!(
+            products[0].glycerolipid.to_string(),
+            "TG(16:0/16:0/18:1(9Z))[rac]"
+        );
+        assert_eq!(products[0].fraction, 0.25);
+        let sum: f64 = products.iter().map(|species| species.fraction).sum();
+        assert!((sum - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn enzymatic() {
+        // The sn-2 acyls stay in place, no species with 16:0 at sn-2 forms
+        let products = Interesterification::Enzymatic
+            .products([
+                ("TG(16:0/18:1(9Z)/16:0)".parse().unwrap(), 1.0),
+                ("TG(18:1(9Z)/18:1(9Z)/18:1(9Z))".parse().unwrap(), 1.0),
+            ])
+            .unwrap();
+        let fractions: Vec<_> = products
+            .iter()
+            .map(|species| (species.glycerolipid.to_string(), species.fraction))
+            .collect();
+        assert_eq!(
+            fractions,
+            [
+                ("TG(16:0/18:1(9Z)/18:1(9Z))[rac]".to_owned(), 0.5),
+                ("TG(16:0/18:1(9Z)/16:0)".to_owned(), 0.25),
+                ("TG(18:1(9Z)/18:1(9Z)/18:1(9Z))".to_owned(), 0.25),
+            ]
+        );
+        let smiles = products[1].smiles().unwrap();
+        let graph: MoleculeGraph =
+            "CCCCCCCCCCCCCCCC(=O)OCC(COC(=O)CCCCCCCCCCCCCCC)OC(=O)CCCCCCC/C=C\\CCCCCCCC"
+                .parse()
+                .unwrap();
+        assert_eq!(smiles, graph.canonical_smiles());
+        let unknown = [("TG(16:0_16:0_18:1)".parse().unwrap(), 1.0)];
+        assert!(Interesterification::Enzymatic
+            .products(unknown.clone())
+            .is_err());
+        assert!(Interesterification::Chemical.products(unknown).is_ok());
+    }
+}
@@ -0,0 +1,299 @@
+use super::fatty_acid::{FattyAcid, Unsaturation};
+use crate::semantic::{Bond, Geometry, MoleculeGraph};
+use std::collections::BTreeSet;
+
+/// Isomer enumeration constraints
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Constraints {
+    /// Only methylene-interrupted unsaturations
+    pub methylene_interrupted: bool,
+    /// Δ locant of the first unsaturation
+    pub first: Option<u8>,
+    /// ω family, the ω locant of the last unsaturation (`3` for n-3)
+    pub family: Option<u8>,
+    /// Geometries of the double bonds, unspecified if empty
+    pub geometries: Vec<Geometry>,
+}
+
+impl Default for Constraints {
+    fn default() -> Self {
+        Self {
+            methylene_interrupted: false,
+            first: None,
+            family: None,
+            geometries: vec![Geometry::Z, Geometry::E],
+        }
+    }
+}
+
+/// Enumerated isomer
+#[derive(Clone, Debug)]
+pub struct Isomer {
+    pub fatty_acid: FattyAcid,
+    pub graph: MoleculeGraph,
+}
+
+impl FattyAcid {
+    /// Positional and geometric isomers
+    ///
+    /// Unsaturations without a position are placed on every free chain bond,
+    /// cumulated bonds excluded, double bonds without a geometry take each of
+    /// the constrained geometries. Isomers with the same canonical SMILES are
+    /// kept once, in fatty acid order.
+    pub fn isomers(&self, constraints: &Constraints) -> Vec<Isomer> {
+        let fixed: Vec<_> = self
+            .unsaturations
+            .iter()
+            .filter(|unsaturation| unsaturation.index.is_some())
+            .copied()
+            .collect();
+        let mut free: Vec<_> = self
+            .unsaturations
+            .iter()
+            .filter(|unsaturation| unsaturation.index.is_none())
+            .map(|unsaturation| unsaturation.bond)
+            .collect();
+        free.sort();
+        let mut placements = Vec::new();
+        self.place(&free, 2, fixed, &mut placements);
+        let mut fatty_acids = BTreeSet::new();
+        for mut unsaturations in placements {
+            unsaturations.sort();
+            if !constraints.allows(self.carbons, &unsaturations) {
+                continue;
+            }
+            let mut geometric = vec![Vec::new()];
+            for unsaturation in &unsaturations {
+                let geometries = match unsaturation.geometry {
+                    None if unsaturation.bond == Bond::Double
+                        && unsaturation
+                            .index
+                            .is_some_and(|index| index + 1 < self.carbons) =>
+                    {
+                        constraints.geometries.iter().copied().map(Some).collect()
+                    }
+                    geometry => vec![geometry],
+                };
+                let geometries = if geometries.is_empty() {
+                    vec![None]
+                } else {
+                    geometries
+                };
+                geometric = geometric
+                    .into_iter()
+                    .flat_map(|isomer: Vec<Unsaturation>| {
+                        geometries.iter().map(move |&geometry| {
+                            let mut isomer = isomer.clone();
+                            isomer.push(Unsaturation {
+                                geometry,
+                                ..*unsaturation
+                            });
+                            isomer
+                        })
+                    })
+                    .collect();
+            }
+            for unsaturations in geometric {
+                let mut fatty_acid = Self::new(self.carbons, unsaturations);
+                fatty_acid.substituents = self.substituents.clone();
+                fatty_acids.insert(fatty_acid);
+            }
+        }
+        let mut smiles = BTreeSet::new();
+        let mut isomers = Vec::new();
+        for fatty_acid in fatty_acids {
+            // Valence conflicts with the substituents are skipped
+            let Ok(graph) = MoleculeGraph::try_from(&fatty_acid) else {
+                continue;
+            };
+            if smiles.insert(graph.canonical_smiles()) {
+                isomers.push(Isomer { fatty_acid, graph });
+            }
+        }
+        isomers
+    }
+
+    /// Places the free unsaturations on chain bonds from `from` on, no two
+    /// sharing a carbon
+    ///
+    /// Unsaturations of the same bond are placed in ascending order, so each
+    /// placement is generated once.
+    fn place(
+        &self,
+        free: &[Bond],
+        from: u8,
+        placed: Vec<Unsaturation>,
+        placements: &mut Vec<Vec<Unsaturation>>,
+    ) {
+        let Some((&bond, free)) = free.split_first() else {
+            placements.push(placed);
+            return;
+        };
+        for index in from..self.carbons {
+            let occupied = placed.iter().any(|unsaturation| {
+                unsaturation
+                    .index
+                    .is_some_and(|other| other.abs_diff(index) < 2)
+            });
+            if occupied {
+                continue;
+            }
+            let mut placed = placed.clone();
+            placed.push(Unsaturation {
+                index: Some(index),
+                bond,
+                geometry: None,
+            });
+            let from = match free.first() {
+                Some(&next) if next == bond => index + 2,
+                _ => 2,
+            };
+            self.place(free, from, placed, placements);
+        }
+    }
+}
+
+impl Constraints {
+    fn allows(&self, carbons: u8, unsaturations: &[Unsaturation]) -> bool {
+        let mut indices: Vec<_> = unsaturations
+            .iter()
+            .filter_map(|unsaturation| unsaturation.index)
+            .collect();
+        indices.sort();
+        if self.methylene_interrupted && indices.windows(2).any(|pair| pair[1] - pair[0] != 3) {
+            return false;
+        }
+        if self
+            .first
+            .is_some_and(|first| indices.first() != Some(&first))
+        {
+            return false;
+        }
+        if self
+            .family
+            .is_some_and(|family| indices.last().map(|last| carbons - last) != Some(family))
+        {
+            return false;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lipid::catalogue::Entry;
+
+    fn isomers(shorthand: &str, constraints: &Constraints) -> Vec<Isomer> {
+        shorthand.parse::<FattyAcid>().unwrap().isomers(constraints)
+    }
+
+    #[test]
+    fn positional() {
+        // Δ2 to Δ17, the terminal bond without geometry
+        assert_eq!(isomers("18:1", &Constraints::default()).len(), 31);
+        let constraints = Constraints {
+            geometries: Vec::new(),
+            ..Default::default()
+        };
+        assert_eq!(isomers("18:1", &constraints).len(), 16);
+        // Non-cumulated pairs among 16 bonds
+        assert_eq!(isomers("18:2", &constraints).len(), 105);
+        assert_eq!(isomers("18:0", &constraints).len(), 1);
+        let all = isomers("18:2", &Constraints::default());
+        let smiles: BTreeSet<_> = all
+            .iter()
+            .map(|isomer| isomer.graph.canonical_smiles())
+            .collect();
+        assert_eq!(smiles.len(), all.len());
+    }
+
+    #[test]
+    fn constrained() {
+        let cis = Constraints {
+            methylene_interrupted: true,
+            geometries: vec![Geometry::Z],
+            ..Default::default()
+        };
+        let linoleic = isomers(
+            "18:2",
+            &Constraints {
+                first: Some(9),
+                ..cis.clone()
+            },
+        );
+        assert_eq!(linoleic.len(), 1);
+        assert_eq!(
+            Entry::identify(&linoleic[0].graph).map(Entry::name),
+            Some("linoleic")
+        );
+        let families = |shorthand, family| {
+            isomers(
+                shorthand,
+                &Constraints {
+                    family: Some(family),
+                    ..cis.clone()
+                },
+            )
+            .iter()
+            .filter_map(|isomer| Entry::identify(&isomer.graph).map(Entry::name))
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(families("18:3", 3), ["alpha-linolenic"]);
+        assert_eq!(families("18:3", 6), ["gamma-linolenic"]);
+        assert_eq!(families("20:3", 9), ["mead"]);
+        assert_eq!(families("20:4", 6), ["arachidonic"]);
+        assert_eq!(
+            isomers(
+                "22:6",
+                &Constraints {
+                    family: Some(3),
+                    ..cis.clone()
+                }
+            )[0]
+            .fatty_acid
+            .to_string(),
+            "22:6(4Z,7Z,10Z,13Z,16Z,19Z)"
+        );
+        // Geometric isomers of linoleic acid
+        let geometric = isomers(
+            "18:2",
+            &Constraints {
+                methylene_interrupted: true,
+                first: Some(9),
+                family: Some(6),
+                ..Default::default()
+            },
+        );
+        assert_eq!(geometric.len(), 4);
+    }
+
+    #[test]
+    fn fixed() {
+        // Known positions keep their place, only geometries vary
+        let constraints = Constraints::default();
+        assert_eq!(isomers("18:2(9,12)", &constraints).len(), 4);
+        assert_eq!(isomers("18:2(9Z,12)", &constraints).len(), 2);
+        // Oleic acid with a second double bond anywhere
+        let fatty_acid = FattyAcid::new(
+            18,
+            [
+                Unsaturation::double(9, Some(Geometry::Z)),
+                Unsaturation {
+                    index: None,
+                    bond: Bond::Double,
+                    geometry: None,
+                },
+            ],
+        );
+        let oleic = fatty_acid.isomers(&Constraints {
+            first: Some(9),
+            methylene_interrupted: true,
+            ..constraints
+        });
+        assert_eq!(oleic.len(), 2);
+        assert!(oleic
+            .iter()
+            .all(|isomer| isomer.fatty_acid.unsaturations[1].index == Some(12)));
+    }
+}
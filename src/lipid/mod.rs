@@ -0,0 +1,41 @@
+//! Lipids
+//!
+//! Fatty acids, glycerolipids and glycerophospholipids: their shorthand
+//! notation, recognition in and construction of molecule graphs, and the
+//! reactions, distributions and analyses built on them.
+
+pub use self::{
+    catalogue::Entry,
+    chromatography::{equivalent_chain_length, Increments},
+    class::{Category, Class, Lipid},
+    distribution::{Composition, Distribution, Model, Species},
+    error::Error,
+    fatty_acid::{FattyAcid, Functional, Shorthand, Substituent, Unsaturation},
+    fragmentation::{Fragment, Ion},
+    glycerolipid::{Glycerolipid, Positions},
+    glycerophospholipid::{Glycerophospholipid, Headgroup},
+    hydrolysis::{hydrolysis, Balance, Hydrolysate},
+    interesterification::Interesterification,
+    isomers::{Constraints, Isomer},
+    pattern::{Branch, Cycle, Oxygenated, Pattern, Spacing, System},
+    quality::{Quality, Structure},
+    transesterification::{Transesterification, Transesterified},
+};
+
+mod catalogue;
+mod chromatography;
+mod class;
+mod distribution;
+mod error;
+mod fatty_acid;
+mod fragmentation;
+mod glycerolipid;
+mod glycerophospholipid;
+mod hydrolysis;
+mod interesterification;
+mod isomers;
+mod name;
+mod nomenclature;
+mod pattern;
+mod quality;
+mod transesterification;
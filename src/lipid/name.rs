@@ -0,0 +1,569 @@
+use super::{
+    catalogue::Entry,
+    error::{Error, NameError, Result},
+    fatty_acid::{carboxyl_oxygens, FattyAcid, Functional, Substituent, Unsaturation},
+    nomenclature::numeral,
+};
+use crate::semantic::{Atom, Bond, Element, Geometry, MoleculeGraph, Notation};
+use std::collections::BTreeMap;
+
+const MULTIPLIERS: &[&str] = &[
+    "di", "tri", "tetra", "penta", "hexa", "hepta", "octa", "nona", "deca",
+];
+const SUBSTITUENTS: &[&str] = &["hydroxy", "methyl", "oxo"];
+
+impl MoleculeGraph {
+    /// Structure of a fatty acid or alkyl ester name
+    ///
+    /// Accepts systematic names, `(9Z,12Z)-octadeca-9,12-dienoic acid`,
+    /// their older forms, `cis-9-octadecenoic acid` or
+    /// `(5Z,8Z,11Z,14Z)-eicosatetraenoic acid`, and the trivial names of the
+    /// [catalogue](Entry), `methyl linoleate`. Acyclic chains with methyl,
+    /// hydroxy and oxo substituents only.
+    pub fn from_name(name: &str) -> Result<Self> {
+        let mut parser = Parser {
+            input: name.trim(),
+            position: 0,
+        };
+        let alkyl = parser.alkyl();
+        let fatty_acid = match trivial(parser.rest(), alkyl.is_some()) {
+            Some(fatty_acid) => Acid {
+                fatty_acid,
+                ..Default::default()
+            },
+            None => {
+                let acid = parser.acid(alkyl.is_some())?;
+                parser.end()?;
+                acid
+            }
+        };
+        fatty_acid.graph(alkyl)
+    }
+}
+
+/// Parsed acid
+#[derive(Clone, Debug, Default)]
+struct Acid {
+    fatty_acid: FattyAcid,
+    hydroxy: Vec<usize>,
+    oxo: Vec<usize>,
+}
+
+impl Acid {
+    /// Free acid, or its ester with the alkyl of the given carbons
+    fn graph(&self, alkyl: Option<usize>) -> Result<MoleculeGraph> {
+        let mut graph = MoleculeGraph::try_from(&self.fatty_acid)?;
+        let chain = graph.principal_chain().ok_or(Error::CarboxylNotFound)?;
+        let carbons = self.fatty_acid.carbons as usize;
+        let unsaturated = |locant: usize| {
+            self.fatty_acid.unsaturations.iter().any(|unsaturation| {
+                unsaturation
+                    .index
+                    .is_some_and(|index| [index, index + 1].contains(&(locant as u8)))
+            })
+        };
+        for (locants, bond) in [(&self.hydroxy, Bond::Single), (&self.oxo, Bond::Double)] {
+            for &locant in locants {
+                if !(2..=carbons).contains(&locant) {
+                    return Err(Error::Locant {
+                        index: locant as _,
+                        carbons: carbons as _,
+                    });
+                }
+                if bond == Bond::Double && unsaturated(locant) {
+                    return Err(Error::Valence { index: locant as _ });
+                }
+                let carbon = chain.atom(locant, Notation::Delta).unwrap();
+                // Chain, methyl and earlier oxygen bonds
+                let valence: u8 = graph
+                    .edges(carbon)
+                    .map(|edge| edge.weight().valence())
+                    .sum();
+                if valence + bond.valence() > 4 {
+                    return Err(Error::Valence { index: locant as _ });
+                }
+                let oxygen = graph.add_node(atom(Element::O));
+                graph.add_edge(carbon, oxygen, bond);
+            }
+        }
+        if let Some(alkyl) = alkyl {
+            let (_, mut previous) =
+                carboxyl_oxygens(&graph, chain[0]).ok_or(Error::CarboxylNotFound)?;
+            for _ in 0..alkyl {
+                let carbon = graph.add_node(atom(Element::C));
+                graph.add_edge(previous, carbon, Bond::Single);
+                previous = carbon;
+            }
+        }
+        Ok(graph)
+    }
+}
+
+/// Catalogued fatty acid of a trivial acid (`oleic acid`) or ester
+/// (`linoleate`) name
+fn trivial(name: &str, ester: bool) -> Option<FattyAcid> {
+    let name = name.to_lowercase();
+    let name = if ester {
+        format!("{}ic", name.strip_suffix("ate")?)
+    } else {
+        name.strip_suffix("acid")?.trim_end().to_owned()
+    };
+    Entry::ALL
+        .iter()
+        .find(|entry| entry.names.contains(&name.as_str()))
+        .map(Entry::fatty_acid)
+}
+
+/// Recursive descent name parser
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    /// Consumes the longest alternative, case insensitive
+    fn literal(&mut self, alternatives: &[&'static str]) -> Option<&'static str> {
+        let rest = self.rest();
+        let found = alternatives
+            .iter()
+            .filter(|alternative| {
+                rest.get(..alternative.len())
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case(alternative))
+            })
+            .max_by_key(|alternative| alternative.len())?;
+        self.position += found.len();
+        Some(found)
+    }
+
+    fn expect(&mut self, alternatives: &'static [&'static str]) -> Result<&'static str> {
+        self.literal(alternatives)
+            .ok_or_else(|| self.error(alternatives))
+    }
+
+    /// Error at the word, number or character at the position
+    fn error(&self, expected: &'static [&'static str]) -> Error {
+        let rest = self.rest();
+        let length = match rest.chars().next() {
+            None => 0,
+            Some(char) if char.is_alphabetic() => rest
+                .find(|char: char| !char.is_alphabetic())
+                .unwrap_or(rest.len()),
+            Some(char) if char.is_ascii_digit() => rest
+                .find(|char: char| !char.is_ascii_digit())
+                .unwrap_or(rest.len()),
+            Some(char) => char.len_utf8(),
+        };
+        Error::Name(NameError {
+            expected,
+            found: rest[..length].to_owned(),
+            span: self.position..self.position + length,
+        })
+    }
+
+    fn is_digit(&self) -> bool {
+        self.rest().starts_with(|char: char| char.is_ascii_digit())
+    }
+
+    fn number(&mut self) -> Option<usize> {
+        let rest = self.rest();
+        let length = rest
+            .find(|char: char| !char.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let number = rest[..length].parse().ok()?;
+        self.position += length;
+        Some(number)
+    }
+
+    /// Comma separated locants
+    fn locants(&mut self) -> Result<Vec<usize>> {
+        let mut locants = vec![self.number().ok_or_else(|| self.error(&["locant"]))?];
+        while self.rest().starts_with(',') {
+            self.position += 1;
+            locants.push(self.number().ok_or_else(|| self.error(&["locant"]))?);
+        }
+        Ok(locants)
+    }
+
+    /// Carbons of a saturated alkyl followed by a space (`methyl `)
+    fn alkyl(&mut self) -> Option<usize> {
+        let start = self.position;
+        let carbons = self.numeral();
+        if carbons.is_some() && self.literal(&["yl"]).is_some() {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            if trimmed.len() < rest.len() {
+                self.position += rest.len() - trimmed.len();
+                return carbons;
+            }
+        }
+        self.position = start;
+        None
+    }
+
+    /// Chain length stem (`octadec`, `eicos`)
+    fn numeral(&mut self) -> Option<usize> {
+        let rest = self.rest();
+        let (carbons, length) = (1..100)
+            .flat_map(|carbons| {
+                let numeral = numeral(carbons).unwrap_or_default();
+                let eicos = (carbons == 20).then(|| "eicos".to_owned());
+                [Some(numeral), eicos]
+                    .into_iter()
+                    .flatten()
+                    .map(move |numeral| (carbons, numeral))
+            })
+            .filter(|(_, numeral)| {
+                rest.get(..numeral.len())
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case(numeral))
+            })
+            .map(|(carbons, numeral)| (carbons, numeral.len()))
+            .max_by_key(|&(_, length)| length)?;
+        self.position += length;
+        Some(carbons)
+    }
+
+    fn multiplier(&mut self) -> Option<usize> {
+        let multiplier = self.literal(MULTIPLIERS)?;
+        Some(MULTIPLIERS.iter().position(|&m| m == multiplier).unwrap() + 2)
+    }
+
+    fn acid(&mut self, ester: bool) -> Result<Acid> {
+        let mut acid = Acid::default();
+        // Geometry descriptors, with or without locants
+        let mut descriptors: Vec<(Option<usize>, Geometry)> = Vec::new();
+        let mut all = None;
+        if self.literal(&["("]).is_some() {
+            loop {
+                let locant = self.number();
+                let geometry = match self.expect(&["Z", "E"])? {
+                    "Z" => Geometry::Z,
+                    _ => Geometry::E,
+                };
+                descriptors.push((locant, geometry));
+                if self.literal(&[","]).is_none() {
+                    break;
+                }
+            }
+            self.expect(&[")"])?;
+            self.expect(&["-"])?;
+        } else {
+            loop {
+                match self.literal(&["all-cis", "all-trans", "cis", "trans"]) {
+                    Some("all-cis") => all = Some(Geometry::Z),
+                    Some("all-trans") => all = Some(Geometry::E),
+                    Some("cis") => descriptors.push((None, Geometry::Z)),
+                    Some(_) => descriptors.push((None, Geometry::E)),
+                    None => break,
+                }
+                if self.literal(&[","]).is_none() {
+                    self.expect(&["-"])?;
+                    break;
+                }
+            }
+        }
+        // Substituent prefixes, the last locants possibly of the unsaturations
+        let mut unsaturations = None;
+        let mut methyls = Vec::new();
+        while self.is_digit() {
+            let locants = self.locants()?;
+            self.expect(&["-"])?;
+            let start = self.position;
+            let count = self.multiplier().unwrap_or(1);
+            let Some(substituent) = self.literal(SUBSTITUENTS) else {
+                self.position = start;
+                unsaturations = Some(locants);
+                break;
+            };
+            if count != locants.len() {
+                return Err(Error::Count {
+                    expected: count as _,
+                    found: locants.len() as _,
+                });
+            }
+            match substituent {
+                "hydroxy" => acid.hydroxy.extend(locants),
+                "oxo" => acid.oxo.extend(locants),
+                _ => methyls.extend(locants),
+            }
+            if self.rest().starts_with('-') {
+                self.position += 1;
+                if !self.is_digit() {
+                    return Err(self.error(&["locant"]));
+                }
+            }
+        }
+        let carbons = self.numeral().ok_or_else(|| self.error(&["numeral"]))?;
+        acid.fatty_acid.carbons = carbons as _;
+        for locant in methyls {
+            acid.fatty_acid.substituents.push(Substituent {
+                index: index(locant, carbons)?,
+                kind: Functional::Methyl,
+            });
+        }
+        // Unsaturations, none for `-anoic`
+        let start = self.position;
+        let saturated = self.literal(&["an"]).is_some() && self.is_ending();
+        if !saturated {
+            self.position = start;
+        }
+        let mut segments = Vec::new();
+        if !saturated {
+            self.literal(&["a"]);
+            for (infix, bond) in [("en", Bond::Double), ("yn", Bond::Triple)] {
+                let start = self.position;
+                let mut locants = None;
+                if self.literal(&["-"]).is_some() {
+                    locants = Some(self.locants()?);
+                    self.expect(&["-"])?;
+                }
+                let count = self.multiplier().unwrap_or(1);
+                if self.literal(&[infix]).is_none() {
+                    self.position = start;
+                    continue;
+                }
+                let locants = match locants.or_else(|| unsaturations.take()) {
+                    Some(locants) => locants,
+                    None if bond == Bond::Double => descriptors
+                        .iter()
+                        .map(|(locant, _)| locant.ok_or(Error::UnknownPositions))
+                        .collect::<Result<Vec<_>>>()?,
+                    None => Vec::new(),
+                };
+                if locants.len() != count {
+                    return Err(if locants.is_empty() {
+                        Error::UnknownPositions
+                    } else {
+                        Error::Count {
+                            expected: count as _,
+                            found: locants.len() as _,
+                        }
+                    });
+                }
+                segments.push((bond, locants));
+            }
+            if segments.is_empty() {
+                return Err(self.error(&["an", "en", "yn"]));
+            }
+        }
+        // Geometries of the double bonds
+        let enes: Vec<_> = segments
+            .iter()
+            .filter(|(bond, _)| *bond == Bond::Double)
+            .flat_map(|(_, locants)| locants.iter().copied())
+            .collect();
+        let mut geometries = BTreeMap::new();
+        if let Some(geometry) = all {
+            geometries.extend(enes.iter().map(|&locant| (locant, geometry)));
+        } else if descriptors.iter().all(|(locant, _)| locant.is_some()) {
+            // Each on its own double bond
+            for &(locant, geometry) in &descriptors {
+                let locant = locant.unwrap();
+                if !enes.contains(&locant) || geometries.insert(locant, geometry).is_some() {
+                    return Err(Error::Locant {
+                        index: index(locant, carbons)?,
+                        carbons: carbons as _,
+                    });
+                }
+            }
+        } else if descriptors.len() == enes.len() {
+            geometries.extend(enes.iter().zip(&descriptors).map(|(&l, &(_, g))| (l, g)));
+        } else {
+            return Err(Error::Count {
+                expected: enes.len() as _,
+                found: descriptors.len() as _,
+            });
+        }
+        let mut unsaturations = Vec::new();
+        for (bond, locants) in segments {
+            for locant in locants {
+                unsaturations.push(Unsaturation {
+                    index: Some(index(locant, carbons)?),
+                    bond,
+                    geometry: geometries.get(&locant).copied(),
+                });
+            }
+        }
+        unsaturations.sort();
+        acid.fatty_acid.unsaturations = unsaturations;
+        acid.fatty_acid.substituents.sort();
+        // Ending
+        if ester {
+            self.expect(&["oate"])?;
+        } else {
+            self.expect(&["oic"])?;
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            if trimmed.len() == rest.len() {
+                return Err(self.error(&[" "]));
+            }
+            self.position += rest.len() - trimmed.len();
+            self.expect(&["acid"])?;
+        }
+        Ok(acid)
+    }
+
+    fn is_ending(&self) -> bool {
+        let rest = self.rest().to_lowercase();
+        rest.starts_with("oic") || rest.starts_with("oate")
+    }
+
+    fn end(&self) -> Result<()> {
+        if self.rest().is_empty() {
+            Ok(())
+        } else {
+            Err(self.error(&["end of name"]))
+        }
+    }
+}
+
+/// Locant as a chain index, saturated in the error past its range
+fn index(locant: usize, carbons: usize) -> Result<u8> {
+    u8::try_from(locant).map_err(|_| Error::Locant {
+        index: u8::MAX,
+        carbons: carbons as _,
+    })
+}
+
+fn atom(element: Element) -> Atom {
+    Atom {
+        element: Some(element),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn smiles(name: &str) -> String {
+        MoleculeGraph::from_name(name).unwrap().canonical_smiles()
+    }
+
+    fn canonical(smiles: &str) -> String {
+        smiles.parse::<MoleculeGraph>().unwrap().canonical_smiles()
+    }
+
+    #[test]
+    fn names() {
+        let oleic = canonical("CCCCCCCC/C=C\\CCCCCCCC(=O)O");
+        for name in [
+            "cis-9-octadecenoic acid",
+            "(9Z)-octadec-9-enoic acid",
+            "(Z)-octadec-9-enoic acid",
+            "oleic acid",
+        ] {
+            assert_eq!(smiles(name), oleic, "{name}");
+        }
+        assert_eq!(
+            smiles("trans-9-octadecenoic acid"),
+            canonical("CCCCCCCC/C=C/CCCCCCCC(=O)O")
+        );
+        let arachidonic = canonical("CCCCC/C=C\\C/C=C\\C/C=C\\C/C=C\\CCCC(=O)O");
+        for name in [
+            "(5Z,8Z,11Z,14Z)-eicosatetraenoic acid",
+            "all-cis-5,8,11,14-eicosatetraenoic acid",
+            "(5Z,8Z,11Z,14Z)-icosa-5,8,11,14-tetraenoic acid",
+        ] {
+            assert_eq!(smiles(name), arachidonic, "{name}");
+        }
+        assert_eq!(
+            smiles("cis,cis-9,12-octadecadienoic acid"),
+            canonical("CCCCC/C=C\\C/C=C\\CCCCCCCC(=O)O")
+        );
+        assert_eq!(
+            smiles("methyl linoleate"),
+            canonical("CCCCC/C=C\\C/C=C\\CCCCCCCC(=O)OC")
+        );
+        assert_eq!(
+            smiles("ethyl hexadecanoate"),
+            canonical("CCCCCCCCCCCCCCCC(=O)OCC")
+        );
+        assert_eq!(
+            smiles("octadec-9-ynoic acid"),
+            canonical("CCCCCCCCC#CCCCCCCCC(=O)O")
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        for entry in Entry::ALL {
+            let graph = MoleculeGraph::from_name(entry.systematic).unwrap();
+            assert_eq!(Entry::identify(&graph), Some(entry));
+        }
+        for smiles in [
+            "CCCCCCCC/C=C\\CCCCCCCC(=O)OC",
+            "CC(C)CCCCCCCCCCCCCC(=O)O",
+            "CCCCCCC(O)C/C=C\\CCCCCCCC(=O)O",
+            "CCCCCCCCCC(=O)CC(O)CCCCCC(=O)O",
+        ] {
+            let graph: MoleculeGraph = smiles.parse().unwrap();
+            let name = graph.systematic_name().unwrap();
+            let parsed = MoleculeGraph::from_name(&name).unwrap();
+            assert_eq!(
+                parsed.canonical_smiles(),
+                graph.canonical_smiles(),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let Err(Error::Name(error)) = MoleculeGraph::from_name("(9Z)-octadec-9-enoic acdi") else {
+            panic!();
+        };
+        assert_eq!(error.expected, ["acid"]);
+        assert_eq!(error.found, "acdi");
+        assert_eq!(error.span, 21..25);
+        let Err(Error::Name(error)) = MoleculeGraph::from_name("foo acid") else {
+            panic!();
+        };
+        assert_eq!(error.expected, ["numeral"]);
+        assert_eq!(error.span, 0..3);
+        assert!(matches!(
+            MoleculeGraph::from_name("octadecenoic acid"),
+            Err(Error::UnknownPositions)
+        ));
+        assert!(matches!(
+            MoleculeGraph::from_name("9-octadecadienoic acid"),
+            Err(Error::Count { .. })
+        ));
+        assert!(matches!(
+            MoleculeGraph::from_name("9,9-octadecadienoic acid"),
+            Err(Error::Locant { index: 9, .. })
+        ));
+        for name in [
+            "(5Z)-octadeca-9,12-dienoic acid",
+            "(9Z,12Z)-octadec-9-enoic acid",
+            "(9Z)-octadec-9-ynoic acid",
+            "(9Z,9E)-octadec-9-enoic acid",
+        ] {
+            assert!(
+                matches!(MoleculeGraph::from_name(name), Err(Error::Locant { .. })),
+                "{name}"
+            );
+        }
+        assert!(MoleculeGraph::from_name("octadecanoic acid extra").is_err());
+        assert!(matches!(
+            MoleculeGraph::from_name("12-hydroxy-12-oxooctadecanoic acid"),
+            Err(Error::Valence { index: 12 })
+        ));
+        assert!(matches!(
+            MoleculeGraph::from_name("3,3-dimethyl-3-hydroxyoctadecanoic acid"),
+            Err(Error::Valence { index: 3 })
+        ));
+        // Locants past the chain, not truncated
+        assert!(matches!(
+            MoleculeGraph::from_name("octadec-265-enoic acid"),
+            Err(Error::Locant { .. })
+        ));
+        assert!(matches!(
+            MoleculeGraph::from_name("265-methyloctadecanoic acid"),
+            Err(Error::Locant { .. })
+        ));
+    }
+}
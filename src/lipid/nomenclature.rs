@@ -0,0 +1,476 @@
+use super::{
+    error::{Error, Result},
+    fatty_acid::carboxyl_oxygens,
+    glycerolipid::{backbone, Glycerolipid},
+};
+use crate::semantic::{Bond, Element, Geometry, MoleculeGraph, Notation};
+use petgraph::graph::NodeIndex;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+};
+
+impl MoleculeGraph {
+    /// IUPAC style name
+    ///
+    /// Covers straight chain and methyl branched acids with hydroxy, oxo and
+    /// epoxy substituents, their anions, alkyl esters and glyceryl esters.
+    /// Double bond geometries are named, configurations of stereocentres are
+    /// not.
+    pub fn systematic_name(&self) -> Result<String> {
+        let heavy = self
+            .node_indices()
+            .filter(|&index| self[index].element != Some(Element::H))
+            .count();
+        if Glycerolipid::try_from(self).is_ok() {
+            return glycerolipid(self, heavy);
+        }
+        let mut error = Error::CarboxylNotFound;
+        for carboxyl in self.node_indices() {
+            let Some((oxo, oxygen)) = carboxyl_oxygens(self, carboxyl) else {
+                continue;
+            };
+            match self.ester(carboxyl, oxo, oxygen, heavy) {
+                Ok(name) => return Ok(name),
+                Err(other) => error = other,
+            }
+        }
+        Err(error)
+    }
+
+    fn ester(
+        &self,
+        carboxyl: NodeIndex,
+        oxo: NodeIndex,
+        oxygen: NodeIndex,
+        heavy: usize,
+    ) -> Result<String> {
+        let (acid, mut atoms) = Parent::new(self, carboxyl, &[oxo, oxygen])?;
+        atoms.extend([oxo, oxygen]);
+        let alcohol = self
+            .neighbors(oxygen)
+            .find(|&index| index != carboxyl && self[index].element != Some(Element::H));
+        let name = match alcohol {
+            Some(start) => {
+                let (alkyl, alkyl_atoms) = Parent::new(self, start, &[oxygen])?;
+                atoms.extend(alkyl_atoms);
+                format!("{} {}", alkyl.name(Ending::Alkyl)?, acid.name(Ending::Ate)?)
+            }
+            None if self[oxygen].charge < 0 => acid.name(Ending::Ate)?,
+            None => acid.name(Ending::Acid)?,
+        };
+        unexpected(self, &atoms, heavy)?;
+        Ok(name)
+    }
+}
+
+/// Names the glyceryl ester after one of its acyls, the other positions
+/// being substituents of the propyl
+fn glycerolipid(graph: &MoleculeGraph, heavy: usize) -> Result<String> {
+    let backbone = graph
+        .node_indices()
+        .find_map(|index| backbone(graph, index))
+        .ok_or(Error::GlycerolNotFound)?;
+    let mut atoms: Vec<_> = backbone.iter().flat_map(|&(c, o)| [c, o]).collect();
+    let mut acyls = Vec::new();
+    for &(carbon, oxygen) in &backbone {
+        let carboxyl = graph
+            .neighbors(oxygen)
+            .find(|&neighbor| neighbor != carbon && graph[neighbor].element != Some(Element::H));
+        acyls.push(match carboxyl {
+            Some(carboxyl) => {
+                let (oxo, _) = carboxyl_oxygens(graph, carboxyl).ok_or(Error::CarboxylNotFound)?;
+                let (acyl, acyl_atoms) = Parent::new(graph, carboxyl, &[oxo, oxygen])?;
+                atoms.extend(acyl_atoms);
+                atoms.push(oxo);
+                Some(acyl)
+            }
+            None => None,
+        });
+    }
+    unexpected(graph, &atoms, heavy)?;
+    // Parent position with the locants of the others
+    let mut candidates = Vec::new();
+    for (parent, others, group) in [
+        (0, [(1, 2), (2, 3)], "propyl"),
+        (2, [(1, 2), (0, 3)], "propyl"),
+        (1, [(0, 1), (2, 3)], "propan-2-yl"),
+    ] {
+        let Some(acyl) = &acyls[parent] else {
+            continue;
+        };
+        if parent == 1 && !candidates.is_empty() {
+            break;
+        }
+        let mut prefixes = Prefixes::default();
+        for (position, locant) in others {
+            match &acyls[position] {
+                Some(other) => prefixes.add(&format!("{}oxy", other.name(Ending::Yl)?), locant),
+                None => prefixes.add("hydroxy", locant),
+            }
+        }
+        candidates.push(format!("{prefixes}{group} {}", acyl.name(Ending::Ate)?));
+    }
+    candidates.into_iter().min().ok_or(Error::GlycerolNotFound)
+}
+
+/// Errors on a heavy atom not taking part in the name
+fn unexpected(graph: &MoleculeGraph, atoms: &[NodeIndex], heavy: usize) -> Result<()> {
+    if atoms.len() == heavy {
+        return Ok(());
+    }
+    let index = graph
+        .node_indices()
+        .find(|index| graph[*index].element != Some(Element::H) && !atoms.contains(index))
+        .unwrap_or_default();
+    Err(Error::UnexpectedAtom {
+        index: index.index(),
+    })
+}
+
+/// Parent carbon chain with its unsaturations and substituents
+#[derive(Clone, Debug, Default)]
+struct Parent {
+    carbons: usize,
+    /// Double bond locants
+    enes: Vec<usize>,
+    /// Triple bond locants
+    ynes: Vec<usize>,
+    geometries: Vec<(usize, Geometry)>,
+    prefixes: Prefixes,
+}
+
+impl Parent {
+    /// Chain starting at the carbon, the excluded neighbours of the first
+    /// carbon left out
+    ///
+    /// Returns the parent and its atoms.
+    fn new(
+        graph: &MoleculeGraph,
+        start: NodeIndex,
+        excluded: &[NodeIndex],
+    ) -> Result<(Self, Vec<NodeIndex>)> {
+        let chain = graph.chain(start);
+        let mut parent = Self {
+            carbons: chain.len(),
+            ..Default::default()
+        };
+        let mut atoms = chain.to_vec();
+        for position in chain.positions(graph, Notation::Delta) {
+            if position.element != Element::C {
+                continue;
+            }
+            match position.bond {
+                Bond::Triple => parent.ynes.push(position.locant),
+                _ => parent.enes.push(position.locant),
+            }
+            if let Some(geometry) = position.geometry {
+                parent.geometries.push((position.locant, geometry));
+            }
+        }
+        for (index, &from) in chain.iter().enumerate() {
+            let locant = index + 1;
+            for to in graph.neighbors(from) {
+                if let Some(position) = chain.iter().position(|&atom| atom == to) {
+                    // Ring closure within the chain
+                    if position.abs_diff(index) > 1 {
+                        return Err(Error::UnexpectedAtom { index: to.index() });
+                    }
+                    continue;
+                }
+                if graph[to].element == Some(Element::H)
+                    || index == 0 && excluded.contains(&to)
+                    || atoms.contains(&to)
+                {
+                    continue;
+                }
+                let bond = graph[graph.find_edge(from, to).unwrap()];
+                let neighbors: Vec<_> = graph
+                    .neighbors(to)
+                    .filter(|&index| graph[index].element != Some(Element::H))
+                    .collect();
+                match (graph[to].element, bond.valence(), &neighbors[..]) {
+                    (None | Some(Element::C), 1, [_]) => {
+                        parent.prefixes.add("methyl", locant);
+                    }
+                    (Some(Element::O), 2, [_]) => parent.prefixes.add("oxo", locant),
+                    (Some(Element::O), 1, [_]) if graph.hydrogens(to) > 0 => {
+                        parent.prefixes.add("hydroxy", locant);
+                    }
+                    (Some(Element::O), 1, &[a, b])
+                        if chain
+                            .get(index + 1)
+                            .is_some_and(|&next| [a, b].contains(&next)) =>
+                    {
+                        parent
+                            .prefixes
+                            .add_locants("epoxy", format!("{locant},{}", locant + 1));
+                    }
+                    _ => {
+                        return Err(Error::UnexpectedAtom { index: to.index() });
+                    }
+                }
+                atoms.push(to);
+            }
+        }
+        Ok((parent, atoms))
+    }
+
+    fn name(&self, ending: Ending) -> Result<String> {
+        let stem = numeral(self.carbons).ok_or(Error::UnknownClass)?;
+        let mut name = String::new();
+        if !self.geometries.is_empty() {
+            let geometries: Vec<_> = self
+                .geometries
+                .iter()
+                .map(|(locant, geometry)| format!("{locant}{geometry}"))
+                .collect();
+            name += &format!("({})-", geometries.join(","));
+        }
+        name += &self.prefixes.to_string();
+        name += &stem;
+        let unsaturations: Vec<_> = [(&self.enes, "en"), (&self.ynes, "yn")]
+            .into_iter()
+            .filter(|(locants, _)| !locants.is_empty())
+            .collect();
+        if unsaturations.is_empty() {
+            name += match ending {
+                Ending::Alkyl => "yl",
+                _ => "an",
+            };
+        } else {
+            if unsaturations.iter().any(|(locants, _)| locants.len() > 1) {
+                name += "a";
+            }
+            for (locants, infix) in unsaturations {
+                name += &format!("-{}-{}{infix}", join(locants), multiplier(locants.len()));
+            }
+            if ending == Ending::Alkyl {
+                name += "-1-yl";
+            }
+        }
+        name += match ending {
+            Ending::Acid => "oic acid",
+            Ending::Ate => "oate",
+            Ending::Yl => "oyl",
+            Ending::Alkyl => "",
+        };
+        Ok(name)
+    }
+}
+
+/// Name ending of the parent chain
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Ending {
+    /// `-oic acid`
+    Acid,
+    /// `-oate`, anion or ester
+    Ate,
+    /// `-oyl`, acyl
+    Yl,
+    /// `-yl`, alkyl
+    Alkyl,
+}
+
+/// Substituent prefixes with their locants, in alphanumerical order
+#[derive(Clone, Debug, Default)]
+struct Prefixes(BTreeMap<(String, String), Vec<String>>);
+
+impl Prefixes {
+    fn add(&mut self, name: &str, locant: usize) {
+        self.add_locants(name, locant.to_string());
+    }
+
+    fn add_locants(&mut self, name: &str, locants: String) {
+        // Alphanumerical order ignores the geometry descriptors
+        let key = name.rsplit(")-").next().unwrap_or(name);
+        let key = key.chars().filter(char::is_ascii_alphabetic).collect();
+        self.0
+            .entry((key, name.to_owned()))
+            .or_default()
+            .push(locants);
+    }
+}
+
+impl Display for Prefixes {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut first = true;
+        for ((_, name), locants) in &self.0 {
+            if !first {
+                f.write_str("-")?;
+            }
+            first = false;
+            let mut locants = locants.clone();
+            locants.sort_by_key(|locant| {
+                locant
+                    .split(',')
+                    .map(|locant| locant.parse::<usize>().unwrap_or_default())
+                    .collect::<Vec<_>>()
+            });
+            let count = locants.len();
+            write!(f, "{}-", locants.join(","))?;
+            if SIMPLE.contains(&name.as_str()) {
+                write!(f, "{}{name}", multiplier(count))?;
+            } else {
+                let enclosed = if name.contains('(') {
+                    format!("[{name}]")
+                } else {
+                    format!("({name})")
+                };
+                write!(f, "{}{enclosed}", complex_multiplier(count))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Substituent prefixes multiplied without enclosing marks
+const SIMPLE: [&str; 4] = ["epoxy", "hydroxy", "methyl", "oxo"];
+
+/// Numerical stem of a chain of `n` carbons
+pub(super) fn numeral(n: usize) -> Option<String> {
+    const SIMPLE: [&str; 11] = [
+        "", "meth", "eth", "prop", "but", "pent", "hex", "hept", "oct", "non", "dec",
+    ];
+    const UNITS: [&str; 10] = [
+        "", "hen", "do", "tri", "tetra", "penta", "hexa", "hepta", "octa", "nona",
+    ];
+    const TENS: [&str; 10] = [
+        "",
+        "dec",
+        "cos",
+        "triacont",
+        "tetracont",
+        "pentacont",
+        "hexacont",
+        "heptacont",
+        "octacont",
+        "nonacont",
+    ];
+    Some(match n {
+        0 | 100.. => return None,
+        1..=10 => SIMPLE[n].to_owned(),
+        11 => "undec".to_owned(),
+        20 => "icos".to_owned(),
+        21 => "henicos".to_owned(),
+        _ => format!("{}{}", UNITS[n % 10], TENS[n / 10]),
+    })
+}
+
+/// Multiplying prefix of simple substituents and unsaturations
+fn multiplier(n: usize) -> String {
+    match n {
+        0 | 1 => String::new(),
+        2 => "di".to_owned(),
+        3 => "tri".to_owned(),
+        4 => "tetra".to_owned(),
+        _ => format!("{}a", numeral(n).unwrap_or_default()),
+    }
+}
+
+/// Multiplying prefix of compound substituents
+fn complex_multiplier(n: usize) -> String {
+    match n {
+        0 | 1 => String::new(),
+        2 => "bis".to_owned(),
+        3 => "tris".to_owned(),
+        _ => format!("{}kis", multiplier(n)),
+    }
+}
+
+fn join(locants: &[usize]) -> String {
+    let locants: Vec<_> = locants.iter().map(ToString::to_string).collect();
+    locants.join(",")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lipid::catalogue::Entry;
+
+    fn name(smiles: &str) -> String {
+        smiles
+            .parse::<MoleculeGraph>()
+            .unwrap()
+            .systematic_name()
+            .unwrap()
+    }
+
+    #[test]
+    fn catalogue() {
+        for entry in Entry::ALL {
+            let graph = entry.graph().unwrap();
+            assert_eq!(graph.systematic_name().unwrap(), entry.systematic);
+        }
+    }
+
+    #[test]
+    fn acids() {
+        assert_eq!(
+            name("CCCCC/C=C\\C/C=C\\CCCCCCCC(=O)O"),
+            "(9Z,12Z)-octadeca-9,12-dienoic acid"
+        );
+        assert_eq!(name("CCCCCCCCC#CCCCCCCCC(=O)O"), "octadec-9-ynoic acid");
+        assert_eq!(
+            name("CC(C)CCCCCCCCCCCCCC(=O)O"),
+            "15-methylhexadecanoic acid"
+        );
+        assert_eq!(
+            name("CCCCCC[C@@H](O)C/C=C\\CCCCCCCC(=O)O"),
+            "(9Z)-12-hydroxyoctadec-9-enoic acid"
+        );
+        assert_eq!(
+            name("CCCCCC1OC1C/C=C\\CCCCCCCC(=O)O"),
+            "(9Z)-12,13-epoxyoctadec-9-enoic acid"
+        );
+        assert_eq!(
+            name("CCCCCCCCCC(=O)CC(O)CCCCCC(=O)O"),
+            "7-hydroxy-9-oxooctadecanoic acid"
+        );
+        assert_eq!(name("CCCCCCCCCCCCCCCC(=O)[O-]"), "hexadecanoate");
+        // Directional bonds to the substituents
+        assert_eq!(name("C/C=C(/C)CCCC(=O)O"), "(5Z)-5-methylhept-5-enoic acid");
+        assert!("CCCCCCCCC1CC1CCCCCCCC(=O)O"
+            .parse::<MoleculeGraph>()
+            .unwrap()
+            .systematic_name()
+            .is_err());
+    }
+
+    #[test]
+    fn esters() {
+        assert_eq!(
+            name("CCCCCCCC/C=C\\CCCCCCCC(=O)OC"),
+            "methyl (9Z)-octadec-9-enoate"
+        );
+        assert_eq!(name("CCCCCCCCCCCCCCCC(=O)OCC"), "ethyl hexadecanoate");
+        assert_eq!(
+            name("CCCCCCCCCCCCCCCC(=O)OCCCCCCCC/C=C\\CCCCCCCC"),
+            "(9Z)-octadec-9-en-1-yl hexadecanoate"
+        );
+    }
+
+    #[test]
+    fn glycerolipids() {
+        assert_eq!(
+            name("CCCCCCCCCCCCCCCC(=O)OCC(OC(=O)CCCCCCCCCCCCCCC)COC(=O)CCCCCCCCCCCCCCC"),
+            "2,3-bis(hexadecanoyloxy)propyl hexadecanoate"
+        );
+        assert_eq!(
+            name("CCCCCCCCCCCCCCCC(=O)OC[C@@H](OC(=O)CCCCCCC/C=C\\CCCCCCCC)COC(=O)CCCCCCCCCCCCCCC"),
+            "3-(hexadecanoyloxy)-2-[(9Z)-octadec-9-enoyloxy]propyl hexadecanoate"
+        );
+        assert_eq!(
+            name("CCCCCCCCCCCCCCCC(=O)OCC(O)CO"),
+            "2,3-dihydroxypropyl hexadecanoate"
+        );
+        assert_eq!(
+            name("CCCCCCCCCCCCCCCC(=O)OC(CO)CO"),
+            "1,3-dihydroxypropan-2-yl hexadecanoate"
+        );
+        assert_eq!(
+            name("CCCCCCCCCCCCCCCC(=O)OCC(O)COC(=O)CCCCCCC/C=C\\CCCCCCCC"),
+            "2-hydroxy-3-[(9Z)-octadec-9-enoyloxy]propyl hexadecanoate"
+        );
+    }
+}
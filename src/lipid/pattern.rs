@@ -0,0 +1,301 @@
+use crate::semantic::{Bond, Element, MoleculeGraph};
+use petgraph::visit::{EdgeRef, IntoEdgeReferences};
+
+/// Structural pattern of a fatty acid chain
+///
+/// Locants are numbered from the first carbon of the principal chain. Ring
+/// and substituent locants refer to the lower numbered chain carbon.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Pattern {
+    /// Chain carbons
+    pub carbons: usize,
+    /// Double and triple bond systems, cumulated and ring bonds excluded
+    pub systems: Vec<System>,
+    /// Cumulated double bonds (`C=C=C`) by the locant of the first bond
+    pub allenes: Vec<usize>,
+    /// Triple bonds
+    pub acetylenes: Vec<usize>,
+    pub branches: Vec<Branch>,
+    pub cycles: Vec<Cycle>,
+    pub oxygenated: Vec<Oxygenated>,
+}
+
+/// Unsaturated bonds with the same spacing, each one sharing its
+/// neighbouring spacing with the next one
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct System {
+    pub spacing: Spacing,
+    /// Bond locants
+    pub locants: Vec<usize>,
+}
+
+/// Spacing of consecutive unsaturated bonds
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Spacing {
+    /// `C=C-C=C`
+    Conjugated,
+    /// `C=C-C-C=C`
+    MethyleneInterrupted,
+    /// Two or more methylenes in between
+    PolymethyleneInterrupted,
+}
+
+/// Methyl branch
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Branch {
+    /// On the penultimate carbon (`ω2`)
+    Iso,
+    /// On the antepenultimate carbon (`ω3`)
+    Anteiso,
+    /// Elsewhere on the chain
+    Methyl(usize),
+}
+
+/// Three membered carbocycle fused to the chain
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Cycle {
+    Cyclopropane(usize),
+    Cyclopropene(usize),
+}
+
+/// Oxygen substituent
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Oxygenated {
+    Hydroxy(usize),
+    /// Oxo on a chain carbon other than the carboxyl
+    Keto(usize),
+    /// Oxirane on two adjacent chain carbons
+    Epoxy(usize),
+}
+
+impl MoleculeGraph {
+    /// Structural pattern of the principal chain
+    ///
+    /// A chain running around a three membered ring is shortcut through the
+    /// ring bond, the apex being part of the ring. `None` without carbons.
+    pub fn pattern(&self) -> Option<Pattern> {
+        let mut chain: Vec<_> = self.principal_chain()?.to_vec();
+        let mut position = 1;
+        while position + 1 < chain.len() {
+            if self
+                .find_edge(chain[position - 1], chain[position + 1])
+                .is_some()
+            {
+                chain.remove(position);
+            } else {
+                position += 1;
+            }
+        }
+        let locant = |index| chain.iter().position(|&atom| atom == index).map(|p| p + 1);
+        let mut pattern = Pattern {
+            carbons: chain.len(),
+            ..Default::default()
+        };
+        // Ring bonds and apexes
+        let mut ring = Vec::new();
+        for pair in chain.windows(2) {
+            let Some(apex) = self
+                .neighbors(pair[0])
+                .find(|&index| !chain.contains(&index) && self.contains_edge(index, pair[1]))
+            else {
+                continue;
+            };
+            let first = locant(pair[0]).unwrap();
+            ring.push(first);
+            match self[apex].element {
+                Some(Element::O) => pattern.oxygenated.push(Oxygenated::Epoxy(first)),
+                None | Some(Element::C) => {
+                    let unsaturated = [(pair[0], pair[1]), (pair[0], apex), (pair[1], apex)]
+                        .into_iter()
+                        .any(|(a, b)| {
+                            self.find_edge(a, b)
+                                .is_some_and(|edge| self[edge] == Bond::Double)
+                        });
+                    pattern.cycles.push(if unsaturated {
+                        Cycle::Cyclopropene(first)
+                    } else {
+                        Cycle::Cyclopropane(first)
+                    });
+                }
+                _ => {}
+            }
+        }
+        // Unsaturated chain bonds
+        let mut bonds: Vec<_> = self
+            .unsaturated()
+            .edge_references()
+            .filter_map(|edge| {
+                let (a, b) = (locant(edge.source())?, locant(edge.target())?);
+                (a.abs_diff(b) == 1).then_some((a.min(b), *edge.weight()))
+            })
+            .filter(|(locant, _)| !ring.contains(locant))
+            .collect();
+        bonds.sort();
+        for pair in bonds.windows(2) {
+            if pair[0].0 + 1 == pair[1].0 && [pair[0].1, pair[1].1] == [Bond::Double; 2] {
+                pattern.allenes.push(pair[0].0);
+            }
+        }
+        let allenic = |locant: usize| {
+            pattern
+                .allenes
+                .iter()
+                .any(|&first| locant == first || locant == first + 1)
+        };
+        bonds.retain(|&(locant, _)| !allenic(locant));
+        pattern.acetylenes = bonds
+            .iter()
+            .filter(|(_, bond)| *bond == Bond::Triple)
+            .map(|&(locant, _)| locant)
+            .collect();
+        for pair in bonds.windows(2) {
+            let (first, second) = (pair[0].0, pair[1].0);
+            let spacing = match second - first {
+                2 => Spacing::Conjugated,
+                3 => Spacing::MethyleneInterrupted,
+                _ => Spacing::PolymethyleneInterrupted,
+            };
+            match pattern.systems.last_mut() {
+                Some(system)
+                    if system.spacing == spacing && system.locants.last() == Some(&first) =>
+                {
+                    system.locants.push(second);
+                }
+                _ => pattern.systems.push(System {
+                    spacing,
+                    locants: vec![first, second],
+                }),
+            }
+        }
+        // Substituents
+        for (position, &from) in chain.iter().enumerate() {
+            let locant = position + 1;
+            for edge in self.edges(from) {
+                let to = edge.target();
+                let heavy = self
+                    .neighbors(to)
+                    .filter(|&index| self[index].element != Some(Element::H))
+                    .count();
+                if chain.contains(&to) || heavy != 1 {
+                    continue;
+                }
+                match (self[to].element, *edge.weight()) {
+                    (None | Some(Element::C), bond) if bond.valence() == 1 => {
+                        pattern.branches.push(match chain.len() - locant {
+                            1 => Branch::Iso,
+                            2 => Branch::Anteiso,
+                            _ => Branch::Methyl(locant),
+                        });
+                    }
+                    (Some(Element::O), _) if self.is_carboxyl(from) => {}
+                    (Some(Element::O), Bond::Double) => {
+                        pattern.oxygenated.push(Oxygenated::Keto(locant));
+                    }
+                    (Some(Element::O), bond) if bond.valence() == 1 && self.hydrogens(to) > 0 => {
+                        pattern.oxygenated.push(Oxygenated::Hydroxy(locant));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        pattern.branches.sort();
+        pattern.cycles.sort();
+        pattern.oxygenated.sort();
+        Some(pattern)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pattern(smiles: &str) -> Pattern {
+        smiles.parse::<MoleculeGraph>().unwrap().pattern().unwrap()
+    }
+
+    #[test]
+    fn systems() {
+        // Linoleic acid
+        let linoleic = pattern("CCCCC/C=C\\C/C=C\\CCCCCCCC(=O)O");
+        assert_eq!(linoleic.carbons, 18);
+        assert_eq!(
+            linoleic.systems,
+            [System {
+                spacing: Spacing::MethyleneInterrupted,
+                locants: vec![9, 12],
+            }]
+        );
+        // Rumenic acid (9Z,11E)
+        let rumenic = pattern("CCCCCC/C=C/C=C\\CCCCCCCC(=O)O");
+        assert_eq!(
+            rumenic.systems,
+            [System {
+                spacing: Spacing::Conjugated,
+                locants: vec![9, 11],
+            }]
+        );
+        // Sciadonic acid (5Z,11Z,14Z)
+        let sciadonic = pattern("CCCCC/C=C\\C/C=C\\CCCC/C=C\\CCCC(=O)O");
+        assert_eq!(
+            sciadonic.systems,
+            [
+                System {
+                    spacing: Spacing::PolymethyleneInterrupted,
+                    locants: vec![5, 11],
+                },
+                System {
+                    spacing: Spacing::MethyleneInterrupted,
+                    locants: vec![11, 14],
+                },
+            ]
+        );
+        // Oleic acid, a single bond
+        assert!(pattern("CCCCCCCC/C=C\\CCCCCCCC(=O)O").systems.is_empty());
+        // Stearolic acid
+        let stearolic = pattern("CCCCCCCCC#CCCCCCCCC(=O)O");
+        assert_eq!(stearolic.acetylenes, [9]);
+        // Laballenic acid (5,6-octadecadienoic)
+        let laballenic = pattern("CCCCCCCCCCCCC=C=CCCCC(=O)O");
+        assert_eq!(laballenic.allenes, [5]);
+        assert!(laballenic.systems.is_empty());
+    }
+
+    #[test]
+    fn branches() {
+        assert_eq!(pattern("CC(C)CCCCCCCCCCCCCC(=O)O").branches, [Branch::Iso]);
+        assert_eq!(
+            pattern("CCC(C)CCCCCCCCCCCC(=O)O").branches,
+            [Branch::Anteiso]
+        );
+        let tuberculostearic = pattern("CCCCCCCCC(C)CCCCCCCCC(=O)O");
+        assert_eq!(tuberculostearic.carbons, 18);
+        assert_eq!(tuberculostearic.branches, [Branch::Methyl(10)]);
+    }
+
+    #[test]
+    fn cycles() {
+        // Dihydrosterculic acid
+        let dihydrosterculic = pattern("CCCCCCCCC1CC1CCCCCCCC(=O)O");
+        assert_eq!(dihydrosterculic.carbons, 18);
+        assert_eq!(dihydrosterculic.cycles, [Cycle::Cyclopropane(9)]);
+        assert!(dihydrosterculic.branches.is_empty());
+        // Sterculic acid
+        let sterculic = pattern("CCCCCCCCC1=C(CCCCCCCC(=O)O)C1");
+        assert_eq!(sterculic.carbons, 18);
+        assert_eq!(sterculic.cycles, [Cycle::Cyclopropene(9)]);
+        assert!(sterculic.systems.is_empty());
+    }
+
+    #[test]
+    fn oxygenated() {
+        // Ricinoleic acid
+        let ricinoleic = pattern("CCCCCC[C@@H](O)C/C=C\\CCCCCCCC(=O)O");
+        assert_eq!(ricinoleic.oxygenated, [Oxygenated::Hydroxy(12)]);
+        // Vernolic acid
+        let vernolic = pattern("CCCCCC1OC1C/C=C\\CCCCCCCC(=O)O");
+        assert_eq!(vernolic.oxygenated, [Oxygenated::Epoxy(12)]);
+        assert!(vernolic.cycles.is_empty());
+        let keto = pattern("CCCCCCCCCC(=O)CCCCCCCC(=O)O");
+        assert_eq!(keto.oxygenated, [Oxygenated::Keto(9)]);
+    }
+}
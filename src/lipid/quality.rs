@@ -0,0 +1,243 @@
+use super::{
+    class::Lipid,
+    error::{Error, Result},
+    fatty_acid::FattyAcid,
+    glycerolipid::Glycerolipid,
+};
+use crate::semantic::{Bond, Element, MoleculeGraph};
+use petgraph::visit::IntoEdgeReferences;
+use std::borrow::Cow;
+
+/// Structure given as a graph or a lipid
+pub trait Structure {
+    fn graph(&self) -> Result<Cow<'_, MoleculeGraph>>;
+}
+
+impl Structure for MoleculeGraph {
+    fn graph(&self) -> Result<Cow<'_, MoleculeGraph>> {
+        Ok(Cow::Borrowed(self))
+    }
+}
+
+impl Structure for FattyAcid {
+    fn graph(&self) -> Result<Cow<'_, MoleculeGraph>> {
+        Ok(Cow::Owned(MoleculeGraph::try_from(self)?))
+    }
+}
+
+impl Structure for Glycerolipid {
+    fn graph(&self) -> Result<Cow<'_, MoleculeGraph>> {
+        Ok(Cow::Owned(MoleculeGraph::try_from(self)?))
+    }
+}
+
+impl<T: Structure + ?Sized> Structure for &T {
+    fn graph(&self) -> Result<Cow<'_, MoleculeGraph>> {
+        (**self).graph()
+    }
+}
+
+/// Quality indices of a fat, oil or biodiesel
+///
+/// Computed from the mass fractions of single chain lipids (free acid, FAME,
+/// FAEE), fatty acids or acylglycerols, the iodine and
+/// saponification values with the molar mass of each structure as given
+/// (the FAME for a biodiesel, the triacylglycerol for an oil).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Quality {
+    /// Iodine value (g I₂/100 g)
+    pub iodine_value: f64,
+    /// Saponification value (mg KOH/g)
+    pub saponification_value: f64,
+    /// Theoretical methyl ester content (g FAME/100 g of the
+    /// triacylglycerol oil)
+    pub ester_content: f64,
+    /// Cetane number, `46.3 + 5458/SV - 0.225·IV` (Krisnangkura)
+    pub cetane_number: f64,
+    /// Cold filter plugging point (°C), `3.1417·LCSF - 16.477` (Ramos et
+    /// al.)
+    pub cold_filter_plugging_point: f64,
+}
+
+impl Quality {
+    pub fn new<T: Structure>(composition: impl IntoIterator<Item = (T, f64)>) -> Result<Self> {
+        let components = composition
+            .into_iter()
+            .map(|(structure, fraction)| Component::new(&*structure.graph()?, fraction))
+            .collect::<Result<Vec<_>>>()?;
+        if components
+            .iter()
+            .any(|component| !component.fraction.is_finite() || component.fraction < 0.0)
+        {
+            return Err(Error::Composition("negative or non-finite amount"));
+        }
+        let sum: f64 = components.iter().map(|component| component.fraction).sum();
+        if sum <= 0.0 {
+            return Err(Error::Composition("empty"));
+        }
+        let iodine = 2.0 * mass(Element::I);
+        let potassium_hydroxide = mass(Element::K) + mass(Element::O) + mass(Element::H);
+        // Mass change of the acid to the methyl ester and to the acyl of a
+        // triacylglycerol, per fatty acid
+        let methyl = molar_mass("CO") - molar_mass("O");
+        let glyceryl = (molar_mass("OCC(O)CO") - 3.0 * molar_mass("O")) / 3.0;
+        let mut quality = Self::default();
+        let (mut esters, mut oil, mut long_chain) = (0.0, 0.0, 0.0);
+        for component in &components {
+            let fraction = component.fraction / sum;
+            let moles = fraction / component.molar_mass;
+            let count = component.chains.len() as f64;
+            let acids: f64 = component.chains.iter().map(|(_, mass)| mass).sum();
+            quality.iodine_value += 100.0 * moles * component.double_bonds as f64 * iodine;
+            quality.saponification_value += 1000.0 * moles * count * potassium_hydroxide;
+            esters += moles * (acids + count * methyl);
+            oil += moles
+                * if component.glycerolipid {
+                    component.molar_mass
+                } else {
+                    acids + glyceryl
+                };
+            for (chain, acid_mass) in &component.chains {
+                if !chain.unsaturations.is_empty() {
+                    continue;
+                }
+                // Share of the chain by the mass of its acid
+                long_chain += 100.0 * fraction * acid_mass / acids
+                    * match chain.carbons {
+                        16 => 0.1,
+                        18 => 0.5,
+                        20 => 1.0,
+                        22 => 1.5,
+                        24 => 2.0,
+                        _ => 0.0,
+                    };
+            }
+        }
+        quality.ester_content = 100.0 * esters / oil;
+        quality.cetane_number =
+            46.3 + 5458.0 / quality.saponification_value - 0.225 * quality.iodine_value;
+        quality.cold_filter_plugging_point = 3.1417 * long_chain - 16.477;
+        Ok(quality)
+    }
+}
+
+/// Composition component
+struct Component {
+    fraction: f64,
+    molar_mass: f64,
+    /// Carbon-carbon double bonds, a triple bond counted twice
+    double_bonds: usize,
+    /// Chains with the molar masses of their free fatty acids
+    chains: Vec<(FattyAcid, f64)>,
+    /// Is the component an acylglycerol of the oil itself
+    glycerolipid: bool,
+}
+
+impl Component {
+    fn new(graph: &MoleculeGraph, fraction: f64) -> Result<Self> {
+        let lipid = Lipid::try_from(graph)?;
+        let glycerolipid = matches!(lipid, Lipid::Glycerolipid(_));
+        let chains = lipid.chains();
+        if !glycerolipid && chains.len() != 1 {
+            return Err(Error::Composition(
+                "single chain structures or acylglycerols only",
+            ));
+        }
+        let molar_mass = graph
+            .molar_mass()
+            .ok_or(Error::Composition("unknown molar mass"))?;
+        let chains = chains
+            .into_iter()
+            .map(|chain| {
+                let acid_mass = MoleculeGraph::try_from(chain)?
+                    .molar_mass()
+                    .ok_or(Error::Composition("unknown molar mass"))?;
+                Ok((chain.clone(), acid_mass))
+            })
+            .collect::<Result<_>>()?;
+        let double_bonds = graph
+            .unsaturated()
+            .edge_references()
+            .map(|edge| match edge.weight() {
+                Bond::Triple => 2,
+                _ => 1,
+            })
+            .sum();
+        Ok(Self {
+            fraction,
+            molar_mass,
+            double_bonds,
+            chains,
+            glycerolipid,
+        })
+    }
+}
+
+fn mass(element: Element) -> f64 {
+    element.average_mass().unwrap_or_default()
+}
+
+fn molar_mass(smiles: &str) -> f64 {
+    smiles
+        .parse::<MoleculeGraph>()
+        .ok()
+        .and_then(|graph| graph.molar_mass())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn methyl_oleate() {
+        let graph: MoleculeGraph = "CCCCCCCC/C=C\\CCCCCCCC(=O)OC".parse().unwrap();
+        let quality = Quality::new([(&graph, 1.0)]).unwrap();
+        // 296.495 g/mol
+        assert!((quality.iodine_value - 85.60).abs() < 0.01);
+        assert!((quality.saponification_value - 189.23).abs() < 0.01);
+        assert!((quality.cetane_number - 55.9).abs() < 0.1);
+        assert!((quality.cold_filter_plugging_point + 16.477).abs() < 1e-9);
+        assert!((quality.ester_content - 100.45).abs() < 0.01);
+    }
+
+    #[test]
+    fn triolein() {
+        let triolein: Glycerolipid = "TG(18:1(9Z)/18:1(9Z)/18:1(9Z))".parse().unwrap();
+        let quality = Quality::new([(&triolein, 1.0)]).unwrap();
+        // 885.45 g/mol, three esters
+        assert!((quality.iodine_value - 86.0).abs() < 0.1);
+        assert!((quality.saponification_value - 190.1).abs() < 0.1);
+        assert!((quality.ester_content - 100.45).abs() < 0.01);
+        // Palmitic acids by their share of the acid mass of POP
+        let pop: Glycerolipid = "TG(16:0/18:1(9Z)/16:0)".parse().unwrap();
+        let quality = Quality::new([(&pop, 1.0)]).unwrap();
+        let long_chain = 100.0 * 0.1 * 2.0 * 256.43 / (2.0 * 256.43 + 282.47);
+        assert!((quality.cold_filter_plugging_point - (3.1417 * long_chain - 16.477)).abs() < 0.01);
+    }
+
+    #[test]
+    fn fatty_acids() {
+        let composition: Vec<(FattyAcid, f64)> = vec![
+            ("16:0".parse().unwrap(), 10.0),
+            ("18:0".parse().unwrap(), 5.0),
+            ("18:1(9Z)".parse().unwrap(), 25.0),
+            ("18:2(9Z,12Z)".parse().unwrap(), 60.0),
+        ];
+        let quality = Quality::new(composition.clone()).unwrap();
+        // Free acids: 256.43, 284.48, 282.47, 280.45 g/mol
+        let expected = 100.0 * 253.809 * (0.25 / 282.466 + 2.0 * 0.6 / 280.450);
+        assert!((quality.iodine_value - expected).abs() < 0.01);
+        let long_chain = 0.1 * 10.0 + 0.5 * 5.0;
+        assert!((quality.cold_filter_plugging_point - (3.1417 * long_chain - 16.477)).abs() < 1e-9);
+        // The same by the amounts
+        let scaled: Vec<_> = composition
+            .iter()
+            .map(|(fatty_acid, fraction)| (fatty_acid, fraction / 100.0))
+            .collect();
+        assert_eq!(Quality::new(scaled).unwrap(), quality);
+        let wax: MoleculeGraph = "CCCCCCCCCCCCCCCC(=O)OCCCCCCCCCCCCCCCC".parse().unwrap();
+        assert!(Quality::new([(wax, 1.0)]).is_err());
+        assert!(Quality::new(Vec::<(FattyAcid, f64)>::new()).is_err());
+    }
+}
@@ -0,0 +1,223 @@
+use super::{
+    error::{Error, Result},
+    fatty_acid::FattyAcid,
+    glycerolipid::Glycerolipid,
+    hydrolysis::Balance,
+    quality::Structure,
+};
+use crate::{
+    errors,
+    semantic::{Bond, Element, MoleculeGraph, Notation},
+};
+use petgraph::graph::NodeIndex;
+use std::{collections::BTreeMap, str::FromStr};
+
+/// Transesterification of acylglycerols with an alcohol
+///
+/// `TG + 3 ROH → 3 RCOOR' + glycerol`, run to completion. The esters are
+/// built from the recognised acyls and the alcohol graph, the double bond
+/// geometries of the acyls and the configurations of the alcohol are kept.
+#[derive(Clone, Debug)]
+pub struct Transesterification {
+    alcohol: MoleculeGraph,
+    hydroxyl: NodeIndex,
+}
+
+impl Transesterification {
+    /// Alcohol with a single hydroxyl group
+    pub fn new(alcohol: MoleculeGraph) -> Result<Self> {
+        let mut hydroxyls = alcohol.node_indices().filter(|&index| {
+            let atom = &alcohol[index];
+            let mut neighbors = alcohol.neighbors(index);
+            atom.element == Some(Element::O)
+                && atom.charge == 0
+                && alcohol.hydrogens(index) == 1
+                && neighbors
+                    .next()
+                    .is_some_and(|neighbor| alcohol[neighbor].element == Some(Element::C))
+                && neighbors.next().is_none()
+        });
+        let (Some(hydroxyl), None) = (hydroxyls.next(), hydroxyls.next()) else {
+            return Err(Error::HydroxylNotFound);
+        };
+        Ok(Self { alcohol, hydroxyl })
+    }
+
+    /// Products of the acylglycerols (amounts in moles)
+    pub fn products<T: Structure>(
+        &self,
+        species: impl IntoIterator<Item = (T, f64)>,
+    ) -> Result<Transesterified> {
+        let mut products = Transesterified {
+            alcohol: self.alcohol.clone(),
+            ..Default::default()
+        };
+        let mut esters = BTreeMap::<_, (MoleculeGraph, f64)>::new();
+        for (structure, amount) in species {
+            if !amount.is_finite() || amount < 0.0 {
+                return Err(Error::Composition("negative or non-finite amount"));
+            }
+            let graph = structure.graph()?.into_owned();
+            let glycerolipid = Glycerolipid::try_from(&graph)?;
+            for fatty_acid in glycerolipid.fatty_acids() {
+                let ester = self.ester(fatty_acid)?.canonical();
+                esters.entry(ester.to_string()).or_insert((ester, 0.0)).1 += amount;
+                products.consumed += amount;
+            }
+            products.glycerol += amount;
+            products.input.push((graph, amount));
+        }
+        products.esters = esters.into_values().collect();
+        products.esters.sort_by(|a, b| b.1.total_cmp(&a.1));
+        Ok(products)
+    }
+
+    /// Ester of the fatty acid with the alcohol
+    fn ester(&self, fatty_acid: &FattyAcid) -> Result<MoleculeGraph> {
+        let mut ester = MoleculeGraph::default();
+        let carboxyl = fatty_acid.attach(&mut ester, Notation::Delta)?;
+        let alcohol: Vec<_> = self.alcohol.node_indices().collect();
+        let alcohol = copy(&mut ester, &self.alcohol, &alcohol);
+        let hydroxyl = alcohol[&self.hydroxyl];
+        if let Some(hydrogens) = &mut ester[hydroxyl].hydrogens {
+            *hydrogens -= 1;
+        }
+        ester.add_edge(carboxyl, hydroxyl, Bond::Single);
+        Ok(ester)
+    }
+}
+
+impl FromStr for Transesterification {
+    type Err = errors::Error;
+
+    /// Alcohol SMILES
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::new(s.parse()?)?)
+    }
+}
+
+/// Products of the transesterification
+#[derive(Clone, Debug, Default)]
+pub struct Transesterified {
+    /// Transesterified acylglycerols
+    pub input: Vec<(MoleculeGraph, f64)>,
+    pub alcohol: MoleculeGraph,
+    /// Consumed alcohol
+    pub consumed: f64,
+    /// Esters (canonical graphs), most abundant first
+    pub esters: Vec<(MoleculeGraph, f64)>,
+    pub glycerol: f64,
+}
+
+impl Transesterified {
+    /// Mass balance (g for amounts in moles)
+    pub fn balance(&self) -> Result<Balance> {
+        let molar_mass = |graph: &MoleculeGraph| {
+            graph
+                .molar_mass()
+                .ok_or(Error::Composition("unknown molar mass"))
+        };
+        let mut reactants = self.consumed * molar_mass(&self.alcohol)?;
+        for (graph, amount) in &self.input {
+            reactants += amount * molar_mass(graph)?;
+        }
+        let glycerol: MoleculeGraph = GLYCEROL.parse().unwrap_or_default();
+        let mut products = self.glycerol * molar_mass(&glycerol)?;
+        for (graph, amount) in &self.esters {
+            products += amount * molar_mass(graph)?;
+        }
+        Ok(Balance {
+            reactants,
+            products,
+        })
+    }
+}
+
+const GLYCEROL: &str = "OCC(O)CO";
+
+/// Copies the atoms and the bonds between them in index order
+///
+/// The order keeps the parities and the bond directions valid. Returns the
+/// new index of each copied atom.
+fn copy(
+    into: &mut MoleculeGraph,
+    from: &MoleculeGraph,
+    atoms: &[NodeIndex],
+) -> BTreeMap<NodeIndex, NodeIndex> {
+    let mut atoms = atoms.to_vec();
+    atoms.sort();
+    let map: BTreeMap<_, _> = atoms
+        .iter()
+        .map(|&index| (index, into.add_node(from[index])))
+        .collect();
+    for edge in from.edge_indices() {
+        let (source, target) = from.edge_endpoints(edge).unwrap();
+        if let (Some(&source), Some(&target)) = (map.get(&source), map.get(&target)) {
+            into.add_edge(source, target, from[edge]);
+        }
+    }
+    map
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lipid::class::Lipid;
+
+    #[test]
+    fn methanolysis() {
+        let methanol: Transesterification = "CO".parse().unwrap();
+        let popo: Glycerolipid = "TG(16:0/18:1(9Z)/16:0)".parse().unwrap();
+        let products = methanol.products([(popo, 2.0)]).unwrap();
+        let esters: Vec<_> = products
+            .esters
+            .iter()
+            .map(|(graph, amount)| (Lipid::try_from(graph).unwrap().to_string(), *amount))
+            .collect();
+        assert_eq!(
+            esters,
+            [
+                ("FAME 16:0".to_owned(), 4.0),
+                ("FAME 18:1(9Z)".to_owned(), 2.0)
+            ]
+        );
+        assert_eq!(products.consumed, 6.0);
+        assert_eq!(products.glycerol, 2.0);
+        assert!(products.balance().unwrap().error().abs() < 1e-12);
+    }
+
+    #[test]
+    fn ethanolysis() {
+        let ethanol: Transesterification = "OCC".parse().unwrap();
+        let graph: MoleculeGraph =
+            "CCCCCCCC/C=C\\CCCCCCCC(=O)OC[C@@H](OC(=O)CCCCCCC/C=C/CCCCCCCC)CO"
+                .parse()
+                .unwrap();
+        let products = ethanol.products([(&graph, 1.0)]).unwrap();
+        let mut smiles: Vec<_> = products
+            .esters
+            .iter()
+            .map(|(graph, _)| graph.canonical_smiles())
+            .collect();
+        smiles.sort();
+        let mut expected: Vec<_> = [
+            "CCCCCCCC/C=C\\CCCCCCCC(=O)OCC",
+            "CCCCCCCC/C=C/CCCCCCCC(=O)OCC",
+        ]
+        .map(|smiles| smiles.parse::<MoleculeGraph>().unwrap().canonical_smiles())
+        .to_vec();
+        expected.sort();
+        assert_eq!(smiles, expected);
+        assert_eq!(products.consumed, 2.0);
+        assert!(products.balance().unwrap().error().abs() < 1e-12);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!("CC".parse::<Transesterification>().is_err());
+        assert!("OCCO".parse::<Transesterification>().is_err());
+        let methanol: Transesterification = "CO".parse().unwrap();
+        let graph: MoleculeGraph = "CCCC(=O)OC".parse().unwrap();
+        assert!(methanol.products([(graph, 1.0)]).is_err());
+    }
+}
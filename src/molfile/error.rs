@@ -0,0 +1,22 @@
+use thiserror::Error;
+
+/// Molfile error
+#[derive(Clone, Error, Debug)]
+pub enum Error {
+    #[error("unexpected end of file")]
+    UnexpectedEnd,
+    #[error("unsupported version {0:?}")]
+    Version(String),
+    #[error("invalid number {0:?}")]
+    Number(String),
+    #[error("unknown atom symbol {0:?}")]
+    Element(String),
+    #[error("atom {0} out of range")]
+    Atom(usize),
+    #[error("unsupported bond type {0}")]
+    Bond(u8),
+    #[error("invalid charge code {0}")]
+    Charge(u8),
+    #[error("expected {0:?}")]
+    Expected(&'static str),
+}
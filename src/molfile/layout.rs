@@ -0,0 +1,118 @@
+use crate::semantic::{Bond, Geometry, MoleculeGraph};
+use petgraph::graph::NodeIndex;
+use std::f64::consts::PI;
+
+/// Bond length of the generated coordinates
+const LENGTH: f64 = 1.5;
+
+/// Copy of the graph with generated planar coordinates
+///
+/// Each component is drawn as a depth-first tree, chains zig-zagging and
+/// branches spread around their atom, the components side by side. The
+/// substituents of a double bond are put on the sides of its stored
+/// geometry. Ring closures are drawn as long bonds.
+pub(super) fn layout(graph: &MoleculeGraph) -> MoleculeGraph {
+    let mut graph = graph.clone();
+    for atom in graph.node_weights_mut() {
+        atom.coordinates = None;
+    }
+    let mut placed = vec![false; graph.node_count()];
+    let mut offset = 0.0;
+    for root in graph.node_indices() {
+        if placed[root.index()] {
+            continue;
+        }
+        placed[root.index()] = true;
+        graph[root].coordinates = Some([0.0; 3]);
+        let mut component = vec![root];
+        // Atom, its parent, the direction from the parent and the turn
+        let mut stack = vec![(root, None, 0.0, 1.0)];
+        while let Some((atom, parent, direction, turn)) = stack.pop() {
+            let mut children: Vec<_> = graph
+                .neighbors(atom)
+                .filter(|child| !placed[child.index()])
+                .collect();
+            children.sort();
+            children.dedup();
+            if children.is_empty() {
+                continue;
+            }
+            let mut turn = turn;
+            if let Some(parent) = parent {
+                if !fits(&graph, parent, atom, &children, direction, turn) {
+                    turn = -turn;
+                }
+            }
+            let angles = angles(children.len(), direction, turn);
+            let children: Vec<_> = children.into_iter().zip(angles).collect();
+            for &(child, angle) in &children {
+                placed[child.index()] = true;
+                graph[child].coordinates = Some(step(&graph, atom, angle));
+                component.push(child);
+            }
+            for &(child, angle) in children.iter().rev() {
+                stack.push((child, Some(atom), angle, -turn));
+            }
+        }
+        // Shift the component right of the previous ones
+        let x = |index: &NodeIndex| graph[*index].coordinates.unwrap_or_default()[0];
+        let minimum = component.iter().map(x).fold(f64::INFINITY, f64::min);
+        let maximum = component.iter().map(x).fold(f64::NEG_INFINITY, f64::max);
+        for &index in &component {
+            if let Some(coordinates) = &mut graph[index].coordinates {
+                coordinates[0] += offset - minimum;
+            }
+        }
+        offset += maximum - minimum + 2.0 * LENGTH;
+    }
+    graph
+}
+
+/// Directions of the children of an atom entered in the given direction
+///
+/// Up to two children are drawn at 120°, more spread evenly, the turn
+/// mirroring them.
+fn angles(children: usize, direction: f64, turn: f64) -> impl Iterator<Item = f64> {
+    let count = (children + 1).max(3) as f64;
+    (1..=children).map(move |j| direction + PI + turn * 2.0 * PI * j as f64 / count)
+}
+
+/// Position a bond length from the atom in the given direction
+fn step(graph: &MoleculeGraph, atom: NodeIndex, angle: f64) -> [f64; 3] {
+    let [x, y, _] = graph[atom].coordinates.unwrap_or_default();
+    [x + LENGTH * angle.cos(), y + LENGTH * angle.sin(), 0.0]
+}
+
+/// Do the children drawn with the turn keep the geometry of the
+/// `parent=atom` double bond
+fn fits(
+    graph: &MoleculeGraph,
+    parent: NodeIndex,
+    atom: NodeIndex,
+    children: &[NodeIndex],
+    direction: f64,
+    turn: f64,
+) -> bool {
+    let Some(edge) = graph.find_edge(parent, atom) else {
+        return true;
+    };
+    if graph[edge] != Bond::Double {
+        return true;
+    }
+    let [ax, ay, _] = graph[parent].coordinates.unwrap_or_default();
+    let [bx, by, _] = graph[atom].coordinates.unwrap_or_default();
+    let side = |[x, y, _]: [f64; 3]| (bx - ax) * (y - ay) - (by - ay) * (x - ax);
+    for (&y, angle) in children.iter().zip(angles(children.len(), direction, turn)) {
+        for x in graph.neighbors(parent).filter(|&x| x != atom) {
+            let Some(position) = graph[x].coordinates else {
+                continue;
+            };
+            let Some(geometry) = graph.geometry(x, parent, atom, y) else {
+                continue;
+            };
+            let z = side(position) * side(step(graph, atom, angle)) > 0.0;
+            return z == (geometry == Geometry::Z);
+        }
+    }
+    true
+}
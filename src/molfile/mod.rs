@@ -0,0 +1,507 @@
+//! MDL molfile
+//!
+//! [CTfile formats](https://discover.3ds.com/ctfile-documentation-request-form)
+
+pub use self::{
+    error::Error,
+    sdf::{Properties, SdfReader, SdfWriter},
+};
+
+use crate::{
+    errors,
+    semantic::{AtomList, Bond, Element, Geometry, MoleculeGraph, Parity, StereoGroup},
+};
+use petgraph::{
+    algo::has_path_connecting,
+    graph::{EdgeIndex, NodeIndex},
+    visit::{EdgeFiltered, EdgeRef},
+};
+use std::fmt::{self, Display, Formatter};
+
+impl MoleculeGraph {
+    /// Reads a molfile
+    ///
+    /// Stereo is perceived from the coordinates and wedge bonds, from the
+    /// atom parities when the atoms have no coordinates.
+    pub fn from_molfile(s: &str) -> errors::Result<Self> {
+        let mut lines = Lines::new(s);
+        for _ in 0..3 {
+            lines.next()?;
+        }
+        let counts = lines.next()?;
+        match column(counts, 33..39) {
+            "V2000" | "" => v2000::read(&mut lines, counts),
+            "V3000" => v3000::read(&mut lines),
+            version => Err(lines.error(Error::Version(version.to_owned()))),
+        }
+    }
+
+    /// Molfile, V3000 when the graph exceeds the V2000 limits
+    ///
+    /// V2000 holds up to 999 atoms and bonds, atom lists of up to 16
+    /// elements and no relative stereo groups.
+    pub fn molfile(&self) -> impl Display + '_ {
+        let exceeded = self.node_count() > v2000::LIMIT
+            || self.edge_count() > v2000::LIMIT
+            || self.node_weights().any(|atom| {
+                atom.stereo_group
+                    .is_some_and(|group| group != StereoGroup::Absolute)
+                    || atom.list.is_some_and(|list| list.len() > v2000::LIST)
+            });
+        let version = if exceeded {
+            Version::V3000
+        } else {
+            Version::V2000
+        };
+        self.molfile_version(version)
+    }
+
+    /// Molfile of the given version
+    pub fn molfile_version(&self, version: Version) -> impl Display + '_ {
+        Molfile {
+            graph: self,
+            version,
+        }
+    }
+}
+
+/// Molfile version
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Version {
+    #[default]
+    V2000,
+    V3000,
+}
+
+/// Molfile writer
+struct Molfile<'a> {
+    graph: &'a MoleculeGraph,
+    version: Version,
+}
+
+impl Display for Molfile<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        // Without coordinates the double bond geometries would be lost
+        let laid_out;
+        let graph = if has_coordinates(self.graph) {
+            self.graph
+        } else {
+            laid_out = layout::layout(self.graph);
+            &laid_out
+        };
+        let dimension = if graph.node_weights().any(|atom| {
+            atom.coordinates
+                .is_some_and(|coordinates| coordinates[2] != 0.0)
+        }) {
+            "3D"
+        } else {
+            "2D"
+        };
+        writeln!(f)?;
+        writeln!(f, "  smiles            {dimension}")?;
+        writeln!(f)?;
+        match self.version {
+            Version::V2000 => v2000::write(f, graph)?,
+            Version::V3000 => v3000::write(f, graph)?,
+        }
+        f.write_str("M  END\n")
+    }
+}
+
+/// Lines with their numbers
+struct Lines<'a> {
+    lines: std::str::Lines<'a>,
+    number: usize,
+}
+
+impl<'a> Lines<'a> {
+    fn new(s: &'a str) -> Self {
+        Self {
+            lines: s.lines(),
+            number: 0,
+        }
+    }
+
+    fn next(&mut self) -> errors::Result<&'a str> {
+        self.number += 1;
+        self.lines
+            .next()
+            .ok_or_else(|| self.error(Error::UnexpectedEnd))
+    }
+
+    /// Error at the current line
+    fn error(&self, source: Error) -> errors::Error {
+        errors::Error::Molfile {
+            line: self.number,
+            source,
+        }
+    }
+}
+
+/// Fixed width column, empty past the end of the line
+fn column(line: &str, range: std::ops::Range<usize>) -> &str {
+    let end = range.end.min(line.len());
+    line.get(range.start.min(end)..end)
+        .unwrap_or_default()
+        .trim()
+}
+
+/// Number in a fixed width column, the default when blank
+fn number<T: std::str::FromStr + Default>(
+    line: &str,
+    range: std::ops::Range<usize>,
+) -> Result<T, Error> {
+    match column(line, range) {
+        "" => Ok(T::default()),
+        text => text.parse().map_err(|_| Error::Number(text.to_owned())),
+    }
+}
+
+/// Atom by its element symbol, query and pseudo atoms without element
+fn element(symbol: &str) -> Result<Option<Element>, Error> {
+    match symbol {
+        "*" | "A" | "Q" | "R" | "R#" | "L" => Ok(None),
+        symbol => symbol
+            .parse()
+            .map(Some)
+            .map_err(|_| Error::Element(symbol.to_owned())),
+    }
+}
+
+/// Hydrogens implied by the molfile valence model
+///
+/// The normal valences are raised by a positive charge on atoms right of
+/// carbon and lowered by any charge on carbon and by a positive charge on
+/// boron.
+fn implicit_hydrogens(graph: &MoleculeGraph, index: NodeIndex) -> u8 {
+    let atom = &graph[index];
+    let Some(element) = atom.element else {
+        return 0;
+    };
+    let bonds: i16 = graph
+        .edges(index)
+        .map(|edge| edge.weight().valence() as i16)
+        .sum();
+    let charge = atom.charge as i16;
+    let shift = match element {
+        Element::B => -charge,
+        Element::C => -charge.abs(),
+        _ => charge,
+    };
+    element
+        .valences()
+        .iter()
+        .map(|&valence| valence as i16 + shift)
+        .find(|&valence| valence >= bonds)
+        .map_or(0, |valence| (valence - bonds) as u8)
+}
+
+/// Hydrogens of an atom read with the valence field (`0` for none)
+fn hydrogens(graph: &MoleculeGraph, index: NodeIndex, valence: u8) -> Option<u8> {
+    let atom = &graph[index];
+    let bonds: u8 = graph.edges(index).map(|edge| edge.weight().valence()).sum();
+    let hydrogens = match valence {
+        0 => implicit_hydrogens(graph, index),
+        15 => 0,
+        valence => valence.saturating_sub(bonds),
+    };
+    let organic = atom.element.is_some_and(|element| element.is_organic());
+    (!organic || atom.charge != 0 || hydrogens != graph.implicit_hydrogens(index))
+        .then_some(hydrogens)
+}
+
+/// Sets the hydrogens from the valence fields and perceives the stereo
+fn finish(graph: &mut MoleculeGraph, valences: Vec<u8>, stereo: Stereo) {
+    for (index, valence) in valences.into_iter().enumerate() {
+        let index = NodeIndex::new(index);
+        graph[index].hydrogens = hydrogens(graph, index, valence);
+    }
+    stereo.perceive(graph);
+}
+
+/// Atom list of element symbols
+fn list<'a>(negated: bool, symbols: impl IntoIterator<Item = &'a str>) -> Result<AtomList, Error> {
+    let elements = symbols
+        .into_iter()
+        .map(|symbol| {
+            symbol
+                .parse()
+                .map_err(|_| Error::Element(symbol.to_owned()))
+        })
+        .collect::<Result<Vec<Element>, _>>()?;
+    Ok(AtomList::new(negated, elements))
+}
+
+/// Valence field of an atom, `0` when implied
+fn valence(graph: &MoleculeGraph, index: NodeIndex) -> u8 {
+    let hydrogens = graph.hydrogens(index);
+    if hydrogens == implicit_hydrogens(graph, index) {
+        return 0;
+    }
+    let bonds: u8 = graph.edges(index).map(|edge| edge.weight().valence()).sum();
+    match bonds + hydrogens {
+        0 => 15,
+        valence => valence,
+    }
+}
+
+/// Formal charge of the atom block charge field
+fn charge(code: u8) -> Result<i8, Error> {
+    match code {
+        0 | 4 => Ok(0),
+        1..=7 => Ok(4 - code as i8),
+        code => Err(Error::Charge(code)),
+    }
+}
+
+/// Atom block charge field, `0` out of its range
+fn charge_code(charge: i8) -> u8 {
+    match charge {
+        -3..=-1 | 1..=3 => (4 - charge) as u8,
+        _ => 0,
+    }
+}
+
+/// Bond of the bond type field
+fn bond(code: u8) -> Result<Bond, Error> {
+    match code {
+        1 => Ok(Bond::Single),
+        2 => Ok(Bond::Double),
+        3 => Ok(Bond::Triple),
+        4 => Ok(Bond::Aromatic),
+        code => Err(Error::Bond(code)),
+    }
+}
+
+/// Bond type field of the bond
+fn bond_code(bond: Bond) -> u8 {
+    match bond {
+        Bond::Single | Bond::Up | Bond::Down => 1,
+        Bond::Double => 2,
+        Bond::Triple => 3,
+        Bond::Aromatic => 4,
+        // Any bond, the closest query
+        Bond::Quadruple => 8,
+    }
+}
+
+/// Neighbours in ascending index order, the atom itself standing for its
+/// implicit hydrogen last
+fn neighbours(graph: &MoleculeGraph, index: NodeIndex) -> Vec<NodeIndex> {
+    let mut neighbours: Vec<_> = graph.neighbors(index).collect();
+    neighbours.sort();
+    if graph.hydrogens(index) > 0 {
+        neighbours.push(index);
+    }
+    neighbours
+}
+
+/// Atom parity field (`1` odd, `2` even)
+fn parity_code(graph: &MoleculeGraph, index: NodeIndex) -> u8 {
+    match graph.parity(index, &neighbours(graph, index)) {
+        Some(Parity::Clockwise) => 1,
+        Some(Parity::Counterclockwise) => 2,
+        None => 0,
+    }
+}
+
+/// Does any atom have planar coordinates
+fn has_coordinates(graph: &MoleculeGraph) -> bool {
+    graph.node_weights().any(|atom| {
+        atom.coordinates
+            .is_some_and(|[x, y, _]| x != 0.0 || y != 0.0)
+    })
+}
+
+/// Stereo read alongside the bonds
+#[derive(Default)]
+struct Stereo {
+    /// Atom parity fields
+    parities: Vec<u8>,
+    /// Wedge bonds by their narrow end, `1` towards the viewer
+    wedges: Vec<(NodeIndex, NodeIndex, f64)>,
+    /// Double bonds of unknown geometry
+    either: Vec<EdgeIndex>,
+}
+
+impl Stereo {
+    /// Sets the atom parities and double bond geometries
+    fn perceive(self, graph: &mut MoleculeGraph) {
+        if !has_coordinates(graph) {
+            for (index, &code) in self.parities.iter().enumerate() {
+                let index = NodeIndex::new(index);
+                let parity = match code {
+                    1 => Parity::Clockwise,
+                    2 => Parity::Counterclockwise,
+                    _ => continue,
+                };
+                let order = neighbours(graph, index);
+                graph.set_parity(index, &order, Some(parity));
+            }
+            return;
+        }
+        let three = graph.node_weights().any(|atom| {
+            atom.coordinates
+                .is_some_and(|coordinates| coordinates[2] != 0.0)
+        });
+        // Wedges only lift planar coordinates, 3D centers carry a parity
+        let mut centers: Vec<_> = self.wedges.iter().map(|&(center, ..)| center).collect();
+        if three {
+            centers.extend(
+                (0..self.parities.len())
+                    .filter(|&index| self.parities[index] != 0)
+                    .map(NodeIndex::new),
+            );
+        }
+        centers.sort();
+        centers.dedup();
+        for center in centers {
+            let wedges: Vec<_> = self
+                .wedges
+                .iter()
+                .filter(|&&(from, ..)| from == center && !three)
+                .map(|&(_, to, z)| (to, z))
+                .collect();
+            let parity = chirality(graph, center, &wedges);
+            let order = neighbours(graph, center);
+            graph.set_parity(center, &order, parity);
+        }
+        for edge in graph.edge_indices().collect::<Vec<_>>() {
+            if graph[edge] != Bond::Double || self.either.contains(&edge) {
+                continue;
+            }
+            let (a, b) = graph.edge_endpoints(edge).unwrap();
+            if let Some((x, y, geometry)) = geometry(graph, edge, a, b) {
+                graph.set_geometry(x, a, b, y, geometry);
+            }
+        }
+    }
+}
+
+/// Position of an atom relative to the center, wedged out of plane
+fn position(
+    graph: &MoleculeGraph,
+    center: NodeIndex,
+    index: NodeIndex,
+    wedges: &[(NodeIndex, f64)],
+) -> [f64; 3] {
+    let origin = graph[center].coordinates.unwrap_or_default();
+    let [x, y, z] = graph[index].coordinates.unwrap_or_default();
+    let (x, y, z) = (x - origin[0], y - origin[1], z - origin[2]);
+    let wedge = wedges
+        .iter()
+        .find(|&&(to, _)| to == index)
+        .map_or(0.0, |&(_, z)| z);
+    [x, y, z + wedge * x.hypot(y)]
+}
+
+/// Parity of a tetrahedral center from its neighbour positions
+///
+/// An implicit hydrogen is placed opposite the other neighbours.
+fn chirality(
+    graph: &MoleculeGraph,
+    center: NodeIndex,
+    wedges: &[(NodeIndex, f64)],
+) -> Option<Parity> {
+    let order = neighbours(graph, center);
+    let mut positions: Vec<_> = order
+        .iter()
+        .filter(|&&index| index != center)
+        .map(|&index| position(graph, center, index, wedges))
+        .collect();
+    if positions.len() == 3 && order.len() == 4 {
+        let mut hydrogen = [0.0; 3];
+        for position in &positions {
+            let length = position.iter().map(|c| c * c).sum::<f64>().sqrt();
+            for (h, c) in hydrogen.iter_mut().zip(position) {
+                *h -= c / length.max(f64::EPSILON);
+            }
+        }
+        positions.push(hydrogen);
+    }
+    let [a, b, c, d] = positions[..] else {
+        return None;
+    };
+    let difference = |p: [f64; 3]| [p[0] - a[0], p[1] - a[1], p[2] - a[2]];
+    let (b, c, d) = (difference(b), difference(c), difference(d));
+    let volume = b[0] * (c[1] * d[2] - c[2] * d[1]) - b[1] * (c[0] * d[2] - c[2] * d[0])
+        + b[2] * (c[0] * d[1] - c[1] * d[0]);
+    if volume.abs() < 1e-6 {
+        None
+    } else if volume > 0.0 {
+        Some(Parity::Clockwise)
+    } else {
+        Some(Parity::Counterclockwise)
+    }
+}
+
+/// Geometry of an acyclic double bond from the planar coordinates of the
+/// lowest numbered singly bonded neighbours
+fn geometry(
+    graph: &MoleculeGraph,
+    edge: EdgeIndex,
+    a: NodeIndex,
+    b: NodeIndex,
+) -> Option<(NodeIndex, NodeIndex, Geometry)> {
+    let acyclic = EdgeFiltered::from_fn(&**graph, |other| other.id() != edge);
+    if has_path_connecting(&acyclic, a, b, None) {
+        return None;
+    }
+    let reference = |center: NodeIndex, partner: NodeIndex| {
+        let mut neighbours: Vec<_> = graph
+            .edges(center)
+            .filter(|other| other.target() != partner && other.weight().valence() == 1)
+            .map(|other| other.target())
+            .collect();
+        neighbours.sort();
+        neighbours.first().copied()
+    };
+    let (x, y) = (reference(a, b)?, reference(b, a)?);
+    let [ax, ay, _] = graph[a].coordinates?;
+    let [bx, by, _] = graph[b].coordinates?;
+    let side = |index: NodeIndex| {
+        let [x, y, _] = graph[index].coordinates.unwrap_or_default();
+        (bx - ax) * (y - ay) - (by - ay) * (x - ax)
+    };
+    let side = side(x) * side(y);
+    if side.abs() < 1e-6 {
+        return None;
+    }
+    Some((x, y, if side > 0.0 { Geometry::Z } else { Geometry::E }))
+}
+
+/// Wedge bonds drawing the stereocenters on planar coordinates
+///
+/// Each center gets a wedge (`1`) or hash (`6`) on its first singly bonded
+/// neighbour that makes the center chiral, the bond written from the center.
+fn wedges(graph: &MoleculeGraph) -> Vec<(EdgeIndex, NodeIndex, u8)> {
+    let mut wedges: Vec<(EdgeIndex, NodeIndex, u8)> = Vec::new();
+    if !has_coordinates(graph) {
+        return wedges;
+    }
+    for center in graph.node_indices() {
+        let Some(parity) = graph.parity(center, &neighbours(graph, center)) else {
+            continue;
+        };
+        let mut edges: Vec<_> = graph
+            .edges(center)
+            .filter(|edge| edge.weight().valence() == 1)
+            .filter(|edge| wedges.iter().all(|&(other, ..)| other != edge.id()))
+            .map(|edge| (edge.target(), edge.id()))
+            .collect();
+        edges.sort();
+        let Some((edge, up)) = edges.into_iter().find_map(|(neighbour, edge)| {
+            Some((edge, chirality(graph, center, &[(neighbour, 1.0)])?))
+        }) else {
+            continue;
+        };
+        wedges.push((edge, center, if up == parity { 1 } else { 6 }));
+    }
+    wedges
+}
+
+mod error;
+mod layout;
+mod sdf;
+mod v2000;
+mod v3000;
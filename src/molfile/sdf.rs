@@ -0,0 +1,240 @@
+use super::Error;
+use crate::{errors, lines::LossyLines, semantic::MoleculeGraph};
+use std::io::{self, BufRead, Write};
+
+/// Data fields of a record, `> <NAME>` and the value, in file order
+pub type Properties = Vec<(String, String)>;
+
+/// Record delimiter
+const DELIMITER: &str = "$$$$";
+
+/// SD file reader
+///
+/// Iterates the records of any [`BufRead`]. A record that fails to read is
+/// yielded as an error with its file line number and the iteration goes on
+/// with the next record, up to an I/O error.
+pub struct SdfReader<R> {
+    lines: LossyLines<R>,
+    line: usize,
+}
+
+impl<R: BufRead> SdfReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: LossyLines::new(reader),
+            line: 0,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for SdfReader<R> {
+    type Item = errors::Result<(MoleculeGraph, Properties)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.line;
+        let mut record = String::new();
+        let mut delimited = false;
+        for line in &mut self.lines {
+            let line = match line {
+                Ok(line) => line,
+                Err(error) => return Some(Err(error.into())),
+            };
+            self.line += 1;
+            if line.trim_end() == DELIMITER {
+                delimited = true;
+                break;
+            }
+            record += &line;
+            record.push('\n');
+        }
+        if !delimited && record.trim().is_empty() {
+            return None;
+        }
+        Some(read(&record).map_err(|error| match error {
+            errors::Error::Molfile { line, source } => errors::Error::Molfile {
+                line: offset + line,
+                source,
+            },
+            error => error,
+        }))
+    }
+}
+
+/// Reads a record, the line numbers relative to its start
+fn read(record: &str) -> errors::Result<(MoleculeGraph, Properties)> {
+    let lines: Vec<_> = record.lines().collect();
+    let end = lines
+        .iter()
+        .position(|line| line.starts_with("M  END"))
+        .map_or(lines.len(), |end| end + 1);
+    let graph = MoleculeGraph::from_molfile(&lines[..end].join("\n"))?;
+    let mut properties = Properties::new();
+    let mut number = end;
+    while number < lines.len() {
+        let line = lines[number];
+        number += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let name = line
+            .strip_prefix('>')
+            .and_then(|header| header.split_once('<'))
+            .and_then(|(_, name)| name.split_once('>'))
+            .map(|(name, _)| name)
+            .ok_or(errors::Error::Molfile {
+                line: number,
+                source: Error::Expected("> <NAME>"),
+            })?;
+        let mut value = Vec::new();
+        while let Some(line) = lines.get(number).filter(|line| !line.is_empty()) {
+            value.push(*line);
+            number += 1;
+        }
+        properties.push((name.to_owned(), value.join("\n")));
+    }
+    Ok((graph, properties))
+}
+
+/// SD file writer
+pub struct SdfWriter<W> {
+    writer: W,
+}
+
+impl<W: Write> SdfWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Writes a record, the molfile followed by the data fields
+    pub fn write(
+        &mut self,
+        graph: &MoleculeGraph,
+        properties: &[(String, String)],
+    ) -> io::Result<()> {
+        write!(self.writer, "{}", graph.molfile())?;
+        for (name, value) in properties {
+            writeln!(self.writer, "> <{name}>")?;
+            // A blank line ends the value
+            for line in value.lines().filter(|line| !line.is_empty()) {
+                writeln!(self.writer, "{line}")?;
+            }
+            writeln!(self.writer)?;
+        }
+        writeln!(self.writer, "{DELIMITER}")
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lines::test::Failing;
+
+    fn properties(properties: &[(&str, &str)]) -> Properties {
+        properties
+            .iter()
+            .map(|&(name, value)| (name.to_owned(), value.to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let records = [
+            (
+                "N[C@@H](C)C(=O)O",
+                properties(&[("NAME", "L-alanine"), ("ID", "1")]),
+            ),
+            (
+                "CCCCCCCC/C=C\\CCCCCCCC(=O)O",
+                properties(&[
+                    ("NAME", "oleic acid"),
+                    ("SYNONYMS", "FA 18:1\n9Z-octadecenoic acid"),
+                ]),
+            ),
+            ("[NH4+]", Properties::new()),
+        ];
+        let mut writer = SdfWriter::new(Vec::new());
+        for (smiles, properties) in &records {
+            let graph: MoleculeGraph = smiles.parse().unwrap();
+            writer.write(&graph, properties).unwrap();
+        }
+        let sdf = writer.into_inner();
+        assert!(String::from_utf8_lossy(&sdf).contains("> <NAME>\nL-alanine\n\n> <ID>\n1\n\n"));
+        let read: Vec<_> = SdfReader::new(&sdf[..]).map(Result::unwrap).collect();
+        assert_eq!(read.len(), records.len());
+        for ((smiles, properties), (graph, read)) in records.iter().zip(read) {
+            let expected: MoleculeGraph = smiles.parse().unwrap();
+            assert_eq!(
+                graph.canonical_smiles(),
+                expected.canonical_smiles(),
+                "{smiles}"
+            );
+            assert_eq!(&read, properties);
+        }
+    }
+
+    #[test]
+    fn recovery() {
+        let good = |name: &str| {
+            let graph: MoleculeGraph = "CC(=O)O".parse().unwrap();
+            let mut writer = SdfWriter::new(Vec::new());
+            writer
+                .write(&graph, &properties(&[("NAME", name)]))
+                .unwrap();
+            String::from_utf8(writer.into_inner()).unwrap()
+        };
+        let first = good("first");
+        // Unknown element on the seventh line of the second record
+        let bad = first.replacen(" O ", " Xx", 1).replace("first", "bad");
+        let sdf = format!("{first}{bad}{}", good("last"));
+        let records: Vec<_> = SdfReader::new(sdf.as_bytes()).collect();
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            records[0].as_ref().unwrap().1,
+            properties(&[("NAME", "first")])
+        );
+        let line = first.lines().count() + 7;
+        match &records[1] {
+            Err(errors::Error::Molfile {
+                line: number,
+                source,
+            }) => {
+                assert_eq!(*number, line);
+                assert_eq!(source.to_string(), r#"unknown atom symbol "Xx""#);
+            }
+            other => panic!("{other:?}"),
+        }
+        assert_eq!(
+            records[2].as_ref().unwrap().1,
+            properties(&[("NAME", "last")])
+        );
+        // Data header without a name
+        let sdf = first.replace("> <NAME>", "> NAME");
+        match SdfReader::new(sdf.as_bytes()).next() {
+            Some(Err(errors::Error::Molfile { line, .. })) => {
+                assert_eq!(
+                    line,
+                    sdf.lines().position(|line| line == "> NAME").unwrap() + 1
+                )
+            }
+            other => panic!("{other:?}"),
+        }
+        // Trailing record without a delimiter
+        let sdf = first.trim_end_matches("$$$$\n");
+        assert_eq!(SdfReader::new(sdf.as_bytes()).count(), 1);
+        assert_eq!(SdfReader::new(&b"\n"[..]).count(), 0);
+        // Invalid UTF-8 in a value
+        let mut sdf = format!("{first}{}", good("lxst")).into_bytes();
+        let at = sdf.windows(4).position(|window| window == b"lxst").unwrap();
+        sdf[at + 1] = 0xff;
+        let records: Vec<_> = SdfReader::new(&sdf[..]).map(Result::unwrap).collect();
+        assert_eq!(records[1].1, properties(&[("NAME", "l\u{fffd}st")]));
+        // Failing reader
+        let mut records = SdfReader::new(io::BufReader::new(Failing));
+        assert!(matches!(records.next(), Some(Err(errors::Error::Io(_)))));
+        assert!(records.next().is_none());
+    }
+}
@@ -0,0 +1,424 @@
+use super::{
+    bond, bond_code, charge, charge_code, column, element, finish, list, number, parity_code,
+    valence, wedges, Error, Lines, Stereo,
+};
+use crate::{
+    errors,
+    semantic::{Atom, AtomList, Bond, MoleculeGraph},
+};
+use petgraph::graph::NodeIndex;
+use std::fmt::{self, Formatter};
+
+/// Atom and bond count limit of the counts line
+pub(super) const LIMIT: usize = 999;
+
+/// Element limit of an atom list
+pub(super) const LIST: usize = 16;
+
+/// Reads the connection table following the counts line
+pub(super) fn read(lines: &mut Lines, counts: &str) -> errors::Result<MoleculeGraph> {
+    let atoms: usize = number(counts, 0..3).map_err(|source| lines.error(source))?;
+    let bonds: usize = number(counts, 3..6).map_err(|source| lines.error(source))?;
+    let mut graph = MoleculeGraph::default();
+    let mut stereo = Stereo::default();
+    let mut valences = Vec::with_capacity(atoms);
+    for _ in 0..atoms {
+        let line = lines.next()?;
+        let (atom, parity, valence) = read_atom(line).map_err(|source| lines.error(source))?;
+        graph.add_node(atom);
+        stereo.parities.push(parity);
+        valences.push(valence);
+    }
+    for _ in 0..bonds {
+        let line = lines.next()?;
+        read_bond(line, &mut graph, &mut stereo).map_err(|source| lines.error(source))?;
+    }
+    let (mut charges, mut isotopes) = (false, false);
+    loop {
+        let line = lines.next()?;
+        if line.starts_with("M  END") {
+            break;
+        }
+        // Atom alias and group lines carry their text on the next line
+        if matches!(column(line, 0..3), "A" | "G") {
+            lines.next()?;
+            continue;
+        }
+        if column(line, 0..6) == "M  ALS" {
+            let (index, list) = read_list(line).map_err(|source| lines.error(source))?;
+            let atom = index
+                .checked_sub(1)
+                .and_then(|index| graph.node_weight_mut(NodeIndex::new(index)))
+                .ok_or_else(|| lines.error(Error::Atom(index)))?;
+            atom.element = None;
+            atom.list = Some(list);
+            continue;
+        }
+        // Charges of -15 to 15, mass numbers from 1
+        let property: fn(&mut Atom, i16) -> Option<()> = match column(line, 0..6) {
+            "M  CHG" => {
+                if !charges {
+                    charges = true;
+                    graph.node_weights_mut().for_each(|atom| atom.charge = 0);
+                }
+                |atom: &mut Atom, value: i16| {
+                    atom.charge = (-15..=15).contains(&value).then_some(value as i8)?;
+                    Some(())
+                }
+            }
+            "M  ISO" => {
+                if !isotopes {
+                    isotopes = true;
+                    graph
+                        .node_weights_mut()
+                        .for_each(|atom| atom.isotope = None);
+                }
+                |atom: &mut Atom, value: i16| {
+                    atom.isotope = Some(u16::try_from(value).ok().filter(|&mass| mass > 0)?);
+                    Some(())
+                }
+            }
+            _ => continue,
+        };
+        let entries: usize = number(line, 6..9).map_err(|source| lines.error(source))?;
+        for entry in 0..entries {
+            let offset = 9 + 8 * entry;
+            let (index, value) = (|| {
+                let index: usize = number(line, offset..offset + 4)?;
+                let value: i16 = number(line, offset + 4..offset + 8)?;
+                Ok((index, value))
+            })()
+            .map_err(|source| lines.error(source))?;
+            let atom = index
+                .checked_sub(1)
+                .and_then(|index| graph.node_weight_mut(NodeIndex::new(index)))
+                .ok_or_else(|| lines.error(Error::Atom(index)))?;
+            property(atom, value).ok_or_else(|| lines.error(Error::Number(value.to_string())))?;
+        }
+    }
+    finish(&mut graph, valences, stereo);
+    Ok(graph)
+}
+
+/// Atom with its parity and valence fields
+fn read_atom(line: &str) -> Result<(Atom, u8, u8), Error> {
+    let coordinates = [
+        number(line, 0..10)?,
+        number(line, 10..20)?,
+        number(line, 20..30)?,
+    ];
+    let element = element(column(line, 31..34))?;
+    // Mass difference of -3 to +4 from the rounded average mass
+    let difference: i8 = number(line, 34..36)?;
+    let isotope = match (element, difference) {
+        (_, 0) => None,
+        (Some(element), -3..=4) => Some(
+            element
+                .average_mass()
+                .and_then(|mass| u16::try_from(mass.round() as i16 + difference as i16).ok())
+                .filter(|&mass| mass > 0)
+                .ok_or_else(|| Error::Number(difference.to_string()))?,
+        ),
+        _ => return Err(Error::Number(difference.to_string())),
+    };
+    let atom = Atom {
+        isotope,
+        element,
+        charge: charge(number(line, 36..39)?)?,
+        coordinates: Some(coordinates),
+        ..Default::default()
+    };
+    Ok((atom, number(line, 39..42)?, number(line, 48..51)?))
+}
+
+/// Atom with its list, `M  ALS aaannn e 11112222...`
+fn read_list(line: &str) -> Result<(usize, AtomList), Error> {
+    let index = number(line, 6..10)?;
+    let entries: usize = number(line, 10..13)?;
+    let negated = column(line, 13..15) == "T";
+    let symbols = (0..entries).map(|entry| column(line, 16 + 4 * entry..20 + 4 * entry));
+    Ok((index, list(negated, symbols)?))
+}
+
+fn read_bond(line: &str, graph: &mut MoleculeGraph, stereo: &mut Stereo) -> Result<(), Error> {
+    let atom = |range| {
+        let index: usize = number(line, range)?;
+        match index.checked_sub(1) {
+            Some(index) if index < graph.node_count() => Ok(NodeIndex::new(index)),
+            _ => Err(Error::Atom(index)),
+        }
+    };
+    let (first, second) = (atom(0..3)?, atom(3..6)?);
+    let bond = bond(number(line, 6..9)?)?;
+    let edge = graph.add_edge(first, second, bond);
+    match (bond, number::<u8>(line, 9..12)?) {
+        (Bond::Single, 1) => stereo.wedges.push((first, second, 1.0)),
+        (Bond::Single, 6) => stereo.wedges.push((first, second, -1.0)),
+        (Bond::Double, 3) => stereo.either.push(edge),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Writes the counts line, connection table and properties
+pub(super) fn write(f: &mut Formatter, graph: &MoleculeGraph) -> fmt::Result {
+    let chiral = graph.node_weights().any(|atom| atom.parity.is_some()) as u8;
+    writeln!(
+        f,
+        "{:>3}{:>3}  0  0{chiral:>3}  0  0  0  0  0999 V2000",
+        graph.node_count(),
+        graph.edge_count(),
+    )?;
+    for index in graph.node_indices() {
+        let atom = &graph[index];
+        let [x, y, z] = atom.coordinates.unwrap_or_default();
+        let symbol = match (atom.element, atom.list) {
+            (Some(element), _) => element.to_string(),
+            (None, Some(_)) => "L".to_owned(),
+            (None, None) => "*".to_owned(),
+        };
+        writeln!(
+            f,
+            "{x:>10.4}{y:>10.4}{z:>10.4} {symbol:<3} 0{:>3}{:>3}  0  0{:>3}  0  0  0  0  0  0",
+            charge_code(atom.charge),
+            parity_code(graph, index),
+            valence(graph, index),
+        )?;
+    }
+    let wedges = wedges(graph);
+    for edge in graph.edge_indices() {
+        let (mut first, mut second) = graph.edge_endpoints(edge).unwrap();
+        let mut stereo = 0;
+        if let Some(&(_, center, wedge)) = wedges.iter().find(|&&(other, ..)| other == edge) {
+            if center != first {
+                (first, second) = (second, first);
+            }
+            stereo = wedge;
+        }
+        let bond = bond_code(graph[edge]);
+        writeln!(
+            f,
+            "{:>3}{:>3}{bond:>3}{stereo:>3}  0  0  0",
+            first.index() + 1,
+            second.index() + 1,
+        )?;
+    }
+    let charges: Vec<_> = graph
+        .node_indices()
+        .filter(|&index| graph[index].charge != 0)
+        .map(|index| (index, graph[index].charge as i16))
+        .collect();
+    let isotopes: Vec<_> = graph
+        .node_indices()
+        .filter_map(|index| Some((index, graph[index].isotope? as i16)))
+        .collect();
+    for index in graph.node_indices() {
+        let Some(list) = graph[index].list else {
+            continue;
+        };
+        let negated = if list.negated { 'T' } else { 'F' };
+        write!(
+            f,
+            "M  ALS {:>3}{:>3} {negated} ",
+            index.index() + 1,
+            list.len()
+        )?;
+        for element in list.elements() {
+            write!(f, "{:<4}", element.to_string())?;
+        }
+        writeln!(f)?;
+    }
+    for (property, entries) in [("CHG", charges), ("ISO", isotopes)] {
+        for chunk in entries.chunks(8) {
+            write!(f, "M  {property}{:>3}", chunk.len())?;
+            for (index, value) in chunk {
+                write!(f, " {:>3} {value:>3}", index.index() + 1)?;
+            }
+            writeln!(f)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// L-alanine, the methyl hashed
+    const ALANINE: &str = "
+  smiles            2D
+
+  6  5  0  0  1  0  0  0  0  0999 V2000
+    0.0000    0.0000    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0
+   -0.8660    0.5000    0.0000 N   0  0  0  0  0  0  0  0  0  0  0  0
+    0.8660    0.5000    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0
+    0.0000   -1.0000    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0
+    0.8660    1.5000    0.0000 O   0  0  0  0  0  0  0  0  0  0  0  0
+    1.7321    0.0000    0.0000 O   0  0  0  0  0  0  0  0  0  0  0  0
+  1  2  1  0  0  0  0
+  1  3  1  0  0  0  0
+  1  4  1  6  0  0  0
+  3  5  2  0  0  0  0
+  3  6  1  0  0  0  0
+M  END
+";
+
+    fn canonical(smiles: &str) -> String {
+        smiles.parse::<MoleculeGraph>().unwrap().canonical_smiles()
+    }
+
+    #[test]
+    fn read() {
+        let alanine = MoleculeGraph::from_molfile(ALANINE).unwrap();
+        assert_eq!(alanine.node_count(), 6);
+        assert_eq!(
+            alanine[NodeIndex::new(1)].coordinates,
+            Some([-0.866, 0.5, 0.0])
+        );
+        assert_eq!(alanine.canonical_smiles(), canonical("N[C@@H](C)C(=O)O"));
+        // Wedged instead of hashed
+        let wedged = ALANINE.replace("  1  4  1  6", "  1  4  1  1");
+        let alanine = MoleculeGraph::from_molfile(&wedged).unwrap();
+        assert_eq!(alanine.canonical_smiles(), canonical("N[C@H](C)C(=O)O"));
+        // Charges and isotopes, properties superseding the atom block
+        let molfile = "
+  smiles            2D
+
+  2  1  0  0  0  0  0  0  0  0999 V2000
+    0.0000    0.0000    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0
+    1.0000    0.0000    0.0000 N   0  3  0  0  0  0  0  0  0  0  0  0
+  1  2  1  0  0  0  0
+M  CHG  1   2   1
+M  ISO  1   1  13
+M  END
+";
+        let graph = MoleculeGraph::from_molfile(molfile).unwrap();
+        assert_eq!(graph.canonical_smiles(), canonical("[13CH3][NH3+]"));
+        let graph = MoleculeGraph::from_molfile(&molfile.replace(" C   0", " C   1")).unwrap();
+        assert_eq!(graph.canonical_smiles(), canonical("[13CH3][NH3+]"));
+        let graph =
+            MoleculeGraph::from_molfile(&molfile.replace("M  ISO  1   1  13\n", "")).unwrap();
+        assert_eq!(graph.canonical_smiles(), canonical("C[NH3+]"));
+        let molfile = molfile
+            .replace("M  ISO  1   1  13\n", "")
+            .replace(" C   0", " C   1");
+        let graph = MoleculeGraph::from_molfile(&molfile).unwrap();
+        assert_eq!(graph.canonical_smiles(), canonical("[13CH3][NH3+]"));
+        // Trans but-2-ene
+        let molfile = "
+  smiles            2D
+
+  4  3  0  0  0  0  0  0  0  0999 V2000
+    0.0000    0.0000    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0
+    0.8660    0.5000    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0
+    1.7321    0.0000    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0
+    2.5981    0.5000    0.0000 C   0  0  0  0  0  0  0  0  0  0  0  0
+  1  2  1  0  0  0  0
+  2  3  2  0  0  0  0
+  3  4  1  0  0  0  0
+M  END
+";
+        let graph = MoleculeGraph::from_molfile(molfile).unwrap();
+        assert_eq!(graph.to_string(), "C/C=C/C");
+        let either = molfile.replace("  2  3  2  0", "  2  3  2  3");
+        let graph = MoleculeGraph::from_molfile(&either).unwrap();
+        assert_eq!(graph.to_string(), "CC=CC");
+    }
+
+    #[test]
+    fn write() {
+        let graph: MoleculeGraph = "C[N+](C)(C)CCO".parse().unwrap();
+        let molfile = graph.molfile().to_string();
+        let lines: Vec<_> = molfile.lines().collect();
+        assert_eq!(lines[3], "  7  6  0  0  0  0  0  0  0  0999 V2000");
+        assert_eq!(
+            lines[5],
+            "    3.8971   -1.2990    0.0000 N   0  3  0  0  0  0  0  0  0  0  0  0"
+        );
+        assert!(lines[11..17].contains(&"  1  2  1  0  0  0  0"));
+        assert_eq!(lines[17], "M  CHG  1   2   1");
+        assert_eq!(lines[18], "M  END");
+    }
+
+    #[test]
+    fn round_trip() {
+        for smiles in [
+            "N[C@@H](C)C(=O)O",
+            "C[C@H](N)C(=O)O",
+            "[NH4+]",
+            "[13CH4]",
+            "[O-]C(=O)CC[NH3+]",
+            "CC(C)[C@@]1(O)CC[C@H](C)C[C@@H]1O",
+            "[Cl-]",
+            "C/C=C/C",
+            "C/C=C\\C",
+            "CCCCCCCC/C=C\\CCCCCCCC(=O)O",
+            "CCCCCCCCCCCCCCCC(=O)OC[C@@H](OC(=O)CCCCCCCCCCCCCCC)COP(=O)([O-])OCC[N+](C)(C)C",
+        ] {
+            let graph: MoleculeGraph = smiles.parse().unwrap();
+            let molfile = graph.molfile().to_string();
+            let read = MoleculeGraph::from_molfile(&molfile).unwrap();
+            assert_eq!(
+                read.canonical_smiles(),
+                graph.canonical_smiles(),
+                "{smiles}"
+            );
+        }
+        // Wedges drawn from the perceived parity
+        let alanine = MoleculeGraph::from_molfile(ALANINE).unwrap();
+        let molfile = alanine.molfile().to_string();
+        assert!(molfile.contains("  1  2  1  1") || molfile.contains("  1  2  1  6"));
+        let read = MoleculeGraph::from_molfile(&molfile).unwrap();
+        assert_eq!(read.canonical_smiles(), alanine.canonical_smiles());
+    }
+
+    #[test]
+    fn errors() {
+        let error = |molfile: &str| match MoleculeGraph::from_molfile(molfile) {
+            Err(errors::Error::Molfile { line, source }) => (line, source.to_string()),
+            other => panic!("{other:?}"),
+        };
+        assert_eq!(
+            error(&ALANINE.replace(" N   0", " Xx  0")),
+            (6, r#"unknown atom symbol "Xx""#.to_owned())
+        );
+        assert_eq!(
+            error(&ALANINE.replace("  1  4  1  6", "  1  9  1  6")),
+            (13, "atom 9 out of range".to_owned())
+        );
+        assert_eq!(
+            error(&ALANINE.replace("M  END\n", "")),
+            (16, "unexpected end of file".to_owned())
+        );
+        assert_eq!(
+            error(&ALANINE.replace("V2000", "V2001")),
+            (4, r#"unsupported version "V2001""#.to_owned())
+        );
+        assert_eq!(
+            error(&ALANINE.replace("  3  5  2", "  3  5  7")),
+            (14, "unsupported bond type 7".to_owned())
+        );
+        // Property values out of range
+        assert_eq!(
+            error(&ALANINE.replace("M  END", "M  CHG  1   1 200\nM  END")),
+            (16, r#"invalid number "200""#.to_owned())
+        );
+        assert_eq!(
+            error(&ALANINE.replace("M  END", "M  ISO  1   1  -1\nM  END")),
+            (16, r#"invalid number "-1""#.to_owned())
+        );
+        assert_eq!(
+            error(&ALANINE.replace("M  END", "M  ISO  1   1   0\nM  END")),
+            (16, r#"invalid number "0""#.to_owned())
+        );
+        // Mass differences out of range or without an average mass
+        assert_eq!(
+            error(&ALANINE.replace(" N   0", " N  -5")),
+            (6, r#"invalid number "-5""#.to_owned())
+        );
+        assert_eq!(
+            error(&ALANINE.replace(" N   0", " Fe  1")),
+            (6, r#"invalid number "1""#.to_owned())
+        );
+    }
+}
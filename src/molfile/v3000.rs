@@ -0,0 +1,516 @@
+use super::{
+    bond, bond_code, element, finish, list, parity_code, valence, wedges, Error, Lines, Stereo,
+};
+use crate::{
+    errors,
+    semantic::{Atom, Bond, MoleculeGraph, StereoGroup},
+};
+use petgraph::graph::NodeIndex;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Formatter},
+};
+
+/// Content width of a `M  V30` line before continuation
+const WIDTH: usize = 72;
+
+/// Reads the connection table following the counts line
+pub(super) fn read(lines: &mut Lines) -> errors::Result<MoleculeGraph> {
+    expect(lines, "BEGIN CTAB")?;
+    let counts = next(lines)?;
+    let mut counts = counts.split_whitespace();
+    if counts.next() != Some("COUNTS") {
+        return Err(lines.error(Error::Expected("COUNTS")));
+    }
+    let atoms = counts.next().unwrap_or_default();
+    let atoms: usize = parse(atoms).map_err(|source| lines.error(source))?;
+    let mut graph = MoleculeGraph::default();
+    let mut stereo = Stereo::default();
+    let mut valences = Vec::with_capacity(atoms);
+    let mut indices = BTreeMap::new();
+    expect(lines, "BEGIN ATOM")?;
+    loop {
+        let line = next(lines)?;
+        if line == "END ATOM" {
+            break;
+        }
+        let (index, atom, parity, valence) =
+            read_atom(&line).map_err(|source| lines.error(source))?;
+        indices.insert(index, graph.add_node(atom));
+        stereo.parities.push(parity);
+        valences.push(valence);
+    }
+    loop {
+        let line = next(lines)?;
+        match &*line {
+            "END CTAB" => break,
+            "BEGIN BOND" => loop {
+                let line = next(lines)?;
+                if line == "END BOND" {
+                    break;
+                }
+                read_bond(&line, &indices, &mut graph, &mut stereo)
+                    .map_err(|source| lines.error(source))?;
+            },
+            "BEGIN COLLECTION" => loop {
+                let line = next(lines)?;
+                if line == "END COLLECTION" {
+                    break;
+                }
+                read_collection(&line, &indices, &mut graph)
+                    .map_err(|source| lines.error(source))?;
+            },
+            // Sgroups, 3D features and other blocks
+            block if block.starts_with("BEGIN ") => {
+                let end = block.replacen("BEGIN", "END", 1);
+                while next(lines)? != end {}
+            }
+            _ => {}
+        }
+    }
+    while !lines.next()?.starts_with("M  END") {}
+    finish(&mut graph, valences, stereo);
+    Ok(graph)
+}
+
+/// Atom with its index, parity and valence fields,
+/// `index type x y z aamap [CHG=c] [MASS=m] [CFG=p] [VAL=v]`
+fn read_atom(line: &str) -> Result<(usize, Atom, u8, u8), Error> {
+    let tokens = tokens(line);
+    let [index, kind, x, y, z, ..] = tokens[..] else {
+        return Err(Error::Expected("atom"));
+    };
+    let mut atom = Atom {
+        coordinates: Some([parse(x)?, parse(y)?, parse(z)?]),
+        ..Default::default()
+    };
+    let negated = kind.starts_with("NOT");
+    match kind.trim_start_matches("NOT").strip_prefix('[') {
+        Some(symbols) => {
+            let symbols = symbols.strip_suffix(']').ok_or(Error::Expected("]"))?;
+            atom.list = Some(list(negated, symbols.split(','))?);
+        }
+        None => atom.element = element(kind)?,
+    }
+    let (mut parity, mut valence) = (0, 0);
+    for (key, value) in tokens[6.min(tokens.len())..]
+        .iter()
+        .filter_map(|token| token.split_once('='))
+    {
+        match key {
+            "CHG" => {
+                atom.charge = match parse(value)? {
+                    charge @ -15..=15 => charge,
+                    _ => return Err(Error::Number(value.to_owned())),
+                }
+            }
+            "MASS" => atom.isotope = Some(parse(value)?),
+            "CFG" => parity = parse(value)?,
+            "VAL" => {
+                valence = match parse::<i8>(value)? {
+                    -1 => 15,
+                    valence @ 0..=14 => valence as u8,
+                    _ => return Err(Error::Number(value.to_owned())),
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok((parse(index)?, atom, parity, valence))
+}
+
+/// `index type atom1 atom2 [CFG=c]`
+fn read_bond(
+    line: &str,
+    indices: &BTreeMap<usize, NodeIndex>,
+    graph: &mut MoleculeGraph,
+    stereo: &mut Stereo,
+) -> Result<(), Error> {
+    let tokens = tokens(line);
+    let [_, kind, first, second, ..] = tokens[..] else {
+        return Err(Error::Expected("bond"));
+    };
+    let (first, second) = (atom(indices, first)?, atom(indices, second)?);
+    let bond = bond(parse(kind)?)?;
+    let edge = graph.add_edge(first, second, bond);
+    let configuration = tokens
+        .iter()
+        .find_map(|token| token.strip_prefix("CFG="))
+        .map(parse::<u8>)
+        .transpose()?;
+    match (bond, configuration) {
+        (Bond::Single, Some(1)) => stereo.wedges.push((first, second, 1.0)),
+        (Bond::Single, Some(3)) => stereo.wedges.push((first, second, -1.0)),
+        (Bond::Double, Some(2)) => stereo.either.push(edge),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Stereo groups, `MDLV30/STEABS ATOMS=(n a1 a2 ...)`, `MDLV30/STERACk`
+/// and `MDLV30/STERELk`
+fn read_collection(
+    line: &str,
+    indices: &BTreeMap<usize, NodeIndex>,
+    graph: &mut MoleculeGraph,
+) -> Result<(), Error> {
+    let tokens = tokens(line);
+    let Some(name) = tokens.first().and_then(|name| name.strip_prefix("MDLV30/")) else {
+        return Ok(());
+    };
+    let group = if name == "STEABS" {
+        StereoGroup::Absolute
+    } else if let Some(number) = name.strip_prefix("STERAC") {
+        StereoGroup::And(parse(number)?)
+    } else if let Some(number) = name.strip_prefix("STEREL") {
+        StereoGroup::Or(parse(number)?)
+    } else {
+        return Ok(());
+    };
+    let atoms = tokens
+        .iter()
+        .find_map(|token| token.strip_prefix("ATOMS="))
+        .ok_or(Error::Expected("ATOMS="))?;
+    for index in list_values(atoms)? {
+        let index = atom(indices, index)?;
+        graph[index].stereo_group = Some(group);
+    }
+    Ok(())
+}
+
+/// Writes the counts line and the connection table
+pub(super) fn write(f: &mut Formatter, graph: &MoleculeGraph) -> fmt::Result {
+    f.write_str("  0  0  0     0  0            999 V3000\n")?;
+    line(f, "BEGIN CTAB")?;
+    let chiral = graph.node_weights().any(|atom| atom.parity.is_some()) as u8;
+    line(
+        f,
+        &format!(
+            "COUNTS {} {} 0 0 {chiral}",
+            graph.node_count(),
+            graph.edge_count()
+        ),
+    )?;
+    line(f, "BEGIN ATOM")?;
+    for index in graph.node_indices() {
+        let atom = &graph[index];
+        let [x, y, z] = atom.coordinates.unwrap_or_default();
+        let kind = match (atom.element, atom.list) {
+            (Some(element), _) => element.to_string(),
+            (None, Some(list)) => {
+                let symbols: Vec<_> = list.elements().map(|element| element.to_string()).collect();
+                let not = if list.negated { "NOT" } else { "" };
+                format!("{not}[{}]", symbols.join(","))
+            }
+            (None, None) => "*".to_owned(),
+        };
+        let mut text = format!("{} {kind} {x:.4} {y:.4} {z:.4} 0", index.index() + 1);
+        if atom.charge != 0 {
+            text += &format!(" CHG={}", atom.charge);
+        }
+        if let Some(isotope) = atom.isotope {
+            text += &format!(" MASS={isotope}");
+        }
+        match parity_code(graph, index) {
+            0 => {}
+            parity => text += &format!(" CFG={parity}"),
+        }
+        match valence(graph, index) {
+            0 => {}
+            15 => text += " VAL=-1",
+            valence => text += &format!(" VAL={valence}"),
+        }
+        line(f, &text)?;
+    }
+    line(f, "END ATOM")?;
+    if graph.edge_count() > 0 {
+        line(f, "BEGIN BOND")?;
+        let wedges = wedges(graph);
+        for edge in graph.edge_indices() {
+            let (mut first, mut second) = graph.edge_endpoints(edge).unwrap();
+            let mut configuration = 0;
+            if let Some(&(_, center, wedge)) = wedges.iter().find(|&&(other, ..)| other == edge) {
+                if center != first {
+                    (first, second) = (second, first);
+                }
+                configuration = if wedge == 1 { 1 } else { 3 };
+            }
+            let bond = bond_code(graph[edge]);
+            let mut text = format!(
+                "{} {bond} {} {}",
+                edge.index() + 1,
+                first.index() + 1,
+                second.index() + 1
+            );
+            if configuration != 0 {
+                text += &format!(" CFG={configuration}");
+            }
+            line(f, &text)?;
+        }
+        line(f, "END BOND")?;
+    }
+    let mut groups = BTreeMap::<_, Vec<_>>::new();
+    for index in graph.node_indices() {
+        if let Some(group) = graph[index].stereo_group {
+            groups.entry(group).or_default().push(index.index() + 1);
+        }
+    }
+    if !groups.is_empty() {
+        line(f, "BEGIN COLLECTION")?;
+        for (group, atoms) in groups {
+            let name = match group {
+                StereoGroup::Absolute => "STEABS".to_owned(),
+                StereoGroup::And(number) => format!("STERAC{number}"),
+                StereoGroup::Or(number) => format!("STEREL{number}"),
+            };
+            let atoms: Vec<_> = atoms.iter().map(ToString::to_string).collect();
+            line(
+                f,
+                &format!("MDLV30/{name} ATOMS=({} {})", atoms.len(), atoms.join(" ")),
+            )?;
+        }
+        line(f, "END COLLECTION")?;
+    }
+    line(f, "END CTAB")
+}
+
+/// Writes a `M  V30` line, continued with `-` past the width
+fn line(f: &mut Formatter, text: &str) -> fmt::Result {
+    let mut text = text;
+    while text.len() > WIDTH {
+        let (head, tail) = text.split_at(WIDTH);
+        writeln!(f, "M  V30 {head}-")?;
+        text = tail;
+    }
+    writeln!(f, "M  V30 {text}")
+}
+
+/// Next `M  V30` line content, continuation lines joined
+fn next(lines: &mut Lines) -> errors::Result<String> {
+    let mut content = String::new();
+    loop {
+        let line = lines.next()?;
+        let text = line
+            .strip_prefix("M  V30 ")
+            .ok_or_else(|| lines.error(Error::Expected("M  V30")))?;
+        match text.strip_suffix('-') {
+            Some(text) => content += text,
+            None => {
+                content += text;
+                return Ok(content.trim().to_owned());
+            }
+        }
+    }
+}
+
+fn expect(lines: &mut Lines, expected: &'static str) -> errors::Result<()> {
+    if next(lines)? != expected {
+        return Err(lines.error(Error::Expected(expected)));
+    }
+    Ok(())
+}
+
+/// Whitespace separated tokens, parenthesized and quoted values kept whole
+fn tokens(line: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let (mut start, mut depth, mut quoted) = (None, 0, false);
+    for (position, char) in line.char_indices() {
+        match char {
+            '"' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted => depth -= 1,
+            char if char.is_whitespace() && depth == 0 && !quoted => {
+                if let Some(start) = start.take() {
+                    tokens.push(&line[start..position]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(position);
+    }
+    if let Some(start) = start {
+        tokens.push(&line[start..]);
+    }
+    tokens
+}
+
+/// Values of a counted list `(n v1 v2 ...)`
+fn list_values(text: &str) -> Result<Vec<&str>, Error> {
+    let text = text
+        .strip_prefix('(')
+        .and_then(|text| text.strip_suffix(')'))
+        .ok_or(Error::Expected("("))?;
+    let mut values = text.split_whitespace();
+    let count: usize = parse(values.next().unwrap_or_default())?;
+    let values: Vec<_> = values.collect();
+    if values.len() != count {
+        return Err(Error::Number(text.to_owned()));
+    }
+    Ok(values)
+}
+
+fn atom(indices: &BTreeMap<usize, NodeIndex>, text: &str) -> Result<NodeIndex, Error> {
+    let index = parse(text)?;
+    indices.get(&index).copied().ok_or(Error::Atom(index))
+}
+
+fn parse<T: std::str::FromStr>(text: &str) -> Result<T, Error> {
+    text.parse().map_err(|_| Error::Number(text.to_owned()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        molfile::Version,
+        semantic::{AtomList, Element},
+    };
+
+    const ALANINE: &str = "
+  smiles            2D
+
+  0  0  0     0  0            999 V3000
+M  V30 BEGIN CTAB
+M  V30 COUNTS 6 5 0 0 0
+M  V30 BEGIN ATOM
+M  V30 1 C 0 0 0 0 CFG=2
+M  V30 2 N -0.866 0.5 0 0
+M  V30 3 C 0.866 0.5 0 0
+M  V30 4 C 0 -1 0 0
+M  V30 5 O 0.866 1.5 0 0
+M  V30 6 O 1.7321 0 0 0 -
+M  V30 CHG=-1
+M  V30 END ATOM
+M  V30 BEGIN BOND
+M  V30 1 1 1 2
+M  V30 2 1 1 3
+M  V30 3 1 1 4 CFG=3
+M  V30 4 2 3 5
+M  V30 5 1 3 6
+M  V30 END BOND
+M  V30 BEGIN COLLECTION
+M  V30 MDLV30/STERAC1 ATOMS=(1 1)
+M  V30 END COLLECTION
+M  V30 END CTAB
+M  END
+";
+
+    #[test]
+    fn read() {
+        let alanine = MoleculeGraph::from_molfile(ALANINE).unwrap();
+        assert_eq!(alanine.node_count(), 6);
+        assert_eq!(alanine[NodeIndex::new(5)].charge, -1);
+        assert_eq!(
+            alanine[NodeIndex::new(0)].stereo_group,
+            Some(StereoGroup::And(1))
+        );
+        let expected: MoleculeGraph = "N[C@@H](C)C(=O)[O-]".parse().unwrap();
+        assert_eq!(alanine.canonical_smiles(), expected.canonical_smiles());
+        // Atom list
+        let molfile = ALANINE.replace("M  V30 2 N ", "M  V30 2 NOT[N,O] ");
+        let graph = MoleculeGraph::from_molfile(&molfile).unwrap();
+        let atom = graph[NodeIndex::new(1)];
+        assert_eq!(atom.element, None);
+        assert_eq!(
+            atom.list,
+            Some(AtomList::new(true, [Element::O, Element::N]))
+        );
+        assert!(atom.list.unwrap().matches(Element::C));
+        assert!(!atom.list.unwrap().matches(Element::N));
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut alanine = MoleculeGraph::from_molfile(ALANINE).unwrap();
+        // Relative stereo is written as V3000
+        let molfile = alanine.molfile().to_string();
+        assert!(molfile.contains("V3000"));
+        assert!(molfile.contains("M  V30 MDLV30/STERAC1 ATOMS=(1 1)"));
+        let read = MoleculeGraph::from_molfile(&molfile).unwrap();
+        assert_eq!(read.canonical_smiles(), alanine.canonical_smiles());
+        assert_eq!(
+            read[NodeIndex::new(0)].stereo_group,
+            Some(StereoGroup::And(1))
+        );
+        alanine[NodeIndex::new(0)].stereo_group = Some(StereoGroup::Absolute);
+        assert!(alanine.molfile().to_string().contains("V2000"));
+        // Atom lists in both versions
+        alanine[NodeIndex::new(1)].element = None;
+        alanine[NodeIndex::new(1)].list = Some(AtomList::new(false, [Element::N, Element::O]));
+        for version in [Version::V2000, Version::V3000] {
+            let molfile = alanine.molfile_version(version).to_string();
+            let read = MoleculeGraph::from_molfile(&molfile).unwrap();
+            assert_eq!(
+                read[NodeIndex::new(1)].list,
+                alanine[NodeIndex::new(1)].list
+            );
+        }
+        for smiles in [
+            "N[C@@H](C)C(=O)O",
+            "[NH4+]",
+            "[13CH4]",
+            "CC(C)[C@@]1(O)CC[C@H](C)C[C@@H]1O",
+            "C/C=C/C",
+            "C/C=C\\C",
+            "CCCCCCCC/C=C\\CCCCCCCC(=O)O",
+        ] {
+            let graph: MoleculeGraph = smiles.parse().unwrap();
+            let molfile = graph.molfile_version(Version::V3000).to_string();
+            let read = MoleculeGraph::from_molfile(&molfile).unwrap();
+            assert_eq!(
+                read.canonical_smiles(),
+                graph.canonical_smiles(),
+                "{smiles}"
+            );
+        }
+    }
+
+    #[test]
+    fn large() {
+        // More than 999 atoms select V3000, the long lines continued
+        let smiles = "C".repeat(1200);
+        let mut graph: MoleculeGraph = smiles.parse().unwrap();
+        for index in 0..4 {
+            graph[NodeIndex::new(index)].stereo_group = Some(StereoGroup::Or(1));
+        }
+        let molfile = graph.molfile().to_string();
+        assert!(molfile.contains("M  V30 COUNTS 1200 1199 0 0 0"));
+        assert!(molfile.lines().all(|line| line.len() <= 80));
+        let read = MoleculeGraph::from_molfile(&molfile).unwrap();
+        assert_eq!(read.node_count(), 1200);
+        assert_eq!(read.canonical_smiles(), graph.canonical_smiles());
+    }
+
+    #[test]
+    fn errors() {
+        let error = |molfile: &str| match MoleculeGraph::from_molfile(molfile) {
+            Err(errors::Error::Molfile { line, source }) => (line, source.to_string()),
+            other => panic!("{other:?}"),
+        };
+        assert_eq!(
+            error(&ALANINE.replace("M  V30 3 1 1 4 CFG=3", "M  V30 3 1 1 9 CFG=3")),
+            (19, "atom 9 out of range".to_owned())
+        );
+        assert_eq!(
+            error(&ALANINE.replace("M  V30 BEGIN ATOM", "M  V30 BEGIN ATOMS")),
+            (7, r#"expected "BEGIN ATOM""#.to_owned())
+        );
+        assert_eq!(
+            error(&ALANINE.replace("M  V30 CHG=-1", "M  V30 CHG=x")),
+            (14, r#"invalid number "x""#.to_owned())
+        );
+        assert_eq!(
+            error(&ALANINE.replace("M  V30 CHG=-1", "M  V30 CHG=100")),
+            (14, r#"invalid number "100""#.to_owned())
+        );
+        assert_eq!(
+            error(&ALANINE.replace("M  V30 CHG=-1", "M  V30 CHG=-1 VAL=-3")),
+            (14, r#"invalid number "-3""#.to_owned())
+        );
+        assert_eq!(
+            error(&ALANINE.replace("M  V30 CHG=-1", "M  V30 CHG=-1 VAL=15")),
+            (14, r#"invalid number "15""#.to_owned())
+        );
+    }
+}
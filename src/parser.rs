@@ -1,4 +1,5 @@
 use crate::{
+    cxsmiles::{self, Layer},
     errors::{Error, Result, SyntaxError},
     lexer::{Lexeme, Lexer},
     syntax::{
@@ -11,15 +12,20 @@ use rowan::{GreenNode, GreenNodeBuilder};
 
 /// Parser
 pub struct Parser<'a> {
+    input: &'a str,
     lexer: PeekNth<Lexer<'a>>,
     builder: GreenNodeBuilder<'static>,
+    /// Atoms parsed so far
+    atoms: usize,
 }
 
 impl<'a> Parser<'a> {
     pub fn new(input: &'a str) -> Self {
         Self {
+            input,
             lexer: peek_nth(Lexer::new(input)),
             builder: GreenNodeBuilder::new(),
+            atoms: 0,
         }
     }
 
@@ -72,15 +78,34 @@ impl<'a> Parser<'a> {
     pub fn parse(mut self) -> Result<Parse> {
         self.builder.start_node(ROOT.into());
         self.tree()?; // TREE
+        let layer = self.layer()?;
         if self.peek(0).is_some() {
             return Err(self.error(&[END_OF_STRING]));
         }
         self.builder.finish_node(); // ROOT
         Ok(Parse {
             green_node: self.builder.finish(),
+            layer,
         })
     }
 
+    /// CXSMILES layer, `|...|` after a space or tab
+    fn layer(&mut self) -> Result<Option<Layer>> {
+        let Some(lexeme) = self.lexer.peek() else {
+            return Ok(None);
+        };
+        let start = usize::from(lexeme.range.start());
+        let rest = &self.input[start..];
+        let text = rest.trim_start_matches([' ', '\t']);
+        if text.len() == rest.len() || !text.starts_with('|') {
+            return Ok(None);
+        }
+        let offset = self.input.len() - text.len();
+        let layer = cxsmiles::parse(text.trim_end(), offset, self.atoms)?;
+        self.lexer.by_ref().for_each(drop);
+        Ok(Some(layer))
+    }
+
     fn tree(&mut self) -> Result<()> {
         self.builder.start_node(TREE.into());
         self.node()?;
@@ -98,9 +123,11 @@ impl<'a> Parser<'a> {
                 self.indexed();
             } else if self.peek(0) == Some(LEFT_PAREN) {
                 self.parentheses()?;
-            } else {
+            } else if self.is_unindexed() {
                 self.unindexed()?;
                 break;
+            } else {
+                break;
             }
         }
         self.builder.finish_node(); // BRANCHES
@@ -143,6 +170,7 @@ impl<'a> Parser<'a> {
 
     /// Node
     fn node(&mut self) -> Result<()> {
+        self.atoms += 1;
         self.builder.start_node(NODE.into());
         match self.peek(0) {
             Some(LEFT_BRACKET) => self.brackets()?,
@@ -241,9 +269,15 @@ impl<'a> Parser<'a> {
 /// Parse
 pub struct Parse {
     green_node: GreenNode,
+    layer: Option<Layer>,
 }
 
 impl Parse {
+    /// CXSMILES layer
+    pub(crate) fn layer(&self) -> Option<&Layer> {
+        self.layer.as_ref()
+    }
+
     pub fn syntax(&self) -> SyntaxNode {
         SyntaxNode::new_root(self.green_node.clone())
     }
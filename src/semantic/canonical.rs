@@ -0,0 +1,444 @@
+use super::{Atom, Bond, Geometry, MoleculeGraph, Parity};
+use petgraph::{graph::NodeIndex, visit::EdgeRef};
+use std::{
+    cmp::max_by_key,
+    collections::{BTreeMap, BTreeSet},
+};
+
+impl MoleculeGraph {
+    /// Canonical rank of each atom
+    ///
+    /// Ranks are refined from the atom invariants by the ranks of the
+    /// neighbours and the stereo configurations. Remaining ties (symmetric
+    /// atoms) are broken by trying each tied atom in turn, the ranks giving
+    /// the smallest SMILES are kept. A tied atom that an automorphism found
+    /// on the way maps onto an already tried one is skipped.
+    pub fn canonical_ranks(&self) -> Vec<usize> {
+        let invariants: Vec<_> = self
+            .node_indices()
+            .map(|index| {
+                let atom = &self[index];
+                (
+                    self.neighbors(index).count(),
+                    atom.element,
+                    atom.isotope,
+                    atom.charge,
+                    self.hydrogens(index),
+                )
+            })
+            .collect();
+        let ranks = self.refine(ranks(&invariants), self.node_indices().collect());
+        let mut search = Search::default();
+        self.search(ranks, &mut Vec::new(), &mut search);
+        search.best.unwrap().ranks
+    }
+
+    /// Searches the ways to break the ties, the path holding the atoms
+    /// individualized so far
+    fn search(&self, ranks: Vec<usize>, path: &mut Vec<usize>, search: &mut Search) {
+        let Some(tied) = tie(&ranks) else {
+            return search.leaf(self, ranks);
+        };
+        let mut tried = Vec::new();
+        for first in 0..ranks.len() {
+            if ranks[first] != tied || search.equivalent(path, &tried, first) {
+                continue;
+            }
+            tried.push(first);
+            let mut ranks = ranks.clone();
+            let mut changed = Vec::new();
+            for (index, rank) in ranks.iter_mut().enumerate() {
+                if *rank == tied && index != first {
+                    *rank += 1;
+                    changed.push(NodeIndex::new(index));
+                }
+            }
+            path.push(first);
+            self.search(self.refine(ranks, changed), path, search);
+            path.pop();
+        }
+    }
+
+    /// Copy with the atoms in canonical order and the stereo markers
+    /// reassigned
+    ///
+    /// Its SMILES is the canonical SMILES.
+    pub fn canonical(&self) -> Self {
+        self.relabel(&self.canonical_ranks())
+    }
+
+    /// Copy with the atoms in rank order
+    fn relabel(&self, ranks: &[usize]) -> Self {
+        let mut order: Vec<_> = self.node_indices().collect();
+        order.sort_by_key(|index| ranks[index.index()]);
+        let mut positions = vec![NodeIndex::end(); order.len()];
+        for (position, index) in order.iter().enumerate() {
+            positions[index.index()] = NodeIndex::new(position);
+        }
+        let map = |index: NodeIndex| positions[index.index()];
+        let mut graph = Self::default();
+        for &index in &order {
+            graph.add_node(Atom {
+                parity: None,
+                ..self[index]
+            });
+        }
+        let mut edges: Vec<_> = self
+            .edge_indices()
+            .map(|edge| {
+                let (source, target) = self.edge_endpoints(edge).unwrap();
+                let (source, target) = (map(source), map(target));
+                let bond = match self[edge] {
+                    Bond::Up | Bond::Down => Bond::Single,
+                    bond => bond,
+                };
+                (source.min(target), source.max(target), bond)
+            })
+            .collect();
+        edges.sort();
+        for (source, target, bond) in edges {
+            graph.add_edge(source, target, bond);
+        }
+        graph.extension = self.extension.map(map);
+        for &index in &order {
+            let Some(parity) = self[index].parity else {
+                continue;
+            };
+            let mut reference: Vec<_> = self.neighbors(index).collect();
+            if self.hydrogens(index) > 0 {
+                reference.push(index);
+            }
+            reference.sort();
+            let reference: Vec<_> = reference.into_iter().map(map).collect();
+            graph.set_parity(map(index), &reference, Some(parity));
+        }
+        let mut geometries = Vec::new();
+        for edge in self.edge_indices() {
+            if self[edge] != Bond::Double {
+                continue;
+            }
+            let (a, b) = self.edge_endpoints(edge).unwrap();
+            let (a, b) = if map(a) < map(b) { (a, b) } else { (b, a) };
+            let reference = |center: NodeIndex, partner: NodeIndex| {
+                self.neighbors(center)
+                    .filter(|&neighbor| neighbor != partner)
+                    .min_by_key(|&neighbor| map(neighbor))
+            };
+            let (Some(x), Some(y)) = (reference(a, b), reference(b, a)) else {
+                continue;
+            };
+            if let Some(geometry) = self.geometry(x, a, b, y) {
+                geometries.push((map(a), map(b), map(x), map(y), geometry));
+            }
+        }
+        geometries.sort();
+        for (a, b, x, y, geometry) in geometries {
+            graph.set_geometry(x, a, b, y, geometry);
+        }
+        graph
+    }
+
+    /// Canonical SMILES
+    pub fn canonical_smiles(&self) -> String {
+        self.canonical().to_string()
+    }
+
+    /// Refines the ranks by the neighbour ranks and the stereo
+    /// configurations until stable, starting from the atoms with changed
+    /// ranks
+    ///
+    /// A rank is the position of the first atom of its cell in the rank
+    /// order. Only the atoms next to a changed rank are keyed again, the
+    /// largest part of a split cell keeping its rank.
+    fn refine(&self, mut ranks: Vec<usize>, mut changed: Vec<NodeIndex>) -> Vec<usize> {
+        let mut order: Vec<_> = self.node_indices().collect();
+        order.sort_by_key(|index| ranks[index.index()]);
+        let mut positions = vec![0; order.len()];
+        let mut sizes = vec![0; order.len()];
+        for (position, index) in order.iter().enumerate() {
+            positions[index.index()] = position;
+            sizes[ranks[index.index()]] += 1;
+        }
+        while !changed.is_empty() {
+            // The geometries depend on the neighbours of the double bond
+            // partners
+            let mut affected = BTreeSet::new();
+            for &index in &changed {
+                for neighbor in self.neighbors(index) {
+                    affected.insert(neighbor);
+                    for edge in self.edges(neighbor) {
+                        if *edge.weight() == Bond::Double {
+                            affected.insert(edge.target());
+                        }
+                    }
+                }
+            }
+            let mut cells = BTreeMap::<_, Vec<_>>::new();
+            for index in affected {
+                cells.entry(ranks[index.index()]).or_default().push(index);
+            }
+            let key = |index: NodeIndex| {
+                let mut neighbors: Vec<_> = self
+                    .edges(index)
+                    .map(|edge| (ranks[edge.target().index()], kind(*edge.weight())))
+                    .collect();
+                neighbors.sort();
+                (neighbors, self.stereo(index, &ranks))
+            };
+            let mut updates = Vec::new();
+            for (start, members) in cells {
+                let end = start + sizes[start];
+                if end - start < 2 {
+                    continue;
+                }
+                // Move the affected atoms to the end of the cell, the others
+                // keeping the key they were split by
+                let base = end - members.len();
+                for (tail, &index) in (base..end).rev().zip(&members) {
+                    let position = positions[index.index()];
+                    order.swap(position, tail);
+                    positions[order[position].index()] = position;
+                    positions[index.index()] = tail;
+                }
+                let kept = (base > start).then(|| key(order[start]));
+                let mut keys: Vec<_> = members.iter().map(|&index| (key(index), index)).collect();
+                keys.sort();
+                let mut groups: Vec<(_, Vec<_>)> = Vec::new();
+                for (key, index) in keys {
+                    match groups.last_mut() {
+                        Some((last, group)) if *last == key => group.push(index),
+                        _ => groups.push((key, vec![index])),
+                    }
+                }
+                if let Some(kept) = &kept {
+                    if let Err(position) = groups.binary_search_by(|(key, _)| key.cmp(kept)) {
+                        groups.insert(position, (kept.clone(), Vec::new()));
+                    }
+                }
+                let size = |(key, group): &(_, Vec<_>)| {
+                    group.len()
+                        + if Some(key) == kept.as_ref() {
+                            base - start
+                        } else {
+                            0
+                        }
+                };
+                // The others follow in key order
+                let largest = (0..groups.len())
+                    .rev()
+                    .max_by_key(|&group| size(&groups[group]))
+                    .unwrap();
+                sizes[start] = size(&groups[largest]);
+                let (key, group) = groups.remove(largest);
+                let mut position = if Some(&key) == kept.as_ref() {
+                    base
+                } else {
+                    for (other, group) in &mut groups {
+                        if Some(&*other) == kept.as_ref() {
+                            group.extend_from_slice(&order[start..base]);
+                        }
+                    }
+                    start
+                };
+                for index in group {
+                    order[position] = index;
+                    positions[index.index()] = position;
+                    position += 1;
+                }
+                for (_, group) in groups {
+                    let rank = position;
+                    sizes[rank] = group.len();
+                    for index in group {
+                        order[position] = index;
+                        positions[index.index()] = position;
+                        updates.push((index, rank));
+                        position += 1;
+                    }
+                }
+            }
+            changed.clear();
+            for (index, rank) in updates {
+                ranks[index.index()] = rank;
+                changed.push(index);
+            }
+        }
+        ranks
+    }
+
+    /// Stereo configurations of the atom relative to the ranks, the parity
+    /// and the geometries of its double bonds
+    ///
+    /// A configuration counts once its reference neighbours are told apart
+    /// by the ranks.
+    fn stereo(&self, index: NodeIndex, ranks: &[usize]) -> (Option<bool>, Vec<bool>) {
+        // The implicit hydrogen ranks first
+        let rank = |node: NodeIndex| (node != index).then(|| ranks[node.index()]);
+        let parity = self[index].parity.and_then(|_| {
+            let mut order: Vec<_> = self.neighbors(index).collect();
+            if self.hydrogens(index) > 0 {
+                order.push(index);
+            }
+            order.sort_by_key(|&node| rank(node));
+            let distinct = order.windows(2).all(|pair| rank(pair[0]) != rank(pair[1]));
+            let parity = self.parity(index, &order)?;
+            distinct.then_some(parity == Parity::Clockwise)
+        });
+        let reference = |center: NodeIndex, partner: NodeIndex| {
+            let neighbors: Vec<_> = self
+                .neighbors(center)
+                .filter(|&neighbor| neighbor != partner)
+                .collect();
+            match neighbors[..] {
+                [neighbor] => Some(neighbor),
+                [first, second] if ranks[first.index()] != ranks[second.index()] => {
+                    Some(max_by_key(first, second, |node| ranks[node.index()]))
+                }
+                _ => None,
+            }
+        };
+        let mut geometries: Vec<_> = self
+            .edges(index)
+            .filter(|edge| *edge.weight() == Bond::Double)
+            .filter_map(|edge| {
+                let partner = edge.target();
+                let (x, y) = (reference(index, partner)?, reference(partner, index)?);
+                let geometry = self.geometry(x, index, partner, y)?;
+                Some(geometry == Geometry::Z)
+            })
+            .collect();
+        geometries.sort();
+        (parity, geometries)
+    }
+}
+
+/// Leaves of the tie breaking search and the automorphisms they reveal
+#[derive(Default)]
+struct Search {
+    first: Option<Leaf>,
+    /// Leaf with the smallest SMILES
+    best: Option<Leaf>,
+    /// Image of each atom
+    automorphisms: Vec<Vec<usize>>,
+}
+
+#[derive(Clone)]
+struct Leaf {
+    smiles: String,
+    /// Graph relabelled by the ranks
+    graph: MoleculeGraph,
+    ranks: Vec<usize>,
+}
+
+impl Search {
+    /// Keeps the leaf if its SMILES is the smallest so far, or records the
+    /// automorphism if it relabels the graph the same as the first or the
+    /// best leaf
+    fn leaf(&mut self, molecule: &MoleculeGraph, ranks: Vec<usize>) {
+        let graph = molecule.relabel(&ranks);
+        for leaf in [&self.first, &self.best].into_iter().flatten() {
+            if same(&leaf.graph, &graph) {
+                // The atom of each rank maps to the atom of the same rank
+                let mut atoms = vec![0; ranks.len()];
+                for (index, &rank) in ranks.iter().enumerate() {
+                    atoms[rank] = index;
+                }
+                let automorphism = leaf.ranks.iter().map(|&rank| atoms[rank]).collect();
+                self.automorphisms.push(automorphism);
+                return;
+            }
+        }
+        let leaf = Leaf {
+            smiles: graph.to_string(),
+            graph,
+            ranks,
+        };
+        if self
+            .best
+            .as_ref()
+            .is_none_or(|best| leaf.smiles < best.smiles)
+        {
+            self.best = Some(leaf.clone());
+        }
+        if self.first.is_none() {
+            self.first = Some(leaf);
+        }
+    }
+
+    /// Is the atom mapped onto a tried one by the automorphisms fixing the
+    /// path
+    fn equivalent(&self, path: &[usize], tried: &[usize], atom: usize) -> bool {
+        let automorphisms: Vec<_> = self
+            .automorphisms
+            .iter()
+            .filter(|automorphism| path.iter().all(|&index| automorphism[index] == index))
+            .collect();
+        let mut orbit = BTreeSet::from([atom]);
+        let mut stack = vec![atom];
+        while let Some(index) = stack.pop() {
+            for automorphism in &automorphisms {
+                if orbit.insert(automorphism[index]) {
+                    stack.push(automorphism[index]);
+                }
+            }
+        }
+        tried.iter().any(|index| orbit.contains(index))
+    }
+}
+
+/// Are the relabelled graphs identical, the coordinates aside
+fn same(first: &MoleculeGraph, second: &MoleculeGraph) -> bool {
+    let atoms = |graph: &MoleculeGraph| -> Vec<_> {
+        graph
+            .node_weights()
+            .map(|atom| Atom {
+                coordinates: None,
+                ..*atom
+            })
+            .collect()
+    };
+    let bonds = |graph: &MoleculeGraph| -> Vec<_> {
+        graph
+            .edge_references()
+            .map(|edge| (edge.source(), edge.target(), *edge.weight()))
+            .collect()
+    };
+    atoms(first) == atoms(second)
+        && bonds(first) == bonds(second)
+        && first.extension == second.extension
+}
+
+/// Bond kind, directional bonds being single
+fn kind(bond: Bond) -> u8 {
+    match bond {
+        Bond::Single | Bond::Up | Bond::Down => 1,
+        Bond::Double => 2,
+        Bond::Triple => 3,
+        Bond::Quadruple => 4,
+        Bond::Aromatic => 5,
+    }
+}
+
+/// Rank of each key, the count of the smaller keys
+fn ranks<T: Ord>(keys: &[T]) -> Vec<usize> {
+    let mut order: Vec<_> = (0..keys.len()).collect();
+    order.sort_by_key(|&index| &keys[index]);
+    let mut ranks = vec![0; keys.len()];
+    for (position, &index) in order.iter().enumerate() {
+        ranks[index] = if position > 0 && keys[index] == keys[order[position - 1]] {
+            ranks[order[position - 1]]
+        } else {
+            position
+        };
+    }
+    ranks
+}
+
+/// Lowest shared rank
+fn tie(ranks: &[usize]) -> Option<usize> {
+    let mut sorted = ranks.to_vec();
+    sorted.sort();
+    sorted
+        .windows(2)
+        .find(|window| window[0] == window[1])
+        .map(|window| window[0])
+}
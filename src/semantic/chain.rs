@@ -0,0 +1,374 @@
+use super::{Bond, Element, Geometry, MoleculeGraph};
+use petgraph::{
+    graph::{EdgeIndex, NodeIndex},
+    visit::EdgeRef,
+};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::VecDeque,
+    ops::Deref,
+};
+
+/// Locant notation
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Notation {
+    /// Numbered from the carboxyl carbon (`Δ1`)
+    #[default]
+    Delta,
+    /// Numbered from the methyl end (`ω1`)
+    Omega,
+}
+
+/// Carbon chain
+///
+/// Atoms are ordered from the first carbon (`Δ1`) to the last one (`ω1`).
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Chain(Vec<NodeIndex>);
+
+impl Chain {
+    /// Locant of the chain atom
+    pub fn locant(&self, index: NodeIndex, notation: Notation) -> Option<usize> {
+        let position = self.iter().position(|&atom| atom == index)?;
+        Some(match notation {
+            Notation::Delta => position + 1,
+            Notation::Omega => self.len() - position,
+        })
+    }
+
+    /// Chain atom at the locant
+    pub fn atom(&self, locant: usize, notation: Notation) -> Option<NodeIndex> {
+        let position = match notation {
+            Notation::Delta => locant.checked_sub(1)?,
+            Notation::Omega => self.len().checked_sub(locant)?,
+        };
+        self.get(position).copied()
+    }
+
+    /// Locant of the bond between two adjacent chain atoms
+    ///
+    /// A bond is numbered by its lower numbered atom.
+    pub fn bond_locant(&self, a: NodeIndex, b: NodeIndex, notation: Notation) -> Option<usize> {
+        let a = self.locant(a, notation)?;
+        let b = self.locant(b, notation)?;
+        (a.abs_diff(b) == 1).then_some(a.min(b))
+    }
+
+    /// Locants of the chain atoms
+    pub fn locants(&self, notation: Notation) -> impl Iterator<Item = (usize, NodeIndex)> + '_ {
+        self.iter()
+            .enumerate()
+            .map(move |(position, &index)| match notation {
+                Notation::Delta => (position + 1, index),
+                Notation::Omega => (self.len() - position, index),
+            })
+    }
+
+    /// Unsaturated bonds of the chain by locant
+    pub fn unsaturations(
+        &self,
+        graph: &MoleculeGraph,
+        notation: Notation,
+    ) -> Vec<(usize, EdgeIndex)> {
+        let mut unsaturations: Vec<_> = self
+            .windows(2)
+            .filter_map(|pair| {
+                let edge = graph.find_edge(pair[0], pair[1])?;
+                if !matches!(graph[edge], Bond::Double | Bond::Triple) {
+                    return None;
+                }
+                Some((self.bond_locant(pair[0], pair[1], notation)?, edge))
+            })
+            .collect();
+        unsaturations.sort();
+        unsaturations
+    }
+
+    /// Unsaturated bond positions of the chain
+    ///
+    /// Covers the bonds between chain carbons (`C=C`, `C#C`) and the bonds
+    /// from chain carbons to off chain atoms (`C=O`). The geometry of a double bond
+    /// between chain carbons refers to the chain itself.
+    pub fn positions(&self, graph: &MoleculeGraph, notation: Notation) -> Vec<Position> {
+        let mut positions = Vec::new();
+        for (position, &from) in self.iter().enumerate() {
+            for edge in graph.edges(from) {
+                let bond = *edge.weight();
+                if !matches!(bond, Bond::Double | Bond::Triple) {
+                    continue;
+                }
+                let to = edge.target();
+                let Some(element) = graph[to].element else {
+                    continue;
+                };
+                let locant = match self.locant(to, notation) {
+                    // Chain bonds are visited from their first atom
+                    Some(_) if self.get(position + 1) != Some(&to) => continue,
+                    Some(_) => self.bond_locant(from, to, notation).unwrap(),
+                    None => self.locant(from, notation).unwrap(),
+                };
+                let geometry = match (position.checked_sub(1), self.get(position + 2)) {
+                    (Some(before), Some(&after))
+                        if bond == Bond::Double && self.get(position + 1) == Some(&to) =>
+                    {
+                        graph.geometry(self[before], from, to, after)
+                    }
+                    _ => None,
+                };
+                positions.push(Position {
+                    locant,
+                    edge: edge.id(),
+                    bond,
+                    element,
+                    geometry,
+                });
+            }
+        }
+        positions.sort_by_key(|position| (position.locant, position.element));
+        positions
+    }
+}
+
+impl Deref for Chain {
+    type Target = [NodeIndex];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Chain> for Vec<NodeIndex> {
+    fn from(value: Chain) -> Self {
+        value.0
+    }
+}
+
+/// Chain tie breaking rule
+///
+/// Rules are applied in order to chains of the same length.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Rule {
+    /// Prefer the chain with more unsaturated bonds
+    Unsaturations,
+    /// Prefer the chain with lower unsaturation locants
+    Locants,
+}
+
+impl Rule {
+    pub const DEFAULT: &'static [Rule] = &[Rule::Unsaturations, Rule::Locants];
+}
+
+/// Unsaturated bond position
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Position {
+    /// Locant of the bond, or of the chain carbon for an off chain partner
+    pub locant: usize,
+    pub edge: EdgeIndex,
+    /// Double or triple bond
+    pub bond: Bond,
+    /// Element of the partner atom, `C` for `C=C` and `O` for `C=O`
+    pub element: Element,
+    pub geometry: Option<Geometry>,
+}
+
+impl MoleculeGraph {
+    /// Unsaturated bond positions on the principal chain
+    ///
+    /// See [`Chain::positions`].
+    pub fn positions(&self, notation: Notation) -> Vec<Position> {
+        self.principal_chain()
+            .map_or_else(Vec::new, |chain| chain.positions(self, notation))
+    }
+
+    /// Principal carbon chain
+    ///
+    /// The chain starts at a carboxyl (acid, ester or carboxylate) carbon if
+    /// there is one, otherwise it is the longest carbon chain numbered from
+    /// the end giving the lowest locants to the unsaturated bonds. Ties are
+    /// resolved by the [default rules](Rule::DEFAULT).
+    pub fn principal_chain(&self) -> Option<Chain> {
+        self.node_indices()
+            .filter(|&index| self.is_carboxyl(index))
+            .map(|start| self.chain(start))
+            .max_by(|a, b| self.compare(a, b, Rule::DEFAULT))
+            .or_else(|| self.longest_chain(Rule::DEFAULT))
+    }
+
+    /// Longest carbon chain starting at the carbon
+    ///
+    /// Ties are resolved by the [default rules](Rule::DEFAULT).
+    pub fn chain(&self, start: NodeIndex) -> Chain {
+        self.chain_by(start, Rule::DEFAULT)
+    }
+
+    /// Longest carbon chain starting at the carbon, ties resolved by the rules
+    pub fn chain_by(&self, start: NodeIndex, rules: &[Rule]) -> Chain {
+        let mut path = vec![start];
+        Chain(self.longest(start, &mut path, rules))
+    }
+
+    /// Longest carbon chain of the molecule, ties resolved by the rules
+    ///
+    /// For an acyclic carbon skeleton only the chains from the ends of its
+    /// diameter are searched, the branches at each carbon compared by the
+    /// rules. A skeleton with rings falls back to an exhaustive search of the
+    /// simple paths.
+    pub fn longest_chain(&self, rules: &[Rule]) -> Option<Chain> {
+        let mut best: Option<Chain> = None;
+        for component in self.carbon_components() {
+            let edges = component
+                .iter()
+                .flat_map(|&index| self.neighbors(index).filter(|&to| self.is_carbon(to)))
+                .count()
+                / 2;
+            let starts = if edges + 1 == component.len() {
+                self.diameter_ends(component[0])
+            } else {
+                component
+            };
+            for start in starts {
+                let chain = self.chain_by(start, rules);
+                if best
+                    .as_ref()
+                    .is_none_or(|best| self.compare(&chain, best, rules).is_gt())
+                {
+                    best = Some(chain);
+                }
+            }
+        }
+        best
+    }
+
+    pub fn the_longest_carbon_chain(&self) -> Vec<NodeIndex> {
+        self.longest_chain(Rule::DEFAULT)
+            .map(Into::into)
+            .unwrap_or_default()
+    }
+
+    /// Is the atom a carboxyl carbon (`C(=O)O`)
+    pub fn is_carboxyl(&self, index: NodeIndex) -> bool {
+        let oxygen = |bond: Bond| {
+            self.edges(index).any(|edge| {
+                *edge.weight() == bond && self[edge.target()].element == Some(Element::O)
+            })
+        };
+        self[index].element == Some(Element::C) && oxygen(Bond::Double) && oxygen(Bond::Single)
+    }
+
+    fn is_carbon(&self, index: NodeIndex) -> bool {
+        matches!(self[index].element, None | Some(Element::C))
+    }
+
+    fn longest(
+        &self,
+        from: NodeIndex,
+        path: &mut Vec<NodeIndex>,
+        rules: &[Rule],
+    ) -> Vec<NodeIndex> {
+        let mut best: Option<Vec<NodeIndex>> = None;
+        let neighbors: Vec<_> = self.neighbors(from).collect();
+        for to in neighbors {
+            if !self.is_carbon(to) || path.contains(&to) {
+                continue;
+            }
+            path.push(to);
+            let mut chain = self.longest(to, path, rules);
+            path.pop();
+            chain.insert(0, from);
+            if best
+                .as_ref()
+                .is_none_or(|best| self.compare(&chain, best, rules).is_gt())
+            {
+                best = Some(chain);
+            }
+        }
+        best.unwrap_or_else(|| vec![from])
+    }
+
+    /// Connected components of the carbon skeleton
+    fn carbon_components(&self) -> Vec<Vec<NodeIndex>> {
+        let mut visited = vec![false; self.node_count()];
+        let mut components = Vec::new();
+        for index in self.node_indices() {
+            if visited[index.index()] || !self.is_carbon(index) {
+                continue;
+            }
+            let distances = self.carbon_distances(index);
+            let component: Vec<_> = self
+                .node_indices()
+                .filter(|index| distances[index.index()].is_some())
+                .collect();
+            for index in &component {
+                visited[index.index()] = true;
+            }
+            components.push(component);
+        }
+        components
+    }
+
+    /// Ends of all the longest paths of an acyclic carbon skeleton
+    ///
+    /// In a tree the eccentricity of an atom is its greatest distance to
+    /// either end of any diameter.
+    fn diameter_ends(&self, index: NodeIndex) -> Vec<NodeIndex> {
+        let farthest = |distances: &[Option<usize>]| {
+            let (index, distance) = distances
+                .iter()
+                .enumerate()
+                .filter_map(|(index, distance)| Some((index, (*distance)?)))
+                .max_by_key(|&(index, distance)| (distance, Reverse(index)))
+                .unwrap();
+            (NodeIndex::new(index), distance)
+        };
+        let (u, _) = farthest(&self.carbon_distances(index));
+        let from_u = self.carbon_distances(u);
+        let (v, diameter) = farthest(&from_u);
+        let from_v = self.carbon_distances(v);
+        self.node_indices()
+            .filter(|index| {
+                let (Some(u), Some(v)) = (from_u[index.index()], from_v[index.index()]) else {
+                    return false;
+                };
+                u.max(v) == diameter
+            })
+            .collect()
+    }
+
+    /// Breadth first distances through carbons
+    fn carbon_distances(&self, start: NodeIndex) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.node_count()];
+        distances[start.index()] = Some(0);
+        let mut queue = VecDeque::from([start]);
+        while let Some(from) = queue.pop_front() {
+            let distance = distances[from.index()].unwrap() + 1;
+            for to in self.neighbors(from) {
+                if self.is_carbon(to) && distances[to.index()].is_none() {
+                    distances[to.index()] = Some(distance);
+                    queue.push_back(to);
+                }
+            }
+        }
+        distances
+    }
+
+    /// Compares chains by length, then by the rules
+    fn compare(&self, a: &[NodeIndex], b: &[NodeIndex], rules: &[Rule]) -> Ordering {
+        let locants = |chain: &[NodeIndex]| -> Vec<usize> {
+            chain
+                .windows(2)
+                .enumerate()
+                .filter(|(_, pair)| {
+                    self.find_edge(pair[0], pair[1])
+                        .is_some_and(|edge| matches!(self[edge], Bond::Double | Bond::Triple))
+                })
+                .map(|(position, _)| position + 1)
+                .collect()
+        };
+        let (x, y) = (locants(a), locants(b));
+        rules.iter().fold(a.len().cmp(&b.len()), |ordering, rule| {
+            ordering.then_with(|| match rule {
+                Rule::Unsaturations => x.len().cmp(&y.len()),
+                Rule::Locants => y.cmp(&x),
+            })
+        })
+    }
+}
@@ -15,4 +15,6 @@ pub enum Error {
     NodeNotFound,
     #[error("tree not found")]
     TreeNotFound,
+    #[error("unclosed ring bond {0}")]
+    UnclosedRing(u8),
 }
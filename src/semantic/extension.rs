@@ -0,0 +1,89 @@
+use petgraph::graph::NodeIndex;
+use std::collections::BTreeMap;
+
+/// Molecule information beyond the atoms and bonds, the CXSMILES layer
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Extension {
+    /// Atom labels, `_AP1` attachment points, `_R1` R groups and pseudo atom
+    /// names
+    pub labels: BTreeMap<NodeIndex, String>,
+    pub link_nodes: Vec<LinkNode>,
+    pub sgroups: Vec<Sgroup>,
+}
+
+impl Extension {
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty() && self.link_nodes.is_empty() && self.sgroups.is_empty()
+    }
+
+    /// Copy with the atoms mapped
+    pub(crate) fn map(&self, map: impl Fn(NodeIndex) -> NodeIndex) -> Self {
+        Self {
+            labels: self
+                .labels
+                .iter()
+                .map(|(&index, label)| (map(index), label.clone()))
+                .collect(),
+            link_nodes: self
+                .link_nodes
+                .iter()
+                .map(|link_node| LinkNode {
+                    atom: map(link_node.atom),
+                    outer: link_node
+                        .outer
+                        .map(|(first, second)| (map(first), map(second))),
+                    ..*link_node
+                })
+                .collect(),
+            sgroups: self
+                .sgroups
+                .iter()
+                .map(|sgroup| Sgroup {
+                    atoms: sgroup.atoms.iter().copied().map(&map).collect(),
+                    ..sgroup.clone()
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Link node, the atom repeated between `minimum` and `maximum` times
+///
+/// The repeated atom is bonded to the `outer` atoms when it has more than
+/// two neighbours.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LinkNode {
+    pub atom: NodeIndex,
+    pub minimum: u16,
+    pub maximum: u16,
+    pub outer: Option<(NodeIndex, NodeIndex)>,
+}
+
+/// Sgroup, a polymer unit of the atoms
+///
+/// The kind is the CTfile Sgroup type in lower case (`n` for a source
+/// based repeating unit, `co` copolymer, `mon` monomer, ...), the subscript
+/// the repeat count label and the superscript the head to tail connectivity
+/// (`ht`, `hh`, `eu`).
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Sgroup {
+    pub kind: String,
+    pub atoms: Vec<NodeIndex>,
+    pub subscript: String,
+    pub superscript: String,
+}
+
+/// Radical
+///
+/// Unpaired electrons of an atom by its valence deficit and spin
+/// multiplicity.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Radical {
+    Monovalent,
+    Divalent,
+    DivalentSinglet,
+    DivalentTriplet,
+    Trivalent,
+    TrivalentDoublet,
+    TrivalentQuartet,
+}
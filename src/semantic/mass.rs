@@ -0,0 +1,126 @@
+use super::{Element, MoleculeGraph};
+
+impl Element {
+    /// Standard atomic weight
+    pub fn average_mass(&self) -> Option<f64> {
+        Some(match self {
+            Self::H => 1.008,
+            Self::B => 10.81,
+            Self::C => 12.011,
+            Self::N => 14.007,
+            Self::O => 15.999,
+            Self::F => 18.998403163,
+            Self::Na => 22.98976928,
+            Self::Mg => 24.305,
+            Self::P => 30.973761998,
+            Self::S => 32.06,
+            Self::Cl => 35.45,
+            Self::K => 39.0983,
+            Self::Ca => 40.078,
+            Self::Br => 79.904,
+            Self::I => 126.90447,
+            _ => return None,
+        })
+    }
+
+    /// Mass of the most abundant isotope
+    pub fn monoisotopic_mass(&self) -> Option<f64> {
+        Some(match self {
+            Self::H => 1.00782503207,
+            Self::B => 11.0093054,
+            Self::C => 12.0,
+            Self::N => 14.0030740048,
+            Self::O => 15.99491461956,
+            Self::F => 18.99840322,
+            Self::Na => 22.9897692809,
+            Self::Mg => 23.985041700,
+            Self::P => 30.97376163,
+            Self::S => 31.97207100,
+            Self::Cl => 34.96885268,
+            Self::K => 38.96370668,
+            Self::Ca => 39.96259098,
+            Self::Br => 78.9183371,
+            Self::I => 126.904473,
+            _ => return None,
+        })
+    }
+
+    /// Mass of the nuclide of the given mass number
+    pub fn isotope_mass(&self, mass_number: u16) -> Option<f64> {
+        Some(match (self, mass_number) {
+            (Self::H, 1) => 1.00782503207,
+            (Self::H, 2) => 2.01410177812,
+            (Self::H, 3) => 3.01604928132,
+            (Self::B, 10) => 10.01293695,
+            (Self::B, 11) => 11.0093054,
+            (Self::C, 12) => 12.0,
+            (Self::C, 13) => 13.00335483507,
+            (Self::C, 14) => 14.0032419884,
+            (Self::N, 14) => 14.0030740048,
+            (Self::N, 15) => 15.0001088982,
+            (Self::O, 16) => 15.99491461956,
+            (Self::O, 17) => 16.9991317565,
+            (Self::O, 18) => 17.99915961286,
+            (Self::F, 19) => 18.99840322,
+            (Self::Na, 23) => 22.9897692809,
+            (Self::Mg, 24) => 23.985041700,
+            (Self::Mg, 25) => 24.98583698,
+            (Self::Mg, 26) => 25.98259297,
+            (Self::P, 31) => 30.97376163,
+            (Self::P, 32) => 31.97390764,
+            (Self::S, 32) => 31.97207100,
+            (Self::S, 33) => 32.97145876,
+            (Self::S, 34) => 33.96786690,
+            (Self::S, 36) => 35.96708076,
+            (Self::Cl, 35) => 34.96885268,
+            (Self::Cl, 37) => 36.96590259,
+            (Self::K, 39) => 38.96370668,
+            (Self::K, 40) => 39.96399848,
+            (Self::K, 41) => 40.96182576,
+            (Self::Ca, 40) => 39.96259098,
+            (Self::Ca, 42) => 41.95861801,
+            (Self::Ca, 43) => 42.9587666,
+            (Self::Ca, 44) => 43.9554818,
+            (Self::Ca, 46) => 45.9536926,
+            (Self::Ca, 48) => 47.952534,
+            (Self::Br, 79) => 78.9183371,
+            (Self::Br, 81) => 80.9162906,
+            (Self::I, 125) => 124.9046302,
+            (Self::I, 127) => 126.904473,
+            (Self::I, 131) => 130.9061246,
+            _ => return None,
+        })
+    }
+}
+
+impl MoleculeGraph {
+    /// Molar mass (g/mol)
+    ///
+    /// `None` if an element has no tabulated mass. Labelled atoms are
+    /// counted with the mass of their nuclide, `None` if it has none
+    /// tabulated.
+    pub fn molar_mass(&self) -> Option<f64> {
+        self.mass(Element::average_mass)
+    }
+
+    /// Monoisotopic mass (Da), electrons neglected
+    ///
+    /// See [`molar_mass`](Self::molar_mass).
+    pub fn monoisotopic_mass(&self) -> Option<f64> {
+        self.mass(Element::monoisotopic_mass)
+    }
+
+    fn mass(&self, mass: fn(&Element) -> Option<f64>) -> Option<f64> {
+        let hydrogen = mass(&Element::H)?;
+        let mut sum = 0.0;
+        for index in self.node_indices() {
+            let atom = &self[index];
+            sum += match atom.isotope {
+                Some(isotope) => atom.element?.isotope_mass(isotope)?,
+                None => mass(&atom.element?)?,
+            };
+            sum += self.hydrogens(index) as f64 * hydrogen;
+        }
+        Some(sum)
+    }
+}
@@ -1,26 +1,39 @@
-pub use self::error::Error;
+pub use self::{
+    chain::{Chain, Notation, Position, Rule},
+    error::Error,
+    extension::{Extension, LinkNode, Radical, Sgroup},
+    query::AtomList,
+    stereo::{Geometry, StereoGroup},
+};
 
 use self::error::Result;
-use crate::syntax::{
-    ast::{Branch, Edge, Node, Root, SyntaxNodeExt, Tree},
-    SyntaxKind::*,
+use crate::{
+    errors,
+    parser::Parser,
+    syntax::{
+        ast::{Branch, Edge, Node, Root, SyntaxNodeExt, Tree},
+        SyntaxKind::*,
+    },
 };
-use itertools::Itertools;
 use petgraph::{
-    algo::astar,
     graph::{EdgeIndex, EdgeReference, NodeIndex},
-    visit::{EdgeFiltered, EdgeRef, FilterEdge, GraphBase, IntoNodeIdentifiers, NodeFiltered},
+    visit::{EdgeFiltered, EdgeRef, NodeFiltered},
     Graph, Undirected,
 };
 use smol_str::ToSmolStr;
 use std::{
-    collections::BTreeSet,
-    ops::{Bound, Deref, DerefMut},
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    ops::{Deref, DerefMut},
+    str::FromStr,
 };
 
 // Molecule graph
 #[derive(Clone, Debug, Default)]
-pub struct MoleculeGraph(Graph<Atom, Bond, Undirected>);
+pub struct MoleculeGraph {
+    graph: Graph<Atom, Bond, Undirected>,
+    pub extension: Extension,
+}
 
 impl MoleculeGraph {
     pub fn carbons(
@@ -33,8 +46,10 @@ impl MoleculeGraph {
 
     pub fn unsaturated<'a>(
         &'a self,
-    ) -> EdgeFiltered<&Graph<Atom, Bond, Undirected>, impl Fn(EdgeReference<'a, Bond>) -> bool + '_>
-    {
+    ) -> EdgeFiltered<
+        &'a Graph<Atom, Bond, Undirected>,
+        impl Fn(EdgeReference<'a, Bond>) -> bool + 'a,
+    > {
         EdgeFiltered::from_fn(self, |edge| {
             matches!(edge.weight(), Bond::Double | Bond::Triple)
                 && matches!(self[edge.source()].element, None | Some(Element::C))
@@ -42,23 +57,28 @@ impl MoleculeGraph {
         })
     }
 
-    pub fn the_longest_carbon_chain(&self) -> Vec<NodeIndex> {
-        let carbons = self.carbons();
-        let paths = carbons
-            .node_identifiers()
-            .permutations(2)
-            .filter_map(|endpoints| {
-                astar(
-                    &carbons,
-                    endpoints[0],
-                    |finish| finish == endpoints[1],
-                    |_| 1,
-                    |_| 0,
-                )
-            });
-        paths.max_by_key(|&(cost, _)| cost).unwrap_or_default().1
+    /// Hydrogens attached to the atom, explicit or implicit
+    ///
+    /// Implicit hydrogens of the organic subset are derived from the lowest
+    /// normal valence that is not exceeded by the bonds of the atom.
+    pub fn hydrogens(&self, index: NodeIndex) -> u8 {
+        self[index]
+            .hydrogens
+            .unwrap_or_else(|| self.implicit_hydrogens(index))
+    }
+
+    /// Hydrogens the atom would have if it were written without brackets
+    pub(crate) fn implicit_hydrogens(&self, index: NodeIndex) -> u8 {
+        let valence: u8 = self.edges(index).map(|edge| edge.weight().valence()).sum();
+        self[index]
+            .element
+            .map_or(&[][..], |element| element.valences())
+            .iter()
+            .find(|&&normal| normal >= valence)
+            .map_or(0, |normal| normal - valence)
     }
 
+    #[allow(dead_code)]
     fn hydrogen_filling(&mut self) {
         for index in self.node_indices() {
             let atom = self.node_weight(index).unwrap();
@@ -84,6 +104,7 @@ impl MoleculeGraph {
         }
     }
 
+    #[allow(dead_code)]
     fn hydrogen(&mut self, from: NodeIndex) -> EdgeIndex {
         let to = self.add_node(Atom {
             element: Some(Element::H),
@@ -97,13 +118,27 @@ impl Deref for MoleculeGraph {
     type Target = Graph<Atom, Bond, Undirected>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.graph
     }
 }
 
 impl DerefMut for MoleculeGraph {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.graph
+    }
+}
+
+impl FromStr for MoleculeGraph {
+    type Err = errors::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = Parser::new(s).parse()?;
+        let root = parse.syntax().cast::<Root>().ok_or(Error::TreeNotFound)?;
+        let mut graph: Self = root.try_into()?;
+        if let Some(layer) = parse.layer() {
+            layer.clone().apply(&mut graph);
+        }
+        Ok(graph)
     }
 }
 
@@ -111,23 +146,69 @@ impl TryFrom<Root> for MoleculeGraph {
     type Error = Error;
 
     fn try_from(value: Root) -> Result<Self, Self::Error> {
-        let mut graph = MoleculeGraph(Graph::new_undirected());
-        walk(&mut graph, &value.tree().ok_or(Error::TreeNotFound)?)?;
+        let mut graph = MoleculeGraph::default();
+        let mut rings = BTreeMap::new();
+        let mut written = Vec::new();
+        let tree = value.tree().ok_or(Error::TreeNotFound)?;
+        walk(&mut graph, &mut rings, &mut written, &tree, None)?;
+        if let Some(&index) = rings.keys().next() {
+            return Err(Error::UnclosedRing(index));
+        }
+        // Parities refer to the neighbours in ascending index order
+        for index in graph.node_indices() {
+            let parity = graph[index].parity;
+            graph.set_parity(index, &written[index.index()], parity);
+        }
         return Ok(graph);
 
-        fn walk(graph: &mut MoleculeGraph, tree: &Tree) -> Result<NodeIndex> {
+        /// Ring bonds opened and not yet closed by their index
+        type Rings = BTreeMap<u8, (NodeIndex, Option<Bond>, usize)>;
+
+        /// Neighbours of each atom in the written order, the atom itself
+        /// standing for its implicit hydrogen
+        type Written = Vec<Vec<NodeIndex>>;
+
+        fn walk(
+            graph: &mut MoleculeGraph,
+            rings: &mut Rings,
+            written: &mut Written,
+            tree: &Tree,
+            parent: Option<NodeIndex>,
+        ) -> Result<NodeIndex> {
             let node = tree.node().ok_or(Error::NodeNotFound)?;
-            let from = graph.add_node(node.try_into()?);
+            let atom: Atom = node.try_into()?;
+            let from = graph.add_node(atom);
+            let hydrogen = atom.hydrogens.is_some_and(|hydrogens| hydrogens > 0);
+            written.push(parent.into_iter().chain(hydrogen.then_some(from)).collect());
             for branch in tree.branches() {
                 match branch {
                     Branch::Indexed(indexed) => {
-                        // TODO
+                        let index = indexed.index().ok_or(Error::NodeNotFound)?;
+                        let index = index.to_string().parse()?;
+                        let edge = indexed.edge().map(Bond::from);
+                        match rings.remove(&index) {
+                            Some((to, bond, position)) => {
+                                // The bond direction follows the side it is written on
+                                match bond {
+                                    Some(bond) => graph.add_edge(to, from, bond),
+                                    None => graph.add_edge(from, to, edge.unwrap_or_default()),
+                                };
+                                written[to.index()][position] = from;
+                                written[from.index()].push(to);
+                            }
+                            None => {
+                                let position = written[from.index()].len();
+                                written[from.index()].push(NodeIndex::end());
+                                rings.insert(index, (from, edge, position));
+                            }
+                        }
                     }
                     Branch::Unindexed(unindexed) => {
                         let tree = unindexed.tree().ok_or(Error::TreeNotFound)?;
-                        let to = walk(graph, &tree)?;
+                        let to = walk(graph, rings, written, &tree, Some(from))?;
                         let edge = unindexed.edge().map_or_else(Default::default, Into::into);
                         graph.add_edge(from, to, edge);
+                        written[from.index()].push(to);
                     }
                 }
             }
@@ -137,7 +218,7 @@ impl TryFrom<Root> for MoleculeGraph {
 }
 
 /// Element
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum Element {
     H,
     He,
@@ -259,13 +340,301 @@ pub enum Element {
     Og,
 }
 
+impl Element {
+    /// Elements by atomic number, from hydrogen
+    pub const ALL: [Self; 118] = [
+        Self::H,
+        Self::He,
+        Self::Li,
+        Self::Be,
+        Self::B,
+        Self::C,
+        Self::N,
+        Self::O,
+        Self::F,
+        Self::Ne,
+        Self::Na,
+        Self::Mg,
+        Self::Al,
+        Self::Si,
+        Self::P,
+        Self::S,
+        Self::Cl,
+        Self::Ar,
+        Self::K,
+        Self::Ca,
+        Self::Sc,
+        Self::Ti,
+        Self::V,
+        Self::Cr,
+        Self::Mn,
+        Self::Fe,
+        Self::Co,
+        Self::Ni,
+        Self::Cu,
+        Self::Zn,
+        Self::Ga,
+        Self::Ge,
+        Self::As,
+        Self::Se,
+        Self::Br,
+        Self::Kr,
+        Self::Rb,
+        Self::Sr,
+        Self::Y,
+        Self::Zr,
+        Self::Nb,
+        Self::Mo,
+        Self::Tc,
+        Self::Ru,
+        Self::Rh,
+        Self::Pd,
+        Self::Ag,
+        Self::Cd,
+        Self::In,
+        Self::Sn,
+        Self::Sb,
+        Self::Te,
+        Self::I,
+        Self::Xe,
+        Self::Cs,
+        Self::Ba,
+        Self::La,
+        Self::Ce,
+        Self::Pr,
+        Self::Nd,
+        Self::Pm,
+        Self::Sm,
+        Self::Eu,
+        Self::Gd,
+        Self::Tb,
+        Self::Dy,
+        Self::Ho,
+        Self::Er,
+        Self::Tm,
+        Self::Yb,
+        Self::Lu,
+        Self::Hf,
+        Self::Ta,
+        Self::W,
+        Self::Re,
+        Self::Os,
+        Self::Ir,
+        Self::Pt,
+        Self::Au,
+        Self::Hg,
+        Self::Tl,
+        Self::Pb,
+        Self::Bi,
+        Self::Po,
+        Self::At,
+        Self::Rn,
+        Self::Fr,
+        Self::Ra,
+        Self::Ac,
+        Self::Th,
+        Self::Pa,
+        Self::U,
+        Self::Np,
+        Self::Pu,
+        Self::Am,
+        Self::Cm,
+        Self::Bk,
+        Self::Cf,
+        Self::Es,
+        Self::Fm,
+        Self::Md,
+        Self::No,
+        Self::Lr,
+        Self::Rf,
+        Self::Db,
+        Self::Sg,
+        Self::Bh,
+        Self::Hs,
+        Self::Mt,
+        Self::Ds,
+        Self::Rg,
+        Self::Cn,
+        Self::Nh,
+        Self::Fl,
+        Self::Mc,
+        Self::Lv,
+        Self::Ts,
+        Self::Og,
+    ];
+
+    /// Normal valences of the organic subset
+    pub fn valences(&self) -> &'static [u8] {
+        match self {
+            Self::B => &[3],
+            Self::C => &[4],
+            Self::N => &[3, 5],
+            Self::O => &[2],
+            Self::P => &[3, 5],
+            Self::S => &[2, 4, 6],
+            Self::F | Self::Cl | Self::Br | Self::I => &[1],
+            _ => &[],
+        }
+    }
+
+    /// Is the element a member of the organic subset
+    pub fn is_organic(&self) -> bool {
+        !self.valences().is_empty()
+    }
+}
+
+impl Display for Element {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        // Variant names are the element symbols
+        write!(f, "{self:?}")
+    }
+}
+
+impl FromStr for Element {
+    type Err = Error;
+
+    /// Parses the element symbol
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "H" => Ok(Self::H),
+            "He" => Ok(Self::He),
+            "Li" => Ok(Self::Li),
+            "Be" => Ok(Self::Be),
+            "B" => Ok(Self::B),
+            "C" => Ok(Self::C),
+            "N" => Ok(Self::N),
+            "O" => Ok(Self::O),
+            "F" => Ok(Self::F),
+            "Ne" => Ok(Self::Ne),
+            "Na" => Ok(Self::Na),
+            "Mg" => Ok(Self::Mg),
+            "Al" => Ok(Self::Al),
+            "Si" => Ok(Self::Si),
+            "P" => Ok(Self::P),
+            "S" => Ok(Self::S),
+            "Cl" => Ok(Self::Cl),
+            "Ar" => Ok(Self::Ar),
+            "K" => Ok(Self::K),
+            "Ca" => Ok(Self::Ca),
+            "Sc" => Ok(Self::Sc),
+            "Ti" => Ok(Self::Ti),
+            "V" => Ok(Self::V),
+            "Cr" => Ok(Self::Cr),
+            "Mn" => Ok(Self::Mn),
+            "Fe" => Ok(Self::Fe),
+            "Co" => Ok(Self::Co),
+            "Ni" => Ok(Self::Ni),
+            "Cu" => Ok(Self::Cu),
+            "Zn" => Ok(Self::Zn),
+            "Ga" => Ok(Self::Ga),
+            "Ge" => Ok(Self::Ge),
+            "As" => Ok(Self::As),
+            "Se" => Ok(Self::Se),
+            "Br" => Ok(Self::Br),
+            "Kr" => Ok(Self::Kr),
+            "Rb" => Ok(Self::Rb),
+            "Sr" => Ok(Self::Sr),
+            "Y" => Ok(Self::Y),
+            "Zr" => Ok(Self::Zr),
+            "Nb" => Ok(Self::Nb),
+            "Mo" => Ok(Self::Mo),
+            "Tc" => Ok(Self::Tc),
+            "Ru" => Ok(Self::Ru),
+            "Rh" => Ok(Self::Rh),
+            "Pd" => Ok(Self::Pd),
+            "Ag" => Ok(Self::Ag),
+            "Cd" => Ok(Self::Cd),
+            "In" => Ok(Self::In),
+            "Sn" => Ok(Self::Sn),
+            "Sb" => Ok(Self::Sb),
+            "Te" => Ok(Self::Te),
+            "I" => Ok(Self::I),
+            "Xe" => Ok(Self::Xe),
+            "Cs" => Ok(Self::Cs),
+            "Ba" => Ok(Self::Ba),
+            "La" => Ok(Self::La),
+            "Ce" => Ok(Self::Ce),
+            "Pr" => Ok(Self::Pr),
+            "Nd" => Ok(Self::Nd),
+            "Pm" => Ok(Self::Pm),
+            "Sm" => Ok(Self::Sm),
+            "Eu" => Ok(Self::Eu),
+            "Gd" => Ok(Self::Gd),
+            "Tb" => Ok(Self::Tb),
+            "Dy" => Ok(Self::Dy),
+            "Ho" => Ok(Self::Ho),
+            "Er" => Ok(Self::Er),
+            "Tm" => Ok(Self::Tm),
+            "Yb" => Ok(Self::Yb),
+            "Lu" => Ok(Self::Lu),
+            "Hf" => Ok(Self::Hf),
+            "Ta" => Ok(Self::Ta),
+            "W" => Ok(Self::W),
+            "Re" => Ok(Self::Re),
+            "Os" => Ok(Self::Os),
+            "Ir" => Ok(Self::Ir),
+            "Pt" => Ok(Self::Pt),
+            "Au" => Ok(Self::Au),
+            "Hg" => Ok(Self::Hg),
+            "Tl" => Ok(Self::Tl),
+            "Pb" => Ok(Self::Pb),
+            "Bi" => Ok(Self::Bi),
+            "Po" => Ok(Self::Po),
+            "At" => Ok(Self::At),
+            "Rn" => Ok(Self::Rn),
+            "Fr" => Ok(Self::Fr),
+            "Ra" => Ok(Self::Ra),
+            "Ac" => Ok(Self::Ac),
+            "Th" => Ok(Self::Th),
+            "Pa" => Ok(Self::Pa),
+            "U" => Ok(Self::U),
+            "Np" => Ok(Self::Np),
+            "Pu" => Ok(Self::Pu),
+            "Am" => Ok(Self::Am),
+            "Cm" => Ok(Self::Cm),
+            "Bk" => Ok(Self::Bk),
+            "Cf" => Ok(Self::Cf),
+            "Es" => Ok(Self::Es),
+            "Fm" => Ok(Self::Fm),
+            "Md" => Ok(Self::Md),
+            "No" => Ok(Self::No),
+            "Lr" => Ok(Self::Lr),
+            "Rf" => Ok(Self::Rf),
+            "Db" => Ok(Self::Db),
+            "Sg" => Ok(Self::Sg),
+            "Bh" => Ok(Self::Bh),
+            "Hs" => Ok(Self::Hs),
+            "Mt" => Ok(Self::Mt),
+            "Ds" => Ok(Self::Ds),
+            "Rg" => Ok(Self::Rg),
+            "Cn" => Ok(Self::Cn),
+            "Nh" => Ok(Self::Nh),
+            "Fl" => Ok(Self::Fl),
+            "Mc" => Ok(Self::Mc),
+            "Lv" => Ok(Self::Lv),
+            "Ts" => Ok(Self::Ts),
+            "Og" => Ok(Self::Og),
+            _ => Err(Error::ElementNotFound),
+        }
+    }
+}
+
 /// Atom
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Atom {
     pub isotope: Option<u16>,
     pub element: Option<Element>,
     pub parity: Option<Parity>,
+    /// Hydrogens count of a bracket atom, `None` for the organic subset
+    pub hydrogens: Option<u8>,
     pub charge: i8,
+    /// Coordinates (Å), kept from a molfile
+    pub coordinates: Option<[f64; 3]>,
+    /// Enhanced stereo group of a stereocenter
+    pub stereo_group: Option<StereoGroup>,
+    /// Atom list query of an atom without element
+    pub list: Option<AtomList>,
+    pub radical: Option<Radical>,
 }
 
 impl TryFrom<Node> for Atom {
@@ -275,14 +644,19 @@ impl TryFrom<Node> for Atom {
         Ok(Atom {
             isotope: isotope(&value)?,
             element: element(&value)?,
-            parity: None,
+            parity: parity(&value),
+            hydrogens: hydrogens(&value)?,
             charge: charge(&value)?,
+            coordinates: None,
+            stereo_group: None,
+            list: None,
+            radical: None,
         })
     }
 }
 
 /// Bond
-#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum Bond {
     #[default]
     Single,
@@ -317,13 +691,42 @@ impl From<Edge> for Bond {
     }
 }
 
+impl Bond {
+    /// Contribution of the bond to the valence of its atoms
+    pub fn valence(&self) -> u8 {
+        match self {
+            Self::Single | Self::Aromatic | Self::Up | Self::Down => 1,
+            Self::Double => 2,
+            Self::Triple => 3,
+            Self::Quadruple => 4,
+        }
+    }
+
+    /// Is the bond a directional single bond
+    pub fn is_directional(&self) -> bool {
+        matches!(self, Self::Up | Self::Down)
+    }
+}
+
 /// Parity
-#[derive(Clone, Copy, Debug)]
+///
+/// Looking from the first neighbour, the others are arranged clockwise
+/// (`@@`) or counterclockwise (`@`).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum Parity {
     Clockwise,
     Counterclockwise,
 }
 
+impl Parity {
+    pub fn inverse(self) -> Self {
+        match self {
+            Self::Clockwise => Self::Counterclockwise,
+            Self::Counterclockwise => Self::Clockwise,
+        }
+    }
+}
+
 fn isotope(node: &Node) -> Result<Option<u16>> {
     match node.node(ISOTOPE) {
         Some(node) => Ok(Some(node.to_smolstr().parse()?)),
@@ -334,131 +737,35 @@ fn isotope(node: &Node) -> Result<Option<u16>> {
 fn element(node: &Node) -> Result<Option<Element>> {
     match node.node(ELEMENT) {
         Some(node) => match &*node.to_smolstr() {
-            "H" => Ok(Some(Element::H)),
-            "He" => Ok(Some(Element::He)),
-            "Li" => Ok(Some(Element::Li)),
-            "Be" => Ok(Some(Element::Be)),
-            "B" => Ok(Some(Element::B)),
-            "C" => Ok(Some(Element::C)),
-            "N" => Ok(Some(Element::N)),
-            "O" => Ok(Some(Element::O)),
-            "F" => Ok(Some(Element::F)),
-            "Ne" => Ok(Some(Element::Ne)),
-            "Na" => Ok(Some(Element::Na)),
-            "Mg" => Ok(Some(Element::Mg)),
-            "Al" => Ok(Some(Element::Al)),
-            "Si" => Ok(Some(Element::Si)),
-            "P" => Ok(Some(Element::P)),
-            "S" => Ok(Some(Element::S)),
-            "Cl" => Ok(Some(Element::Cl)),
-            "Ar" => Ok(Some(Element::Ar)),
-            "K" => Ok(Some(Element::K)),
-            "Ca" => Ok(Some(Element::Ca)),
-            "Sc" => Ok(Some(Element::Sc)),
-            "Ti" => Ok(Some(Element::Ti)),
-            "V" => Ok(Some(Element::V)),
-            "Cr" => Ok(Some(Element::Cr)),
-            "Mn" => Ok(Some(Element::Mn)),
-            "Fe" => Ok(Some(Element::Fe)),
-            "Co" => Ok(Some(Element::Co)),
-            "Ni" => Ok(Some(Element::Ni)),
-            "Cu" => Ok(Some(Element::Cu)),
-            "Zn" => Ok(Some(Element::Zn)),
-            "Ga" => Ok(Some(Element::Ga)),
-            "Ge" => Ok(Some(Element::Ge)),
-            "As" => Ok(Some(Element::As)),
-            "Se" => Ok(Some(Element::Se)),
-            "Br" => Ok(Some(Element::Br)),
-            "Kr" => Ok(Some(Element::Kr)),
-            "Rb" => Ok(Some(Element::Rb)),
-            "Sr" => Ok(Some(Element::Sr)),
-            "Y" => Ok(Some(Element::Y)),
-            "Zr" => Ok(Some(Element::Zr)),
-            "Nb" => Ok(Some(Element::Nb)),
-            "Mo" => Ok(Some(Element::Mo)),
-            "Tc" => Ok(Some(Element::Tc)),
-            "Ru" => Ok(Some(Element::Ru)),
-            "Rh" => Ok(Some(Element::Rh)),
-            "Pd" => Ok(Some(Element::Pd)),
-            "Ag" => Ok(Some(Element::Ag)),
-            "Cd" => Ok(Some(Element::Cd)),
-            "In" => Ok(Some(Element::In)),
-            "Sn" => Ok(Some(Element::Sn)),
-            "Sb" => Ok(Some(Element::Sb)),
-            "Te" => Ok(Some(Element::Te)),
-            "I" => Ok(Some(Element::I)),
-            "Xe" => Ok(Some(Element::Xe)),
-            "Cs" => Ok(Some(Element::Cs)),
-            "Ba" => Ok(Some(Element::Ba)),
-            "La" => Ok(Some(Element::La)),
-            "Ce" => Ok(Some(Element::Ce)),
-            "Pr" => Ok(Some(Element::Pr)),
-            "Nd" => Ok(Some(Element::Nd)),
-            "Pm" => Ok(Some(Element::Pm)),
-            "Sm" => Ok(Some(Element::Sm)),
-            "Eu" => Ok(Some(Element::Eu)),
-            "Gd" => Ok(Some(Element::Gd)),
-            "Tb" => Ok(Some(Element::Tb)),
-            "Dy" => Ok(Some(Element::Dy)),
-            "Ho" => Ok(Some(Element::Ho)),
-            "Er" => Ok(Some(Element::Er)),
-            "Tm" => Ok(Some(Element::Tm)),
-            "Yb" => Ok(Some(Element::Yb)),
-            "Lu" => Ok(Some(Element::Lu)),
-            "Hf" => Ok(Some(Element::Hf)),
-            "Ta" => Ok(Some(Element::Ta)),
-            "W" => Ok(Some(Element::W)),
-            "Re" => Ok(Some(Element::Re)),
-            "Os" => Ok(Some(Element::Os)),
-            "Ir" => Ok(Some(Element::Ir)),
-            "Pt" => Ok(Some(Element::Pt)),
-            "Au" => Ok(Some(Element::Au)),
-            "Hg" => Ok(Some(Element::Hg)),
-            "Tl" => Ok(Some(Element::Tl)),
-            "Pb" => Ok(Some(Element::Pb)),
-            "Bi" => Ok(Some(Element::Bi)),
-            "Po" => Ok(Some(Element::Po)),
-            "At" => Ok(Some(Element::At)),
-            "Rn" => Ok(Some(Element::Rn)),
-            "Fr" => Ok(Some(Element::Fr)),
-            "Ra" => Ok(Some(Element::Ra)),
-            "Ac" => Ok(Some(Element::Ac)),
-            "Th" => Ok(Some(Element::Th)),
-            "Pa" => Ok(Some(Element::Pa)),
-            "U" => Ok(Some(Element::U)),
-            "Np" => Ok(Some(Element::Np)),
-            "Pu" => Ok(Some(Element::Pu)),
-            "Am" => Ok(Some(Element::Am)),
-            "Cm" => Ok(Some(Element::Cm)),
-            "Bk" => Ok(Some(Element::Bk)),
-            "Cf" => Ok(Some(Element::Cf)),
-            "Es" => Ok(Some(Element::Es)),
-            "Fm" => Ok(Some(Element::Fm)),
-            "Md" => Ok(Some(Element::Md)),
-            "No" => Ok(Some(Element::No)),
-            "Lr" => Ok(Some(Element::Lr)),
-            "Rf" => Ok(Some(Element::Rf)),
-            "Db" => Ok(Some(Element::Db)),
-            "Sg" => Ok(Some(Element::Sg)),
-            "Bh" => Ok(Some(Element::Bh)),
-            "Hs" => Ok(Some(Element::Hs)),
-            "Mt" => Ok(Some(Element::Mt)),
-            "Ds" => Ok(Some(Element::Ds)),
-            "Rg" => Ok(Some(Element::Rg)),
-            "Cn" => Ok(Some(Element::Cn)),
-            "Nh" => Ok(Some(Element::Nh)),
-            "Fl" => Ok(Some(Element::Fl)),
-            "Mc" => Ok(Some(Element::Mc)),
-            "Lv" => Ok(Some(Element::Lv)),
-            "Ts" => Ok(Some(Element::Ts)),
-            "Og" => Ok(Some(Element::Og)),
             "*" => Ok(None),
-            _ => unreachable!(),
+            symbol => Ok(Some(symbol.parse()?)),
         },
         None => Err(Error::ElementNotFound),
     }
 }
 
+/// Parity in the written neighbour order
+fn parity(node: &Node) -> Option<Parity> {
+    let parity = node.node(PARITY)?;
+    match parity.children_with_tokens().count() {
+        1 => Some(Parity::Counterclockwise),
+        _ => Some(Parity::Clockwise),
+    }
+}
+
+fn hydrogens(node: &Node) -> Result<Option<u8>> {
+    if node.token(LEFT_BRACKET).is_none() {
+        return Ok(None);
+    }
+    match node.node(HYDROGENS) {
+        Some(hydrogens) => match hydrogens.node(UNSIGNED) {
+            Some(unsigned) => Ok(Some(unsigned.to_smolstr().parse()?)),
+            None => Ok(Some(1)),
+        },
+        None => Ok(Some(0)),
+    }
+}
+
 fn charge(node: &Node) -> Result<i8> {
     match node.node(CHARGE).and_then(|charge| charge.node(SIGNED)) {
         Some(signed) if signed.node(UNSIGNED).is_some() => Ok(signed.to_smolstr().parse()?),
@@ -468,26 +775,10 @@ fn charge(node: &Node) -> Result<i8> {
     }
 }
 
-pub trait Temp {
-    fn delta(self);
-}
-
-impl<F: FilterEdge<EdgeReference>> Temp for EdgeFiltered<&Graph<Atom, Bond, Undirected>, F> {
-    fn delta(self) {
-        let indices: BTreeSet<_> = self
-            .edge_references()
-            .map(|edge| {
-                let index = edge.source().index() + 1;
-                match notation {
-                    Notation::Delta => {
-                        let carbons = graph.carbons().node_identifiers().count();
-                        carbons - index
-                    }
-                    Notation::Omega => index,
-                }
-            })
-            .collect();
-    }
-}
-
+mod canonical;
+mod chain;
 mod error;
+mod extension;
+mod mass;
+mod query;
+mod stereo;
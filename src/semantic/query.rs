@@ -0,0 +1,43 @@
+use super::Element;
+
+/// Atom list query
+///
+/// Matches an atom of any of the elements, or of none of them when
+/// negated.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct AtomList {
+    pub negated: bool,
+    /// Elements by atomic number bit
+    elements: u128,
+}
+
+impl AtomList {
+    pub fn new(negated: bool, elements: impl IntoIterator<Item = Element>) -> Self {
+        Self {
+            negated,
+            elements: elements
+                .into_iter()
+                .fold(0, |elements, element| elements | 1 << element as u8),
+        }
+    }
+
+    /// Listed elements in atomic number order
+    pub fn elements(&self) -> impl Iterator<Item = Element> {
+        let elements = self.elements;
+        Element::ALL
+            .into_iter()
+            .filter(move |&element| elements & 1 << element as u8 != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements == 0
+    }
+
+    pub fn matches(&self, element: Element) -> bool {
+        (self.elements & 1 << element as u8 != 0) != self.negated
+    }
+}
@@ -0,0 +1,169 @@
+use super::{Bond, MoleculeGraph, Parity};
+use petgraph::graph::NodeIndex;
+use std::fmt::{self, Display, Formatter};
+
+/// Double bond geometry
+///
+/// Configuration of two reference neighbours across a double bond: `Z` when
+/// they are on the same side (cis), `E` when they are on opposite sides
+/// (trans).
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Geometry {
+    Z,
+    E,
+}
+
+impl Display for Geometry {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Z => f.write_str("Z"),
+            Self::E => f.write_str("E"),
+        }
+    }
+}
+
+/// Enhanced stereo group of a stereocenter
+///
+/// The centers of an `And` group are a mixture of both configurations, the
+/// centers of an `Or` group have either the configuration or its inverse,
+/// unknown which. Groups of the same kind are told apart by their number.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum StereoGroup {
+    Absolute,
+    And(u16),
+    Or(u16),
+}
+
+impl MoleculeGraph {
+    /// Geometry of the `x` and `y` neighbours across the `a=b` double bond
+    ///
+    /// The geometry is perceived from the directional (`/`, `\`) bonds of
+    /// both double bond atoms. `None` if either side has no directional bond.
+    pub fn geometry(
+        &self,
+        x: NodeIndex,
+        a: NodeIndex,
+        b: NodeIndex,
+        y: NodeIndex,
+    ) -> Option<Geometry> {
+        let left = self.side(a, b, x)?;
+        let right = self.side(b, a, y)?;
+        Some(if left + right == 0 {
+            Geometry::E
+        } else {
+            Geometry::Z
+        })
+    }
+
+    /// Sets directional bonds so that `x` and `y` have the given geometry
+    /// across the `a=b` double bond
+    ///
+    /// An existing directional bond on either side (shared with a conjugated
+    /// double bond) is kept and the other side is derived from it.
+    pub fn set_geometry(
+        &mut self,
+        x: NodeIndex,
+        a: NodeIndex,
+        b: NodeIndex,
+        y: NodeIndex,
+        geometry: Geometry,
+    ) {
+        let sign = match geometry {
+            Geometry::Z => 1,
+            Geometry::E => -1,
+        };
+        match (self.side(a, b, x), self.side(b, a, y)) {
+            (Some(_), Some(_)) => {}
+            (Some(left), None) => self.set_side(b, y, sign * left),
+            (None, Some(right)) => self.set_side(a, x, sign * right),
+            (None, None) => {
+                self.set_side(a, x, 1);
+                self.set_side(b, y, sign);
+            }
+        }
+    }
+
+    /// Parity of the atom with its neighbours taken in the given order
+    ///
+    /// The stored parity refers to the neighbours in ascending index order.
+    /// The atom itself stands for its implicit hydrogen in `order`.
+    pub fn parity(&self, index: NodeIndex, order: &[NodeIndex]) -> Option<Parity> {
+        let parity = self[index].parity?;
+        Some(if self.is_odd(index, order) {
+            parity.inverse()
+        } else {
+            parity
+        })
+    }
+
+    /// Sets the parity of the atom with its neighbours taken in the given
+    /// order
+    ///
+    /// See [`parity`](Self::parity).
+    pub fn set_parity(&mut self, index: NodeIndex, order: &[NodeIndex], parity: Option<Parity>) {
+        let odd = self.is_odd(index, order);
+        self[index].parity = parity.map(|parity| if odd { parity.inverse() } else { parity });
+    }
+
+    /// Is the order an odd permutation of the neighbours in ascending index
+    /// order
+    fn is_odd(&self, index: NodeIndex, order: &[NodeIndex]) -> bool {
+        let mut reference: Vec<_> = self.neighbors(index).collect();
+        if self.hydrogens(index) > 0 {
+            reference.push(index);
+        }
+        reference.sort();
+        let positions: Vec<_> = order
+            .iter()
+            .filter_map(|node| reference.iter().position(|other| other == node))
+            .collect();
+        let mut inversions = 0;
+        for (i, a) in positions.iter().enumerate() {
+            inversions += positions[i + 1..].iter().filter(|&b| b < a).count();
+        }
+        inversions % 2 == 1
+    }
+
+    /// Direction of the `center` side of the double bond to `partner`
+    /// relative to the `reference` neighbour
+    ///
+    /// `+1` when the reference neighbour points "up" looking along the
+    /// directional bond towards the center, `-1` otherwise.
+    fn side(&self, center: NodeIndex, partner: NodeIndex, reference: NodeIndex) -> Option<i8> {
+        let mut neighbors = self
+            .neighbors(center)
+            .filter(|&neighbor| neighbor != partner);
+        let (neighbor, edge) = match self.find_edge(reference, center) {
+            Some(edge) if self[edge].is_directional() => (reference, edge),
+            _ => neighbors.find_map(|neighbor| {
+                let edge = self.find_edge(neighbor, center)?;
+                self[edge].is_directional().then_some((neighbor, edge))
+            })?,
+        };
+        let (source, _) = self.edge_endpoints(edge)?;
+        let mut sign = match self[edge] {
+            Bond::Up => 1,
+            _ => -1,
+        };
+        if source != neighbor {
+            sign = -sign;
+        }
+        if neighbor != reference {
+            sign = -sign;
+        }
+        Some(sign)
+    }
+
+    /// Sets the bond between `center` and `reference` so that the side has
+    /// the given direction
+    fn set_side(&mut self, center: NodeIndex, reference: NodeIndex, sign: i8) {
+        let Some(edge) = self.find_edge(reference, center) else {
+            return;
+        };
+        let Some((source, _)) = self.edge_endpoints(edge) else {
+            return;
+        };
+        let sign = if source == reference { sign } else { -sign };
+        self[edge] = if sign > 0 { Bond::Up } else { Bond::Down };
+    }
+}
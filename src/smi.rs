@@ -0,0 +1,179 @@
+//! SMILES file
+//!
+//! One record per line, the SMILES followed by a terminator (space or tab)
+//! and an optional title. A CXSMILES layer after the terminator is part of
+//! the SMILES.
+
+use crate::{
+    errors::{Error, Result},
+    lines::LossyLines,
+    semantic::MoleculeGraph,
+    syntax::SyntaxKind::END_OF_STRING,
+};
+use std::io::{self, BufRead, Write};
+
+/// SMILES file reader
+///
+/// Iterates the `(molecule, title)` records of any [`BufRead`], the title
+/// empty when the line has none. Blank lines are skipped. A line that fails
+/// to parse is yielded as an error with its line and column, the iteration
+/// goes on with the next line, up to an I/O error.
+pub struct SmiReader<R> {
+    lines: LossyLines<R>,
+    line: usize,
+}
+
+impl<R: BufRead> SmiReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: LossyLines::new(reader),
+            line: 0,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for SmiReader<R> {
+    type Item = Result<(MoleculeGraph, String)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(error) => return Some(Err(error.into())),
+            };
+            self.line += 1;
+            if !line.trim().is_empty() {
+                break line;
+            }
+        };
+        let (mut smiles, mut title) = line.split_once([' ', '\t']).unwrap_or((&line, ""));
+        // The CXSMILES layer belongs to the SMILES
+        if let Some(layer) = title.trim_start().strip_prefix('|') {
+            if let Some(end) = layer.find('|') {
+                let end = line.len() - layer.len() + end + 1;
+                (smiles, title) = line.split_at(end);
+            }
+        }
+        Some(
+            smiles
+                .parse()
+                .map(|graph| (graph, title.trim().to_owned()))
+                .map_err(|error| Error::Smi {
+                    line: self.line,
+                    column: column(&error, smiles),
+                    source: Box::new(error),
+                }),
+        )
+    }
+}
+
+/// Column of the offending lexeme, past the SMILES at its end and the
+/// first column for semantic errors
+fn column(error: &Error, smiles: &str) -> usize {
+    match error {
+        Error::Syntax(error) if error.found.kind == END_OF_STRING => smiles.len() + 1,
+        Error::Syntax(error) => usize::from(error.found.range.start()) + 1,
+        Error::CxSmiles { column, .. } => *column,
+        _ => 1,
+    }
+}
+
+/// SMILES file writer
+pub struct SmiWriter<W> {
+    writer: W,
+}
+
+impl<W: Write> SmiWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Writes a record, the title separated by a space when not empty
+    ///
+    /// The SMILES is followed by its CXSMILES layer when it has one.
+    pub fn write(&mut self, graph: &MoleculeGraph, title: &str) -> io::Result<()> {
+        let smiles = graph.cxsmiles();
+        match title {
+            "" => writeln!(self.writer, "{smiles}"),
+            title => writeln!(self.writer, "{smiles} {title}"),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::lines::test::Failing;
+
+    #[test]
+    fn read() {
+        let smi =
+            "CC(=O)O acetic acid\nN[C@@H](C)C(=O)O\tL-alanine\n\nCCO\nC(C\nC1CC  cyclic\n[NH4+]";
+        let records: Vec<_> = SmiReader::new(smi.as_bytes()).collect();
+        assert_eq!(records.len(), 6);
+        let record = |index: usize| {
+            let (graph, title) = records[index].as_ref().unwrap();
+            (graph.to_string(), title.as_str())
+        };
+        assert_eq!(record(0), ("CC(=O)O".to_owned(), "acetic acid"));
+        assert_eq!(record(1), ("N[C@@H](C)C(=O)O".to_owned(), "L-alanine"));
+        assert_eq!(record(2), ("CCO".to_owned(), ""));
+        assert_eq!(record(5), ("[NH4+]".to_owned(), ""));
+        let error = |index: usize| match &records[index] {
+            Err(Error::Smi { line, column, .. }) => (*line, *column),
+            other => panic!("{other:?}"),
+        };
+        // Unclosed branch at the end of the SMILES
+        assert_eq!(error(3), (5, 4));
+        // Unclosed ring
+        assert_eq!(error(4), (6, 1));
+        // CXSMILES layer before the title
+        let (graph, title) = SmiReader::new(&b"C[C@H](N)C(=O)O |&1:1| rac-alanine"[..])
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(graph.cxsmiles().to_string(), "C[C@H](N)C(=O)O |&1:1|");
+        assert_eq!(title, "rac-alanine");
+        // Error in the layer
+        match SmiReader::new(&b"CC |&1:5| ethane"[..]).next() {
+            Some(Err(Error::Smi { line, column, .. })) => assert_eq!((line, column), (1, 5)),
+            other => panic!("{other:?}"),
+        }
+        // Invalid UTF-8 in a title
+        let records: Vec<_> = SmiReader::new(&b"CCO eth\xffanol\r\nC"[..])
+            .map(|record| record.unwrap().1)
+            .collect();
+        assert_eq!(records, ["eth\u{fffd}anol", ""]);
+        // Failing reader
+        let mut records = SmiReader::new(io::BufReader::new(Failing));
+        assert!(matches!(records.next(), Some(Err(Error::Io(_)))));
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn write() {
+        let mut writer = SmiWriter::new(Vec::new());
+        for (smiles, title) in [("CC(=O)O", "acetic acid"), ("CCO", "")] {
+            writer.write(&smiles.parse().unwrap(), title).unwrap();
+        }
+        let smi = writer.into_inner();
+        assert_eq!(String::from_utf8_lossy(&smi), "CC(=O)O acetic acid\nCCO\n");
+        let titles: Vec<_> = SmiReader::new(&smi[..])
+            .map(|record| record.unwrap().1)
+            .collect();
+        assert_eq!(titles, ["acetic acid", ""]);
+        // CXSMILES layer
+        let line = "C[C@H](N)C(=O)O |&1:1| rac-alanine";
+        let (graph, title) = SmiReader::new(line.as_bytes()).next().unwrap().unwrap();
+        let mut writer = SmiWriter::new(Vec::new());
+        writer.write(&graph, &title).unwrap();
+        let smi = writer.into_inner();
+        assert_eq!(String::from_utf8_lossy(&smi), format!("{line}\n"));
+        let (read, _) = SmiReader::new(&smi[..]).next().unwrap().unwrap();
+        assert_eq!(read.cxsmiles().to_string(), graph.cxsmiles().to_string());
+    }
+}
@@ -51,7 +51,7 @@ pub(crate) type SyntaxNode = rowan::SyntaxNode<Language>;
 
 pub(crate) type SyntaxToken = rowan::SyntaxToken<Language>;
 
-#[allow(non_camel_case_types)]
+#[allow(clippy::upper_case_acronyms, non_camel_case_types)]
 #[derive(Clone, Copy, Debug, Eq, Hash, Logos, Ord, PartialEq, PartialOrd)]
 #[repr(u16)]
 pub enum SyntaxKind {
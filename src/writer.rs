@@ -0,0 +1,246 @@
+use crate::{
+    cxsmiles,
+    semantic::{Bond, MoleculeGraph, Parity},
+};
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use std::{
+    collections::BTreeSet,
+    fmt::{Display, Formatter, Result, Write},
+};
+
+impl Display for MoleculeGraph {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        Writer::new(self).write(f)
+    }
+}
+
+impl MoleculeGraph {
+    /// SMILES followed by the CXSMILES layer, if the graph has any layer
+    /// information
+    pub fn cxsmiles(&self) -> impl Display + '_ {
+        CxSmiles(self)
+    }
+}
+
+/// CXSMILES writer
+struct CxSmiles<'a>(&'a MoleculeGraph);
+
+impl Display for CxSmiles<'_> {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        Writer {
+            extension: true,
+            ..Writer::new(self.0)
+        }
+        .write(f)
+    }
+}
+
+/// SMILES writer
+///
+/// Atoms are visited depth first starting from the lowest index, neighbours
+/// are visited in ascending index order and the last one continues the
+/// chain, the others become branches.
+pub(crate) struct Writer<'a> {
+    graph: &'a MoleculeGraph,
+    /// Position of each atom in the output
+    positions: Vec<Option<usize>>,
+    parents: Vec<Option<NodeIndex>>,
+    children: Vec<Vec<NodeIndex>>,
+    closures: Vec<Vec<EdgeIndex>>,
+    digits: Vec<Option<usize>>,
+    /// Writes the CXSMILES layer
+    extension: bool,
+}
+
+impl<'a> Writer<'a> {
+    pub(crate) fn new(graph: &'a MoleculeGraph) -> Self {
+        Self {
+            graph,
+            positions: vec![None; graph.node_count()],
+            parents: vec![None; graph.node_count()],
+            children: vec![Vec::new(); graph.node_count()],
+            closures: vec![Vec::new(); graph.node_count()],
+            digits: vec![None; graph.edge_count()],
+            extension: false,
+        }
+    }
+
+    pub(crate) fn write(mut self, f: &mut impl Write) -> Result {
+        let mut position = 0;
+        let mut closures = BTreeSet::new();
+        for index in self.graph.node_indices() {
+            if self.positions[index.index()].is_none() {
+                self.visit(index, &mut position, &mut closures);
+            }
+        }
+        // Ring closures of an atom are written in the order of their partners
+        let mut partners = vec![Vec::new(); self.graph.node_count()];
+        for edge in closures {
+            let (source, target) = self.graph.edge_endpoints(edge).unwrap();
+            partners[source.index()].push((self.positions[target.index()], edge));
+            partners[target.index()].push((self.positions[source.index()], edge));
+        }
+        for (closures, mut partners) in self.closures.iter_mut().zip(partners) {
+            partners.sort();
+            *closures = partners.into_iter().map(|(_, edge)| edge).collect();
+        }
+        let mut first = true;
+        for index in self.graph.node_indices() {
+            if self.parents[index.index()].is_none() && self.positions[index.index()].is_some() {
+                if !first {
+                    f.write_char('.')?;
+                }
+                first = false;
+                self.tree(f, index)?;
+            }
+        }
+        if self.extension {
+            let positions: Vec<_> = self.positions.iter().flatten().copied().collect();
+            cxsmiles::write(f, self.graph, &positions)?;
+        }
+        Ok(())
+    }
+
+    /// Depth first search assigning positions, spanning tree and ring
+    /// closures
+    fn visit(
+        &mut self,
+        index: NodeIndex,
+        position: &mut usize,
+        closures: &mut BTreeSet<EdgeIndex>,
+    ) {
+        self.positions[index.index()] = Some(*position);
+        *position += 1;
+        let mut neighbors: Vec<_> = self.graph.neighbors(index).collect();
+        neighbors.sort();
+        neighbors.dedup();
+        for neighbor in neighbors {
+            if Some(neighbor) == self.parents[index.index()] {
+                continue;
+            }
+            if self.positions[neighbor.index()].is_none() {
+                self.parents[neighbor.index()] = Some(index);
+                self.children[index.index()].push(neighbor);
+                self.visit(neighbor, position, closures);
+            } else if let Some(edge) = self.graph.find_edge(index, neighbor) {
+                closures.insert(edge);
+            }
+        }
+    }
+
+    fn tree(&mut self, f: &mut impl Write, index: NodeIndex) -> Result {
+        self.atom(f, index)?;
+        for edge in self.closures[index.index()].clone() {
+            match self.digits[edge.index()].take() {
+                Some(digit) => write_digit(f, digit)?,
+                None => {
+                    let used: BTreeSet<_> = self.digits.iter().flatten().copied().collect();
+                    let digit = (1..).find(|digit| !used.contains(digit)).unwrap();
+                    self.digits[edge.index()] = Some(digit);
+                    self.bond(f, edge, index)?;
+                    write_digit(f, digit)?;
+                }
+            }
+        }
+        let children = self.children[index.index()].clone();
+        if let Some((last, branches)) = children.split_last() {
+            for &child in branches {
+                f.write_char('(')?;
+                let edge = self.graph.find_edge(index, child).unwrap();
+                self.bond(f, edge, index)?;
+                self.tree(f, child)?;
+                f.write_char(')')?;
+            }
+            let edge = self.graph.find_edge(index, *last).unwrap();
+            self.bond(f, edge, index)?;
+            self.tree(f, *last)?;
+        }
+        Ok(())
+    }
+
+    fn atom(&self, f: &mut impl Write, index: NodeIndex) -> Result {
+        let atom = &self.graph[index];
+        let hydrogens = self.graph.hydrogens(index);
+        let organic = atom.element.is_none_or(|element| element.is_organic());
+        if organic
+            && atom.isotope.is_none()
+            && atom.charge == 0
+            && atom.parity.is_none()
+            && hydrogens == self.graph.implicit_hydrogens(index)
+        {
+            return match atom.element {
+                Some(element) => write!(f, "{element}"),
+                None => f.write_char('*'),
+            };
+        }
+        f.write_char('[')?;
+        if let Some(isotope) = atom.isotope {
+            write!(f, "{isotope}")?;
+        }
+        match atom.element {
+            Some(element) => write!(f, "{element}")?,
+            None => f.write_char('*')?,
+        }
+        if let Some(parity) = self.parity(index, hydrogens) {
+            match parity {
+                Parity::Counterclockwise => f.write_str("@")?,
+                Parity::Clockwise => f.write_str("@@")?,
+            }
+        }
+        match hydrogens {
+            0 => {}
+            1 => f.write_char('H')?,
+            _ => write!(f, "H{hydrogens}")?,
+        }
+        match atom.charge {
+            0 => {}
+            1 => f.write_char('+')?,
+            -1 => f.write_char('-')?,
+            charge => write!(f, "{charge:+}")?,
+        }
+        f.write_char(']')
+    }
+
+    /// Parity in the written neighbour order
+    fn parity(&self, index: NodeIndex, hydrogens: u8) -> Option<Parity> {
+        let hydrogen = (hydrogens > 0).then_some(index);
+        let written: Vec<_> = self.parents[index.index()]
+            .into_iter()
+            .chain(hydrogen)
+            .chain(self.closures[index.index()].iter().map(|&edge| {
+                let (source, target) = self.graph.edge_endpoints(edge).unwrap();
+                if source == index {
+                    target
+                } else {
+                    source
+                }
+            }))
+            .chain(self.children[index.index()].iter().copied())
+            .collect();
+        self.graph.parity(index, &written)
+    }
+
+    /// Bond written from the `from` atom
+    fn bond(&self, f: &mut impl Write, edge: EdgeIndex, from: NodeIndex) -> Result {
+        let (source, _) = self.graph.edge_endpoints(edge).unwrap();
+        let forward = source == from;
+        match self.graph[edge] {
+            Bond::Single => Ok(()),
+            Bond::Double => f.write_char('='),
+            Bond::Triple => f.write_char('#'),
+            Bond::Quadruple => f.write_char('$'),
+            Bond::Aromatic => f.write_char(':'),
+            Bond::Up if forward => f.write_char('/'),
+            Bond::Down if !forward => f.write_char('/'),
+            Bond::Up | Bond::Down => f.write_char('\\'),
+        }
+    }
+}
+
+fn write_digit(f: &mut impl Write, digit: usize) -> Result {
+    if digit < 10 {
+        write!(f, "{digit}")
+    } else {
+        write!(f, "%{digit}")
+    }
+}